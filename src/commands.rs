@@ -1,5 +1,7 @@
 use crate::validate::Dialect;
+use serde::{Deserialize, Serialize};
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct ParameterInfo {
     pub allow_plural: bool,
     pub allow_gender: bool,
@@ -12,22 +14,100 @@ pub enum Occurence {
     EXACT,   //< Command must match exactly with base.
 }
 
+#[derive(Serialize, Debug, Clone, Copy)]
 pub struct CommandInfo<'a> {
     pub name: &'a str,
     pub norm_name: Option<&'a str>,
     pub dialects: &'a [Dialect],
     pub occurence: Occurence,
     pub allow_case: bool,
+    pub count_like: bool, //< Parameters represent an amount, which plurals can agree with.
+    pub sets_colour: bool, //< Command changes the text colour.
+    pub sets_font: bool,  //< Command changes the font size, like '{TINY_FONT}'.
+    pub is_space_like: bool, //< Command prints a blank, like a non-breaking space.
+    pub requires_following: bool, //< Command modifies the next command, which must be a number, like '{NUM}'. No built-in command of any dialect is such a modifier, only custom commands.
     pub def_plural_subindex: Option<usize>,
     pub parameters: &'a [ParameterInfo],
 }
 
+/// String command, which is not built-in, but added by the language configuration.
+#[derive(Deserialize, Debug, Clone)]
+pub struct CustomCommand {
+    pub name: String,
+    pub occurence: Occurence,
+    #[serde(default)]
+    pub allow_case: bool,
+    #[serde(default)]
+    pub count_like: bool, //< Parameters represent an amount, which plurals can agree with.
+    #[serde(default)]
+    pub sets_colour: bool, //< Command changes the text colour.
+    #[serde(default)]
+    pub sets_font: bool, //< Command changes the font size, like '{TINY_FONT}'.
+    #[serde(default)]
+    pub is_space_like: bool, //< Command prints a blank, like a non-breaking space.
+    #[serde(default)]
+    pub requires_following: bool, //< Command modifies the next command, which must be a number, like '{NUM}'.
+    #[serde(default)]
+    pub parameters: Vec<ParameterInfo>,
+}
+
+impl Serialize for Occurence {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(match self {
+            Self::ANY => "any",
+            Self::NONZERO => "nonzero",
+            Self::EXACT => "exact",
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for Occurence {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let string = String::deserialize(deserializer)?;
+        match string.as_str() {
+            "any" => Ok(Self::ANY),
+            "nonzero" => Ok(Self::NONZERO),
+            "exact" => Ok(Self::EXACT),
+            _ => Err(serde::de::Error::unknown_variant(
+                string.as_str(),
+                &["any", "nonzero", "exact"],
+            )),
+        }
+    }
+}
+
 impl<'a> CommandInfo<'a> {
     pub fn get_norm_name(&self) -> &'a str {
         self.norm_name.unwrap_or(self.name)
     }
 }
 
+impl CustomCommand {
+    /// Describe the custom command like a built-in command of any dialect.
+    pub fn info(&self) -> CommandInfo<'_> {
+        CommandInfo {
+            name: &self.name,
+            norm_name: None,
+            dialects: DNGO,
+            occurence: self.occurence,
+            allow_case: self.allow_case,
+            count_like: self.count_like,
+            sets_colour: self.sets_colour,
+            sets_font: self.sets_font,
+            is_space_like: self.is_space_like,
+            requires_following: self.requires_following,
+            def_plural_subindex: None,
+            parameters: &self.parameters,
+        }
+    }
+}
+
 const P__: ParameterInfo = ParameterInfo {
     allow_plural: false,
     allow_gender: false,
@@ -58,6 +138,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DNGO,
         occurence: Occurence::ANY,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: true,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -67,6 +152,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DNGO,
         occurence: Occurence::ANY,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -77,6 +167,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::ANY,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -86,6 +181,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::ANY,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -95,6 +195,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::ANY,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -104,6 +209,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::ANY,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -113,6 +223,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::ANY,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -122,6 +237,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::ANY,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -131,6 +251,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::ANY,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -141,6 +266,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DNGO,
         occurence: Occurence::ANY,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -150,6 +280,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DNGO,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -159,6 +294,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DNGO,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -168,6 +308,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DNGO,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -177,6 +322,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DNGO,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -186,6 +336,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DNGO,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -195,6 +350,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DNGO,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -207,6 +367,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D__O,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -216,6 +381,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D__O,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -225,6 +395,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D__O,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -234,6 +409,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D__O,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -243,6 +423,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D__O,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -252,6 +437,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D__O,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -261,6 +451,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D__O,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -271,6 +466,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D__O,
         occurence: Occurence::ANY,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -280,6 +480,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D__O,
         occurence: Occurence::ANY,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -289,6 +494,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D__O,
         occurence: Occurence::ANY,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -298,6 +508,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D__O,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[P__],
     },
@@ -308,6 +523,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: true,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -317,6 +537,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DNGO,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: true,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -326,6 +551,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DNGO,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: true,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -335,6 +565,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: true,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -345,6 +580,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DNGO,
         occurence: Occurence::NONZERO,
         allow_case: false,
+        count_like: false,
+        sets_colour: true,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -354,6 +594,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DNGO,
         occurence: Occurence::NONZERO,
         allow_case: false,
+        count_like: false,
+        sets_colour: true,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -363,6 +608,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DNGO,
         occurence: Occurence::NONZERO,
         allow_case: false,
+        count_like: false,
+        sets_colour: true,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -372,6 +622,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DNGO,
         occurence: Occurence::NONZERO,
         allow_case: false,
+        count_like: false,
+        sets_colour: true,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -381,6 +636,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DNGO,
         occurence: Occurence::NONZERO,
         allow_case: false,
+        count_like: false,
+        sets_colour: true,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -390,6 +650,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DNGO,
         occurence: Occurence::NONZERO,
         allow_case: false,
+        count_like: false,
+        sets_colour: true,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -399,6 +664,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DNGO,
         occurence: Occurence::NONZERO,
         allow_case: false,
+        count_like: false,
+        sets_colour: true,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -408,6 +678,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DNGO,
         occurence: Occurence::NONZERO,
         allow_case: false,
+        count_like: false,
+        sets_colour: true,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -417,6 +692,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DNGO,
         occurence: Occurence::NONZERO,
         allow_case: false,
+        count_like: false,
+        sets_colour: true,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -426,6 +706,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DNGO,
         occurence: Occurence::NONZERO,
         allow_case: false,
+        count_like: false,
+        sets_colour: true,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -435,6 +720,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DNGO,
         occurence: Occurence::NONZERO,
         allow_case: false,
+        count_like: false,
+        sets_colour: true,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -444,6 +734,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DNGO,
         occurence: Occurence::NONZERO,
         allow_case: false,
+        count_like: false,
+        sets_colour: true,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -453,6 +748,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DNGO,
         occurence: Occurence::NONZERO,
         allow_case: false,
+        count_like: false,
+        sets_colour: true,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -462,6 +762,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DNGO,
         occurence: Occurence::NONZERO,
         allow_case: false,
+        count_like: false,
+        sets_colour: true,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -471,6 +776,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DNGO,
         occurence: Occurence::NONZERO,
         allow_case: false,
+        count_like: false,
+        sets_colour: true,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -480,6 +790,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DNGO,
         occurence: Occurence::NONZERO,
         allow_case: false,
+        count_like: false,
+        sets_colour: true,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -489,16 +804,25 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DNGO,
         occurence: Occurence::NONZERO,
         allow_case: false,
+        count_like: false,
+        sets_colour: true,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
-    // TODO check that push and pop are balanced
     CommandInfo {
         name: "PUSH_COLOUR",
         norm_name: None,
         dialects: DNGO,
         occurence: Occurence::ANY,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -508,6 +832,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DNGO,
         occurence: Occurence::ANY,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[],
     },
@@ -517,6 +846,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::NONZERO,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[P__],
     },
@@ -526,6 +860,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DN__,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[P__],
     },
@@ -536,6 +875,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DNGO,
         occurence: Occurence::NONZERO,
         allow_case: true,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[P_G],
     },
@@ -545,6 +889,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::NONZERO,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[P_G],
     },
@@ -554,6 +903,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::NONZERO,
         allow_case: true,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[P_G, PPG],
     },
@@ -563,6 +917,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::NONZERO,
         allow_case: true,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[P_G, PPG, PPG],
     },
@@ -572,6 +931,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::NONZERO,
         allow_case: true,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[P_G, PPG, PPG, PPG],
     },
@@ -581,6 +945,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::NONZERO,
         allow_case: true,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[P_G, PPG, PPG, PPG, PPG],
     },
@@ -590,6 +959,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::NONZERO,
         allow_case: true,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[P_G, PPG, PPG, PPG, PPG, PPG],
     },
@@ -599,6 +973,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::NONZERO,
         allow_case: true,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[P_G, PPG, PPG, PPG, PPG, PPG, PPG],
     },
@@ -608,6 +987,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::NONZERO,
         allow_case: true,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[P_G, PPG, PPG, PPG, PPG, PPG, PPG, PPG],
     },
@@ -618,6 +1002,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DNGO,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: true,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: Some(0),
         parameters: &[PP_],
     },
@@ -627,6 +1016,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DN__,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: true,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: Some(0),
         parameters: &[PP_],
     },
@@ -636,6 +1030,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DN__,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: true,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: Some(0),
         parameters: &[PP_],
     },
@@ -645,6 +1044,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DNGO,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: Some(0),
         parameters: &[PP_],
     },
@@ -654,6 +1058,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: true,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: Some(0),
         parameters: &[PP_],
     },
@@ -664,6 +1073,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: true,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: Some(0),
         parameters: &[PP_, P__],
     },
@@ -673,6 +1087,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: true,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: Some(0),
         parameters: &[PP_, P__],
     },
@@ -683,6 +1102,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: true,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: Some(0),
         parameters: &[PP_],
     },
@@ -692,6 +1116,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: true,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: Some(0),
         parameters: &[PP_],
     },
@@ -701,6 +1130,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DN__,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: Some(0),
         parameters: &[PP_],
     },
@@ -710,6 +1144,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: Some(0),
         parameters: &[PP_],
     },
@@ -719,6 +1158,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: Some(0),
         parameters: &[PP_],
     },
@@ -728,6 +1172,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DNGO,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: true,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: Some(0),
         parameters: &[PP_],
     },
@@ -737,6 +1186,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DN__,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: true,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: Some(0),
         parameters: &[PP_],
     },
@@ -746,6 +1200,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: true,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: Some(0),
         parameters: &[PP_],
     },
@@ -755,6 +1214,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DNGO,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: true,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: Some(0),
         parameters: &[PP_],
     },
@@ -764,6 +1228,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: true,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: Some(0),
         parameters: &[PP_],
     },
@@ -773,6 +1242,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DNGO,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: true,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: Some(0),
         parameters: &[PP_],
     },
@@ -782,6 +1256,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: true,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: Some(0),
         parameters: &[PP_],
     },
@@ -791,6 +1270,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DN__,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: true,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: Some(0),
         parameters: &[PP_],
     },
@@ -800,6 +1284,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: true,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: Some(0),
         parameters: &[PP_],
     },
@@ -809,6 +1298,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DNGO,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: true,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: Some(0),
         parameters: &[PP_],
     },
@@ -818,6 +1312,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: true,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: Some(0),
         parameters: &[PPG],
     },
@@ -827,6 +1326,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: true,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: Some(0),
         parameters: &[PPG],
     },
@@ -836,6 +1340,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: true,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: Some(0),
         parameters: &[PPG],
     },
@@ -845,6 +1354,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: true,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: Some(0),
         parameters: &[PPG],
     },
@@ -855,6 +1369,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DNGO,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: true,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: Some(1),
         parameters: &[P_G, PP_],
     },
@@ -864,6 +1383,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DNGO,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: true,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: Some(1),
         parameters: &[P_G, PP_],
     },
@@ -873,6 +1397,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DNGO,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: true,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: Some(1),
         parameters: &[P__, PP_],
     },
@@ -883,6 +1412,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DN__,
         occurence: Occurence::EXACT,
         allow_case: true,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[P__],
     },
@@ -892,6 +1426,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DN__,
         occurence: Occurence::EXACT,
         allow_case: true,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[P__],
     },
@@ -901,6 +1440,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DNGO,
         occurence: Occurence::EXACT,
         allow_case: true,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[P__],
     },
@@ -910,6 +1454,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DNGO,
         occurence: Occurence::EXACT,
         allow_case: true,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[P__],
     },
@@ -919,6 +1468,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[P__],
     },
@@ -928,6 +1482,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::EXACT,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[P__],
     },
@@ -938,6 +1497,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DN__,
         occurence: Occurence::NONZERO,
         allow_case: true,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[P_G],
     },
@@ -947,6 +1511,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::EXACT,
         allow_case: true,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[P__],
     },
@@ -956,6 +1525,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::NONZERO,
         allow_case: true,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[P_G],
     },
@@ -965,6 +1539,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::NONZERO,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[P_G],
     },
@@ -974,6 +1553,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: DNGO,
         occurence: Occurence::NONZERO,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[P_G],
     },
@@ -983,6 +1567,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::NONZERO,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[P_G, P__],
     },
@@ -992,6 +1581,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::NONZERO,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[P_G],
     },
@@ -1001,6 +1595,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::NONZERO,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[P_G],
     },
@@ -1010,6 +1609,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::NONZERO,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[P_G],
     },
@@ -1019,6 +1623,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::NONZERO,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[P_G],
     },
@@ -1028,6 +1637,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::NONZERO,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[P_G],
     },
@@ -1037,6 +1651,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::NONZERO,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[P_G],
     },
@@ -1046,6 +1665,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::NONZERO,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[P__],
     },
@@ -1055,6 +1679,11 @@ pub const COMMANDS: &'static [CommandInfo] = &[
         dialects: D_GO,
         occurence: Occurence::NONZERO,
         allow_case: false,
+        count_like: false,
+        sets_colour: false,
+        sets_font: false,
+        is_space_like: false,
+        requires_following: false,
         def_plural_subindex: None,
         parameters: &[P_G],
     },
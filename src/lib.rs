@@ -2,14 +2,73 @@ use serde_wasm_bindgen;
 use wasm_bindgen::prelude::*;
 
 mod commands;
-mod parser;
+pub mod parser;
 pub mod validate;
 
+/// Convert a response to a JS value. If that fails, return the JS error instead of panicking.
+fn to_js<T: serde::Serialize>(response: &T) -> JsValue {
+    serde_wasm_bindgen::to_value(response).unwrap_or_else(JsValue::from)
+}
+
+/// Convert the JS arguments and compute the response in 'wrapper', and convert the response or error to a JS value.
+fn respond<T: serde::Serialize>(wrapper: impl FnOnce() -> Result<T, JsValue>) -> JsValue {
+    match wrapper() {
+        Ok(response) => to_js(&response),
+        Err(err) => err,
+    }
+}
+
+/// Report an invalid argument as validation result with a single error, instead of panicking.
+fn invalid_argument(
+    code: validate::ErrorCode,
+    name: &str,
+    err: serde_wasm_bindgen::Error,
+) -> JsValue {
+    let response = validate::ValidationResult {
+        errors: vec![validate::ValidationError {
+            severity: validate::Severity::Error,
+            code,
+            pos_begin: None,
+            pos_end: None,
+            message: format!("Invalid {}: {}", name, err),
+            suggestion: Some(format!("Check the {}.", name)),
+        }],
+        normalized: None,
+    };
+    to_js(&response)
+}
+
+/// Convert the language configuration, or report it as invalid.
+fn config_from_js(js_config: JsValue) -> Result<validate::LanguageConfig, JsValue> {
+    serde_wasm_bindgen::from_value(js_config).map_err(|err| {
+        invalid_argument(
+            validate::ErrorCode::InvalidConfig,
+            "language configuration",
+            err,
+        )
+    })
+}
+
+/// Convert another argument, like a list of strings, or report it as invalid. 'name' describes the argument.
+fn from_js<T: serde::de::DeserializeOwned>(value: JsValue, name: &str) -> Result<T, JsValue> {
+    serde_wasm_bindgen::from_value(value)
+        .map_err(|err| invalid_argument(validate::ErrorCode::InvalidArgument, name, err))
+}
+
+#[wasm_bindgen]
+pub fn validate_config(js_config: JsValue) -> JsValue {
+    respond(|| {
+        let config = config_from_js(js_config)?;
+        Ok(validate::validate_config(&config))
+    })
+}
+
 #[wasm_bindgen]
 pub fn validate_base(js_config: JsValue, base: String) -> JsValue {
-    let config: validate::LanguageConfig = serde_wasm_bindgen::from_value(js_config).unwrap();
-    let response = validate::validate_base(&config, &base);
-    serde_wasm_bindgen::to_value(&response).unwrap()
+    respond(|| {
+        let config = config_from_js(js_config)?;
+        Ok(validate::validate_base(&config, &base))
+    })
 }
 
 #[wasm_bindgen]
@@ -19,9 +78,224 @@ pub fn validate_translation(
     case: String,
     translation: String,
 ) -> JsValue {
-    let config: validate::LanguageConfig = serde_wasm_bindgen::from_value(js_config).unwrap();
-    let response = validate::validate_translation(&config, &base, &case, &translation);
-    serde_wasm_bindgen::to_value(&response).unwrap()
+    respond(|| {
+        let config = config_from_js(js_config)?;
+        Ok(validate::validate_translation(
+            &config,
+            &base,
+            &case,
+            &translation,
+        ))
+    })
+}
+
+#[wasm_bindgen]
+pub fn validate_pair(
+    js_config: JsValue,
+    base: String,
+    case: String,
+    translation: String,
+) -> JsValue {
+    respond(|| {
+        let config = config_from_js(js_config)?;
+        Ok(validate::validate_pair(&config, &base, &case, &translation))
+    })
+}
+
+#[wasm_bindgen]
+pub fn validate_translation_any(
+    js_config: JsValue,
+    js_bases: JsValue,
+    case: String,
+    translation: String,
+) -> JsValue {
+    respond(|| {
+        let config = config_from_js(js_config)?;
+        let bases: Vec<String> = from_js(js_bases, "list of base strings")?;
+        Ok(validate::validate_translation_any(
+            &config,
+            &bases,
+            &case,
+            &translation,
+        ))
+    })
+}
+
+#[wasm_bindgen]
+pub fn validate_translation_batch(
+    js_config: JsValue,
+    base: String,
+    js_translations: JsValue,
+) -> JsValue {
+    respond(|| {
+        let config = config_from_js(js_config)?;
+        let translations: Vec<(String, String)> =
+            from_js(js_translations, "list of cases and translations")?;
+        Ok(validate::validate_translation_batch(
+            &config,
+            &base,
+            &translations,
+        ))
+    })
+}
+
+#[wasm_bindgen]
+pub fn annotate_commands(js_config: JsValue, base: Option<String>, string: String) -> JsValue {
+    // the response borrows from the configuration, so it is converted here
+    let config = match config_from_js(js_config) {
+        Ok(config) => config,
+        Err(err) => return err,
+    };
+    let response = validate::annotate_commands(&config, base.as_deref(), &string);
+    to_js(&response)
+}
+
+#[wasm_bindgen]
+pub fn would_normalize_change(js_config: JsValue, string: String) -> JsValue {
+    respond(|| {
+        let config = config_from_js(js_config)?;
+        Ok(validate::would_normalize_change(&config, &string))
+    })
+}
+
+#[wasm_bindgen]
+pub fn normalize_batch(js_config: JsValue, js_strings: JsValue) -> JsValue {
+    respond(|| {
+        let config = config_from_js(js_config)?;
+        let strings: Vec<String> = from_js(js_strings, "list of strings")?;
+        Ok(validate::normalize_batch(&config, &strings))
+    })
+}
+
+#[wasm_bindgen]
+pub fn command_diff(js_config: JsValue, base: String, translation: String) -> JsValue {
+    respond(|| {
+        let config = config_from_js(js_config)?;
+        Ok(validate::command_diff(&config, &base, &translation))
+    })
+}
+
+#[wasm_bindgen]
+pub fn signature_diff(js_config: JsValue, base: String, translation: String) -> JsValue {
+    respond(|| {
+        let config = config_from_js(js_config)?;
+        Ok(validate::signature_diff(&config, &base, &translation))
+    })
+}
+
+#[wasm_bindgen]
+pub fn base_signature(js_config: JsValue, base: String) -> JsValue {
+    respond(|| {
+        let config = config_from_js(js_config)?;
+        Ok(validate::base_signature(&config, &base))
+    })
+}
+
+#[wasm_bindgen]
+pub fn validate_base_all_dialects(js_config: JsValue, base: String) -> JsValue {
+    respond(|| {
+        let config = config_from_js(js_config)?;
+        Ok(validate::validate_base_all_dialects(&config, &base))
+    })
+}
+
+#[wasm_bindgen]
+pub fn coverage_report(js_config: JsValue, base: String, translation: String) -> JsValue {
+    // the response borrows from the configuration, so it is converted here
+    let config = match config_from_js(js_config) {
+        Ok(config) => config,
+        Err(err) => return err,
+    };
+    let response = validate::coverage_report(&config, &base, &translation);
+    to_js(&response)
+}
+
+#[wasm_bindgen]
+pub fn validate_case_consistency(js_config: JsValue, js_translations: JsValue) -> JsValue {
+    respond(|| {
+        let config = config_from_js(js_config)?;
+        let translations: Vec<(String, String)> =
+            from_js(js_translations, "list of cases and translations")?;
+        Ok(validate::validate_case_consistency(&config, &translations))
+    })
+}
+
+#[wasm_bindgen]
+pub fn validate_substring_gender(
+    js_config: JsValue,
+    host: String,
+    pos: usize,
+    substring: String,
+) -> JsValue {
+    respond(|| {
+        let config = config_from_js(js_config)?;
+        Ok(validate::validate_substring_gender(
+            &config, &host, pos, &substring,
+        ))
+    })
+}
+
+#[wasm_bindgen]
+pub fn list_commands(js_dialect: JsValue) -> JsValue {
+    respond(|| {
+        let dialect: validate::Dialect = from_js(js_dialect, "dialect")?;
+        Ok(validate::list_commands(&dialect))
+    })
+}
+
+#[wasm_bindgen]
+pub fn suggest_command(js_config: JsValue, name: String) -> JsValue {
+    respond(|| {
+        let config = config_from_js(js_config)?;
+        Ok(validate::suggest_command(&config, &name))
+    })
+}
+
+#[wasm_bindgen]
+pub fn newline_info(js_dialect: JsValue) -> JsValue {
+    respond(|| {
+        let dialect: validate::Dialect = from_js(js_dialect, "dialect")?;
+        Ok(validate::newline_info(&dialect))
+    })
+}
+
+#[wasm_bindgen]
+pub fn explain(js_config: JsValue, string: String) -> JsValue {
+    respond(|| {
+        let config = config_from_js(js_config)?;
+        Ok(validate::explain(&config, &string))
+    })
+}
+
+#[wasm_bindgen]
+pub fn validation_summary(js_result: JsValue) -> JsValue {
+    respond(|| {
+        let result: validate::ValidationResult = from_js(js_result, "validation result")?;
+        Ok(result.summary())
+    })
+}
+
+#[wasm_bindgen]
+pub fn plural_count_for_language(code: String) -> Option<usize> {
+    validate::plural_count_for_language(&code)
+}
+
+#[wasm_bindgen]
+pub fn parse_string(string: String) -> JsValue {
+    let response = parser::ParsedString::parse(&string);
+    to_js(&response)
+}
+
+#[wasm_bindgen]
+pub fn plain_text(string: String) -> Option<String> {
+    parser::ParsedString::parse(&string)
+        .ok()
+        .map(|parsed| parsed.to_plain_text())
+}
+
+#[wasm_bindgen]
+pub fn escape_choice_value(value: String) -> Option<String> {
+    parser::is_valid_choice_value(&value).then(|| parser::escape_choice_value(&value))
 }
 
 #[wasm_bindgen]
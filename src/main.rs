@@ -1,49 +1,294 @@
-use clap::Parser;
+use clap::{CommandFactory, Parser};
+use nile_library::validate;
+use std::io::BufRead;
 
-mod commands;
-mod parser;
-mod validate;
+#[derive(clap::ValueEnum, Clone, Debug)]
+enum Format {
+    Text, //< human-readable lines.
+    Json, //< the ValidationResult as JSON object.
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+enum MinSeverity {
+    Error,   //< only errors.
+    Warning, //< errors and warnings.
+    Info,    //< everything.
+}
+
+#[derive(Clone, Debug)]
+enum DialectArg {
+    One(validate::Dialect), //< strings of this dialect.
+    All,                    //< list for each command of the base, which dialects reject it.
+}
+
+impl std::str::FromStr for DialectArg {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "all" => Ok(DialectArg::All),
+            _ => value
+                .parse()
+                .map(DialectArg::One)
+                .map_err(|err| format!("{}, or 'all'", err)),
+        }
+    }
+}
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Args {
-    base: String,
+    /// Base string, or '-' to validate each line of stdin as base string.
+    #[clap(required_unless_present_any = ["stdin", "list_commands"])]
+    base: Option<String>,
+    #[clap(conflicts_with = "stdin")]
     translation: Option<String>,
     case: Option<String>,
 
-    #[clap(short, long, default_value_t = String::from("openttd"))]
-    dialect: String,
+    /// Validate each line of stdin as separate string, and output one JSON result per line.
+    /// If <BASE> is given, the lines are validated as translations of it.
+    #[clap(long)]
+    stdin: bool,
+
+    /// List the string commands of the dialect, instead of validating a string.
+    #[clap(long, conflicts_with_all = ["base", "stdin"])]
+    list_commands: bool,
+
+    /// Output format.
+    #[clap(short, long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+
+    /// Only output messages of this severity or higher. The exit status is non-zero, if any message is output.
+    #[clap(long, value_enum, default_value_t = MinSeverity::Warning)]
+    min_severity: MinSeverity,
+
+    /// Do not output the normalized string.
+    #[clap(long)]
+    no_normalize: bool,
+
+    /// Dialect of the strings, or 'all' to list for each command of <BASE>, which dialects reject it.
+    #[clap(short, long, default_value = "openttd")]
+    dialect: DialectArg,
     #[clap(short, long)]
     cases: Vec<String>,
     #[clap(short, long)]
     genders: Vec<String>,
     #[clap(short, long, default_value_t = 2)]
     plural_count: usize,
+    /// Isocode of the language, like 'en_GB', to derive the plural count from.
+    #[clap(short, long, conflicts_with = "plural_count")]
+    language: Option<String>,
 }
 
 fn main() {
     let args = Args::parse();
+    let all_dialects = matches!(args.dialect, DialectArg::All);
+    let dialect = match args.dialect {
+        DialectArg::One(dialect) => dialect,
+        DialectArg::All => validate::Dialect::default(),
+    };
+    if all_dialects
+        && (args.list_commands
+            || args.stdin
+            || args.translation.is_some()
+            || args.base.as_deref() == Some("-"))
+    {
+        Args::command()
+            .error(
+                clap::error::ErrorKind::ArgumentConflict,
+                "'--dialect all' only checks a single base string.",
+            )
+            .exit()
+    }
+    if args.list_commands {
+        print_commands(&dialect, &args.format);
+        return;
+    }
+
+    let plural_count = match &args.language {
+        Some(language) => validate::plural_count_for_language(language).unwrap_or_else(|| {
+            Args::command()
+                .error(
+                    clap::error::ErrorKind::InvalidValue,
+                    format!(
+                        "Unknown language '{}', use --plural-count instead.",
+                        language
+                    ),
+                )
+                .exit()
+        }),
+        None => args.plural_count,
+    };
     let config = validate::LanguageConfig {
-        dialect: validate::Dialect::try_from(args.dialect.as_str()).unwrap(),
+        dialect,
         cases: args.cases,
         genders: args.genders,
-        plural_count: args.plural_count,
+        plural_count,
+        ..Default::default()
+    };
+
+    let case = args.case.unwrap_or(String::from("default"));
+
+    if args.stdin {
+        let mut reported = false;
+        for line in std::io::stdin().lock().lines() {
+            let line = read_line(line);
+            if line.trim().is_empty() {
+                println!("null");
+                continue;
+            }
+            let mut result = match &args.base {
+                Some(base) => validate::validate_translation(&config, base, &case, &line),
+                None => validate::validate_base(&config, &line),
+            };
+            reported |= filter_result(&mut result, &args.min_severity, args.no_normalize);
+            println!("{}", serde_json::to_string(&result).unwrap());
+        }
+        if reported {
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let base = args.base.unwrap();
+    if all_dialects {
+        if print_dialects(&config, &base, &args.format) {
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let mut reported = false;
+    if base == "-" && args.translation.is_none() {
+        for (i, line) in std::io::stdin().lock().lines().enumerate() {
+            let mut result = validate::validate_base(&config, &read_line(line));
+            reported |= filter_result(&mut result, &args.min_severity, args.no_normalize);
+            match args.format {
+                Format::Text => print_result(&format!("{}: ", i + 1), &result),
+                Format::Json => println!("{}", serde_json::to_string(&result).unwrap()),
+            }
+        }
+    } else {
+        let mut result = match &args.translation {
+            Some(translation) => validate::validate_translation(&config, &base, &case, translation),
+            None => validate::validate_base(&config, &base),
+        };
+        reported = filter_result(&mut result, &args.min_severity, args.no_normalize);
+        match args.format {
+            Format::Text => print_result("", &result),
+            Format::Json => println!("{}", serde_json::to_string(&result).unwrap()),
+        }
+    }
+    if reported {
+        std::process::exit(1);
+    }
+}
+
+/// Unwrap a line read from stdin, or report the error and exit, for example for non-UTF-8 input.
+fn read_line(line: std::io::Result<String>) -> String {
+    line.unwrap_or_else(|err| {
+        eprintln!("Failed to read from stdin: {}", err);
+        std::process::exit(2);
+    })
+}
+
+/// Remove messages below '--min-severity', and the normalized string for '--no-normalize'.
+/// Returns whether any message is left.
+fn filter_result(
+    result: &mut validate::ValidationResult,
+    min_severity: &MinSeverity,
+    no_normalize: bool,
+) -> bool {
+    let rank = |severity: &validate::Severity| match severity {
+        validate::Severity::Error => 2,
+        validate::Severity::Warning => 1,
+        validate::Severity::Info => 0,
+    };
+    let min_rank = match min_severity {
+        MinSeverity::Error => 2,
+        MinSeverity::Warning => 1,
+        MinSeverity::Info => 0,
     };
+    result.errors.retain(|err| rank(&err.severity) >= min_rank);
+    if no_normalize {
+        result.normalized = None;
+    }
+    !result.errors.is_empty()
+}
+
+/// Print the commands of a dialect with their flags, one per line, or as JSON array.
+fn print_commands(dialect: &validate::Dialect, format: &Format) {
+    let commands = validate::list_commands(dialect);
+    if let Format::Json = format {
+        println!("{}", serde_json::to_string(&commands).unwrap());
+        return;
+    }
+    for cmd in &commands {
+        print!(
+            "{} params={} case={} gender={} plural={} occurrence={}",
+            cmd.name,
+            cmd.has_parameters,
+            cmd.allow_case,
+            cmd.allow_gender,
+            cmd.allow_plural,
+            serde_json::to_value(cmd.occurence)
+                .unwrap()
+                .as_str()
+                .unwrap()
+        );
+        if let Some(norm_name) = cmd.norm_name {
+            println!(" alias-of={}", norm_name);
+        } else {
+            println!();
+        }
+    }
+}
 
-    let result = match args.translation {
-        Some(translation) => validate::validate_translation(
-            &config,
-            &args.base,
-            &args.case.unwrap_or(String::from("default")),
-            &translation,
-        ),
-        None => validate::validate_base(&config, &args.base),
+/// Print for each command of 'base', which dialects accept it, one command per line, or as JSON array.
+/// Returns whether any dialect rejects a command, or the base is invalid.
+fn print_dialects(config: &validate::LanguageConfig, base: &str, format: &Format) -> bool {
+    let commands = match validate::validate_base_all_dialects(config, base) {
+        Ok(commands) => commands,
+        Err(errors) => {
+            let result = validate::ValidationResult {
+                errors,
+                normalized: None,
+            };
+            match format {
+                Format::Text => print_result("", &result),
+                Format::Json => println!("{}", serde_json::to_string(&result).unwrap()),
+            }
+            return true;
+        }
     };
+    if let Format::Json = format {
+        println!("{}", serde_json::to_string(&commands).unwrap());
+    } else {
+        for cmd in &commands {
+            let support: Vec<String> = validate::Dialect::ALL
+                .iter()
+                .map(|dialect| {
+                    let accepted = if cmd.rejected_by.contains(dialect) {
+                        "no"
+                    } else {
+                        "yes"
+                    };
+                    format!("{}={}", dialect.as_str(), accepted)
+                })
+                .collect();
+            println!("{} {}", cmd.command, support.join(" "));
+        }
+    }
+    commands.iter().any(|cmd| !cmd.rejected_by.is_empty())
+}
 
+/// Print the errors and the normalized string in human-readable form, each line starting with 'prefix'.
+fn print_result(prefix: &str, result: &validate::ValidationResult) {
     for err in &result.errors {
         let sev = match err.severity {
             validate::Severity::Error => "ERROR",
             validate::Severity::Warning => "WARNING",
+            validate::Severity::Info => "INFO",
         };
         let pos_begin = err
             .pos_begin
@@ -53,10 +298,13 @@ fn main() {
             .suggestion
             .as_ref()
             .map_or(String::new(), |h| format!(" HINT: {}", h));
-        println!("{}{}{}: {}{}", sev, pos_begin, pos_end, err.message, hint);
+        println!(
+            "{}{}{}{}: {}{}",
+            prefix, sev, pos_begin, pos_end, err.message, hint
+        );
     }
 
-    if let Some(normalized) = result.normalized {
-        println!("NORMALIZED:{}", normalized);
+    if let Some(normalized) = &result.normalized {
+        println!("{}NORMALIZED:{}", prefix, normalized);
     }
 }
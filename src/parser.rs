@@ -1,48 +1,111 @@
+//! Parser for OpenTTD strings.
+//!
+//! A string is split into fragments of text, string commands, gender definitions, choice lists and comments.
+//! Compiling the fragments results in the original string, up to quoting and whitespace inside commands:
+//!
+//! ```
+//! use nile_library::parser::{FragmentContent, ParsedString};
+//!
+//! let parsed = ParsedString::parse("{G=m}{NUM} {P car cars}").unwrap();
+//! assert!(matches!(parsed.fragments[1].content, FragmentContent::Command(_)));
+//! assert_eq!(parsed.compile(), "{G=m}{NUM} {P car cars}");
+//! ```
+//!
+//! Like in OpenTTD's strgen, every `{` in text starts a string command, and a literal `{` is written as the command `{{}`.
+//! A `}` outside of a command is a literal `}`, there is no escape for it.
+//! Compiling a text fragment writes each `{` as `{{}`, so `Text("{}")` compiles to `{{}}`, which parses as `{{}` followed by the text `}`.
+//!
+//! `{#...}` is a comment for translators, which ends at the first `}`.
+//!
+//! Choice values of `{P ...}` and `{G ...}` are plain text, they cannot contain string commands.
+//! The first `}` ends the choice list, so `{P {NUM} b}` is the choice list `{P {NUM}` followed by the text ` b}`.
+//! `ParsedString::parse_with_choice_contents` instead ends a choice list at the matching `}`, so choices can contain
+//! complete string commands, like `{NUM}` in `{P {NUM} b}`.
+
 use once_cell::sync::Lazy;
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct StringCommand {
     pub index: Option<usize>,
     pub name: String,
     pub case: Option<String>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct GenderDefinition {
+    pub index: Option<usize>,
     pub gender: String,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct ChoiceList {
     pub name: String,
     pub indexref: Option<usize>,
     pub indexsubref: Option<usize>,
-    pub choices: Vec<String>,
+    pub choices: Vec<String>, //< plain text; only 'ParsedString::parse_with_choice_contents' returns choices with string commands.
+    pub choice_spans: Vec<(usize, usize)>, //< codepoint offsets of each choice, including quotes, relative to the start of the command.
 }
 
-#[derive(Debug, PartialEq)]
+/// Serialized with the variant as "type", like '{"type": "Text", "value": "abc"}'.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(tag = "type", content = "value")]
 pub enum FragmentContent {
     Text(String),
     Command(StringCommand),
     Gender(GenderDefinition),
     Choice(ChoiceList),
+    Comment(String), //< text of '{#...}', without the braces and '#'.
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct StringFragment {
     pub pos_begin: usize,
     pub pos_end: usize,
     pub content: FragmentContent,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct ParsedString {
     pub fragments: Vec<StringFragment>,
 }
 
-#[derive(Debug, PartialEq)]
+/// Limits for parsing untrusted input, see 'ParsedString::parse_with_limits'.
+#[derive(Debug, Clone, Copy)]
+pub struct ParserLimits {
+    pub max_length: usize,    //< maximum number of codepoints of the input.
+    pub max_fragments: usize, //< maximum number of text fragments, string commands, ...
+}
+
+impl Default for ParserLimits {
+    /// Generous limits, which real strings never come close to.
+    fn default() -> Self {
+        ParserLimits {
+            max_length: 65536,
+            max_fragments: 4096,
+        }
+    }
+}
+
+/// Stable identifier of the kind of a ParserError, for tools which must not depend on the message text.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Copy, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub enum ParseErrorKind {
+    UnterminatedCommand, //< '{' without matching '}'.
+    InvalidCommand,      //< the content of '{...}' is not a known syntax.
+    InvalidPosition,     //< a position reference has non-ASCII digits.
+    UnterminatedQuote,   //< a quoted choice has no closing '"'.
+    StrayQuote,          //< a '"' in the middle of a choice.
+    MissingSeparator,    //< a quoted choice is not followed by whitespace.
+    TooLong,             //< the string exceeds 'ParserLimits::max_length'.
+    TooManyFragments,    //< the string exceeds 'ParserLimits::max_fragments'.
+    InvalidChoiceValue, //< 'ParsedString::map_choices' returned a value with '"' or a '}' without matching '{'.
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
 pub struct ParserError {
+    pub kind: ParseErrorKind,
     pub pos_begin: usize,
     pub pos_end: Option<usize>,
     pub message: String,
@@ -61,7 +124,7 @@ impl StringCommand {
         })
     }
 
-    fn compile(&self) -> String {
+    pub fn compile(&self) -> String {
         let mut result = String::from("{");
         if let Some(i) = self.index {
             result.push_str(&format!("{}:", i));
@@ -75,47 +138,154 @@ impl StringCommand {
     }
 }
 
-static PAT_GENDER: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\{G\s*=\s*(\w+)\}$").unwrap());
+static PAT_GENDER: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\{(?:(\d+):)?G\s*=\s*(\w+)\}$").unwrap());
 
 impl GenderDefinition {
     fn parse(string: &str) -> Option<GenderDefinition> {
         let caps = PAT_GENDER.captures(string)?;
         Some(GenderDefinition {
-            gender: String::from(&caps[1]),
+            index: caps.get(1).and_then(|v| v.as_str().parse().ok()),
+            gender: String::from(&caps[2]),
         })
     }
 
-    fn compile(&self) -> String {
-        format!("{{G={}}}", self.gender)
+    pub fn compile(&self) -> String {
+        match self.index {
+            Some(i) => format!("{{{}:G={}}}", i, self.gender),
+            None => format!("{{G={}}}", self.gender),
+        }
     }
 }
 
-static PAT_CHOICE: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"^\{([PG])(?:\s+(\d+)(?::(\d+))?)?(\s+[^\s0-9].*?)\s*\}$").unwrap());
+static PAT_CHOICE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?s)^\{([PG])(?:\s+(\d+)(?::(\d+))?)?(\s+[^\s0-9].*?)\s*\}$").unwrap()
+});
 static PAT_ITEM: Lazy<Regex> =
     Lazy::new(|| Regex::new(r##"^\s+(?:([^\s"]+)|"([^"]*)")"##).unwrap());
 
+/// Byte offsets of the '}' in 'value', which do not end a string command started in 'value'.
+/// '{{}' is a complete command, so its '{' does not need a matching '}'.
+fn unmatched_braces(value: &str) -> Vec<usize> {
+    let mut result = Vec::new();
+    let mut depth = 0;
+    let mut iter = value.char_indices();
+    while let Some((i, c)) = iter.next() {
+        match c {
+            '{' if value[i..].starts_with("{{}") => {
+                iter.nth(1);
+            }
+            '{' => depth += 1,
+            '}' if depth > 0 => depth -= 1,
+            '}' => result.push(i),
+            _ => (),
+        }
+    }
+    result
+}
+
+/// Whether a value can be used as choice in a '{P ...}' or '{G ...}' choice list.
+/// Choices cannot contain '"', or '}' except at the end of a string command in the choice, because there is no escape for them.
+/// Choices with string commands, like '{NUM}', can only be read back with 'ParsedString::parse_with_choice_contents'.
+pub fn is_valid_choice_value(value: &str) -> bool {
+    !value.contains('"') && unmatched_braces(value).is_empty()
+}
+
+/// Quote a choice value if needed, so it can be used in a '{P ...}' or '{G ...}' choice list.
+/// Values starting with a digit or '=' are quoted, so they are not mistaken for a position or a gender definition.
+/// Characters, which cannot be represented in a choice list, are removed; use 'is_valid_choice_value' to check for them.
+pub fn escape_choice_value(value: &str) -> String {
+    let unmatched = unmatched_braces(value);
+    let value: String = value
+        .char_indices()
+        .filter(|(i, c)| *c != '"' && !unmatched.contains(i))
+        .map(|(_, c)| c)
+        .collect();
+    if value.is_empty()
+        || value.contains(char::is_whitespace)
+        || value.starts_with(|v: char| v.is_numeric() || v == '=')
+    {
+        format!(r##""{}""##, value)
+    } else {
+        value
+    }
+}
+
 impl ChoiceList {
-    fn parse(string: &str) -> Option<ChoiceList> {
-        let caps = PAT_CHOICE.captures(string)?;
+    /// Parse a choice list.
+    /// Returns None, if 'string' is no choice list; or an error, if the choices are malformed.
+    fn parse(string: &str) -> Result<Option<ChoiceList>, ParserError> {
+        let Some(caps) = PAT_CHOICE.captures(string) else {
+            return Ok(None);
+        };
         let mut result = ChoiceList {
             name: String::from(&caps[1]),
             indexref: caps.get(2).and_then(|v| v.as_str().parse().ok()),
             indexsubref: caps.get(3).and_then(|v| v.as_str().parse().ok()),
             choices: Vec::new(),
+            choice_spans: Vec::new(),
         };
         let mut rest = &caps[4];
+        let mut pos_code = string[..caps.get(4).unwrap().start()].chars().count();
         while !rest.is_empty() {
-            let m = PAT_ITEM.captures(rest)?;
+            let Some(m) = PAT_ITEM.captures(rest) else {
+                return Err(ChoiceList::item_error(rest, pos_code));
+            };
             result
                 .choices
                 .push(String::from(m.get(1).or(m.get(2)).unwrap().as_str()));
-            rest = &rest[m.get(0).unwrap().end()..];
+            let item = m.get(0).unwrap().as_str();
+            let len_code = item.chars().count();
+            let len_value = item.trim_start().chars().count();
+            result
+                .choice_spans
+                .push((pos_code + len_code - len_value, pos_code + len_code));
+            pos_code += len_code;
+            rest = &rest[item.len()..];
+        }
+        return Ok(Some(result));
+    }
+
+    /// Describe why 'rest' does not start with a valid choice.
+    /// 'pos_code' is the codepoint offset of 'rest' in the string command.
+    fn item_error(rest: &str, pos_code: usize) -> ParserError {
+        let trimmed = rest.trim_start();
+        let pos_begin = pos_code + rest.chars().count() - trimmed.chars().count();
+        if trimmed.len() == rest.len() {
+            if trimmed.starts_with('"') {
+                ParserError {
+                    kind: ParseErrorKind::StrayQuote,
+                    pos_begin,
+                    pos_end: Some(pos_begin + 1),
+                    message: String::from(
+                        "Stray '\"' in choice, quotes are only allowed around whole choices.",
+                    ),
+                }
+            } else {
+                ParserError {
+                    kind: ParseErrorKind::MissingSeparator,
+                    pos_begin,
+                    pos_end: Some(pos_begin + 1),
+                    message: String::from("Choices must be separated by whitespace."),
+                }
+            }
+        } else {
+            // PAT_ITEM matches every item starting with whitespace, except for unterminated quotes.
+            ParserError {
+                kind: ParseErrorKind::UnterminatedQuote,
+                pos_begin,
+                pos_end: Some(pos_begin + trimmed.chars().count()),
+                message: String::from("Unterminated quote in choice, '\"' expected."),
+            }
         }
-        return Some(result);
     }
 
-    fn compile(&self) -> String {
+    /// Compile the choice list back to string form.
+    /// 'parse' and 'ParsedString::map_choices' never return choices with '"' or unmatched '}'. For hand-built choice lists
+    /// these characters are removed, see 'escape_choice_value'.
+    /// A sub-index can only be written together with a position reference. 'parse' never returns a sub-index without one,
+    /// and 'normalize_string' resolves implicit references, so this only drops the sub-index of hand-built choice lists.
+    pub fn compile(&self) -> String {
         let mut result = format!("{{{}", self.name);
         if let Some(i) = self.indexref {
             result.push_str(&format!(" {}", i));
@@ -124,99 +294,308 @@ impl ChoiceList {
             }
         }
         for c in &self.choices {
-            if c.is_empty() || c.contains(|v| char::is_ascii_whitespace(&v)) {
-                result.push_str(&format!(r##" "{}""##, c));
-            } else {
-                result.push_str(&format!(" {}", c));
-            }
+            result.push_str(&format!(" {}", escape_choice_value(c)));
         }
         result.push_str("}");
         result
     }
 }
 
+static PAT_CHOICE_START: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\{[PG]\s").unwrap());
+
+/// Byte offset of the '}', which ends the string command at the start of 'string'.
+/// If 'choice_contents' is set, a choice list ends at the matching '}', so its choices can contain string commands.
+fn find_command_end(string: &str, choice_contents: bool) -> Option<usize> {
+    if !choice_contents || !PAT_CHOICE_START.is_match(string) {
+        return string.find('}');
+    }
+    let mut depth = 0;
+    let mut iter = string.char_indices();
+    while let Some((i, c)) = iter.next() {
+        match c {
+            '{' if string[i..].starts_with("{{}") => {
+                iter.nth(1);
+            }
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => (),
+        }
+    }
+    None
+}
+
+static PAT_INDEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\{(?:[PG]\s+)?(\d+)(?::(\d+))?[:\s]").unwrap());
+
 impl FragmentContent {
-    fn parse(string: &str) -> Result<FragmentContent, String> {
-        if let Some(command) = StringCommand::parse(string) {
+    /// Parse a single string command.
+    /// Error positions are relative to the start of 'string'.
+    fn parse(string: &str) -> Result<FragmentContent, ParserError> {
+        if let Some(caps) = PAT_INDEX.captures(string) {
+            // '\d' also matches non-ASCII digits, which would not parse as position reference.
+            for m in caps.iter().skip(1).flatten() {
+                if !m.as_str().chars().all(|c| c.is_ascii_digit()) {
+                    let pos_begin = string[..m.start()].chars().count();
+                    return Err(ParserError {
+                        kind: ParseErrorKind::InvalidPosition,
+                        pos_begin,
+                        pos_end: Some(pos_begin + m.as_str().chars().count()),
+                        message: format!(
+                            "Invalid position reference '{}', only ASCII digits '0' to '9' are allowed.",
+                            m.as_str()
+                        ),
+                    });
+                }
+            }
+        }
+
+        if let Some(comment) = string.strip_prefix("{#").and_then(|s| s.strip_suffix('}')) {
+            Ok(FragmentContent::Comment(String::from(comment)))
+        } else if let Some(command) = StringCommand::parse(string) {
             Ok(FragmentContent::Command(command))
         } else if let Some(gender) = GenderDefinition::parse(string) {
             Ok(FragmentContent::Gender(gender))
-        } else if let Some(choice) = ChoiceList::parse(string) {
+        } else if let Some(choice) = ChoiceList::parse(string)? {
             Ok(FragmentContent::Choice(choice))
         } else {
-            Err(format!("Invalid string command: '{}'", string))
+            Err(ParserError {
+                kind: ParseErrorKind::InvalidCommand,
+                pos_begin: 0,
+                pos_end: Some(string.chars().count()),
+                message: format!("Invalid string command: '{}'", string),
+            })
         }
     }
 
-    fn compile(&self) -> String {
+    pub fn compile(&self) -> String {
         match self {
-            Self::Text(s) => s.clone(),
+            Self::Text(s) => s.replace('{', "{{}"),
             Self::Command(command) => command.compile(),
             Self::Gender(gender) => gender.compile(),
             Self::Choice(choice) => choice.compile(),
+            Self::Comment(comment) => format!("{{#{}}}", comment),
         }
     }
 }
 
 impl ParsedString {
+    /// Split a string into fragments.
+    /// Returns the position and description of the first invalid string command, if any.
     pub fn parse(string: &str) -> Result<ParsedString, ParserError> {
+        ParsedString::parse_with_limits(string, &ParserLimits::default())
+    }
+
+    /// Split a string into fragments, like 'parse'.
+    /// Returns an error, if the string exceeds the length or fragment count of 'limits'.
+    pub fn parse_with_limits(
+        string: &str,
+        limits: &ParserLimits,
+    ) -> Result<ParsedString, ParserError> {
+        ParsedString::parse_impl(string, limits, false)
+    }
+
+    /// Split a string into fragments, like 'parse', but allow complete string commands inside choices.
+    /// A choice list ends at the '}' matching its '{', so '{P {NUM} b}' is a choice list with the choices '{NUM}' and 'b'.
+    /// The choices stay plain text; parse them with this method again to get their string commands.
+    pub fn parse_with_choice_contents(string: &str) -> Result<ParsedString, ParserError> {
+        ParsedString::parse_impl(string, &ParserLimits::default(), true)
+    }
+
+    fn parse_impl(
+        string: &str,
+        limits: &ParserLimits,
+        choice_contents: bool,
+    ) -> Result<ParsedString, ParserError> {
+        if let Some((byte_pos, _)) = string.char_indices().nth(limits.max_length) {
+            return Err(ParserError {
+                kind: ParseErrorKind::TooLong,
+                pos_begin: limits.max_length,
+                pos_end: Some(limits.max_length + string[byte_pos..].chars().count()),
+                message: format!(
+                    "String is too long, at most {} characters are allowed.",
+                    limits.max_length
+                ),
+            });
+        }
+        let too_many_fragments = |pos_begin: usize| {
+            ParserError {
+            kind: ParseErrorKind::TooManyFragments,
+            pos_begin,
+            pos_end: None,
+            message: format!(
+                "String has too many parts, at most {} text fragments and string commands are allowed.",
+                limits.max_fragments
+            ),
+        }
+        };
+
         let mut result = ParsedString {
             fragments: Vec::new(),
         };
         let mut rest: &str = string;
         let mut pos_code: usize = 0;
+        let mut text = String::new();
+        let mut text_begin: usize = 0;
         while !rest.is_empty() {
-            if let Some(start) = rest.find('{') {
-                if start > 0 {
-                    let text: &str;
-                    (text, rest) = rest.split_at(start);
-                    let len_code = text.chars().count();
+            if rest.starts_with('{') {
+                if !text.is_empty() {
                     result.fragments.push(StringFragment {
-                        pos_begin: pos_code,
-                        pos_end: pos_code + len_code,
-                        content: FragmentContent::Text(String::from(text)),
+                        pos_begin: text_begin,
+                        pos_end: pos_code,
+                        content: FragmentContent::Text(std::mem::take(&mut text)),
                     });
-                    pos_code += len_code;
                 }
-                if let Some(end) = rest.find('}') {
-                    let text: &str;
-                    (text, rest) = rest.split_at(end + 1);
-                    let len_code = text.chars().count();
-                    match FragmentContent::parse(text) {
+                if result.fragments.len() >= limits.max_fragments {
+                    return Err(too_many_fragments(pos_code));
+                }
+                if let Some(end) = find_command_end(rest, choice_contents) {
+                    let command: &str;
+                    (command, rest) = rest.split_at(end + 1);
+                    let len_code = command.chars().count();
+                    match FragmentContent::parse(command) {
                         Ok(content) => result.fragments.push(StringFragment {
                             pos_begin: pos_code,
                             pos_end: pos_code + len_code,
                             content: content,
                         }),
-                        Err(message) => {
+                        Err(err) => {
                             return Err(ParserError {
-                                pos_begin: pos_code,
-                                pos_end: Some(pos_code + len_code),
-                                message: message,
+                                kind: err.kind,
+                                pos_begin: pos_code + err.pos_begin,
+                                pos_end: err.pos_end.map(|p| pos_code + p),
+                                message: err.message,
                             });
                         }
                     };
                     pos_code += len_code;
+                    text_begin = pos_code;
                 } else {
                     return Err(ParserError {
+                        kind: ParseErrorKind::UnterminatedCommand,
                         pos_begin: pos_code,
                         pos_end: None,
                         message: String::from("Unterminated string command, '}' expected."),
                     });
                 }
             } else {
-                let len_code = rest.chars().count();
-                result.fragments.push(StringFragment {
-                    pos_begin: pos_code,
-                    pos_end: pos_code + len_code,
-                    content: FragmentContent::Text(String::from(rest)),
-                });
-                break;
+                // Plain text up to the next command. A '}' is also plain text.
+                let len = rest.find('{').unwrap_or(rest.len());
+                let chunk: &str;
+                (chunk, rest) = rest.split_at(len);
+                text.push_str(chunk);
+                pos_code += chunk.chars().count();
             }
         }
+        if !text.is_empty() {
+            if result.fragments.len() >= limits.max_fragments {
+                return Err(too_many_fragments(text_begin));
+            }
+            result.fragments.push(StringFragment {
+                pos_begin: text_begin,
+                pos_end: pos_code,
+                content: FragmentContent::Text(text),
+            });
+        }
         Ok(result)
     }
 
+    /// Iterate over all string commands, like '{NUM}'.
+    pub fn commands(&self) -> impl Iterator<Item = &StringCommand> {
+        self.fragments.iter().filter_map(|f| match &f.content {
+            FragmentContent::Command(cmd) => Some(cmd),
+            _ => None,
+        })
+    }
+
+    /// Iterate over all choice lists, like '{P ...}' and '{G ...}'.
+    pub fn choices(&self) -> impl Iterator<Item = &ChoiceList> {
+        self.fragments.iter().filter_map(|f| match &f.content {
+            FragmentContent::Choice(choice) => Some(choice),
+            _ => None,
+        })
+    }
+
+    /// Iterate over all gender definitions, like '{G=m}'.
+    pub fn genders(&self) -> impl Iterator<Item = &GenderDefinition> {
+        self.fragments.iter().filter_map(|f| match &f.content {
+            FragmentContent::Gender(gender) => Some(gender),
+            _ => None,
+        })
+    }
+
+    /// Replace the text of all text fragments with the result of 'f'. String commands and choice lists are not changed.
+    pub fn map_text(&mut self, mut f: impl FnMut(&str) -> String) {
+        for fragment in &mut self.fragments {
+            if let FragmentContent::Text(text) = &mut fragment.content {
+                *text = f(text);
+            }
+        }
+    }
+
+    /// Replace all choice values of '{P ...}' and '{G ...}' with the result of 'f'.
+    /// Returns an error and keeps the string unchanged, if 'f' returns a value which cannot be used as choice.
+    pub fn map_choices(&mut self, mut f: impl FnMut(&str) -> String) -> Result<(), ParserError> {
+        let mut mapped = Vec::new();
+        for fragment in &self.fragments {
+            if let FragmentContent::Choice(choice) = &fragment.content {
+                for value in &choice.choices {
+                    let value = f(value);
+                    if !is_valid_choice_value(&value) {
+                        return Err(ParserError {
+                            kind: ParseErrorKind::InvalidChoiceValue,
+                            pos_begin: fragment.pos_begin,
+                            pos_end: Some(fragment.pos_end),
+                            message: format!(
+                                "Choice '{}' cannot be used, because it contains '\"' or '}}' without matching '{{'.",
+                                value
+                            ),
+                        });
+                    }
+                    mapped.push(value);
+                }
+            }
+        }
+        let mut mapped = mapped.into_iter();
+        for fragment in &mut self.fragments {
+            if let FragmentContent::Choice(choice) = &mut fragment.content {
+                for value in &mut choice.choices {
+                    *value = mapped.next().unwrap();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the visible text, for example for search indexing or length estimation.
+    /// String commands and gender definitions are dropped, except line breaks '{}' and braces '{{}'.
+    /// Choice lists are replaced with their first choice.
+    pub fn to_plain_text(&self) -> String {
+        let mut result = String::new();
+        for f in &self.fragments {
+            match &f.content {
+                FragmentContent::Text(text) => result.push_str(text),
+                FragmentContent::Command(cmd) => match cmd.name.as_str() {
+                    "" => result.push('\n'),
+                    "{" => result.push('{'),
+                    _ => (),
+                },
+                FragmentContent::Choice(choice) => {
+                    if let Some(first) = choice.choices.first() {
+                        result.push_str(first);
+                    }
+                }
+                FragmentContent::Gender(_) | FragmentContent::Comment(_) => (),
+            }
+        }
+        result
+    }
+
+    /// Concatenate all fragments into a string.
     pub fn compile(&self) -> String {
         let mut result = String::new();
         for f in &self.fragments {
@@ -291,12 +670,21 @@ mod tests {
         assert_eq!(
             FragmentContent::parse("{G=n}"),
             Ok(FragmentContent::Gender(GenderDefinition {
+                index: None,
                 gender: String::from("n")
             }))
         );
         assert_eq!(
             FragmentContent::parse("{G = n}"),
             Ok(FragmentContent::Gender(GenderDefinition {
+                index: None,
+                gender: String::from("n")
+            }))
+        );
+        assert_eq!(
+            FragmentContent::parse("{1:G=n}"),
+            Ok(FragmentContent::Gender(GenderDefinition {
+                index: Some(1),
                 gender: String::from("n")
             }))
         );
@@ -306,7 +694,8 @@ mod tests {
                 name: String::from("P"),
                 indexref: None,
                 indexsubref: None,
-                choices: vec![String::from("a"), String::from("b")]
+                choices: vec![String::from("a"), String::from("b")],
+                choice_spans: vec![(3, 4), (5, 6)],
             }))
         );
         assert_eq!(
@@ -315,7 +704,8 @@ mod tests {
                 name: String::from("P"),
                 indexref: None,
                 indexsubref: None,
-                choices: vec![String::from("a"), String::from("b")]
+                choices: vec![String::from("a"), String::from("b")],
+                choice_spans: vec![(3, 4), (5, 6)],
             }))
         );
         assert_eq!(
@@ -324,7 +714,8 @@ mod tests {
                 name: String::from("P"),
                 indexref: None,
                 indexsubref: None,
-                choices: vec![String::from(""), String::from("b")]
+                choices: vec![String::from(""), String::from("b")],
+                choice_spans: vec![(3, 5), (6, 7)],
             }))
         );
         assert_eq!(
@@ -333,7 +724,18 @@ mod tests {
                 name: String::from("P"),
                 indexref: None,
                 indexsubref: None,
-                choices: vec![String::from("a b"), String::from("c")]
+                choices: vec![String::from("a b"), String::from("c")],
+                choice_spans: vec![(3, 8), (9, 12)],
+            }))
+        );
+        assert_eq!(
+            FragmentContent::parse("{P \"a\nb\" c}"),
+            Ok(FragmentContent::Choice(ChoiceList {
+                name: String::from("P"),
+                indexref: None,
+                indexsubref: None,
+                choices: vec![String::from("a\nb"), String::from("c")],
+                choice_spans: vec![(3, 8), (9, 10)],
             }))
         );
         assert_eq!(
@@ -342,7 +744,8 @@ mod tests {
                 name: String::from("P"),
                 indexref: Some(1),
                 indexsubref: None,
-                choices: vec![String::from("a"), String::from("b")]
+                choices: vec![String::from("a"), String::from("b")],
+                choice_spans: vec![(5, 6), (7, 8)],
             }))
         );
         assert_eq!(
@@ -351,7 +754,8 @@ mod tests {
                 name: String::from("P"),
                 indexref: Some(1),
                 indexsubref: None,
-                choices: vec![String::from("a"), String::from("b")]
+                choices: vec![String::from("a"), String::from("b")],
+                choice_spans: vec![(5, 6), (7, 8)],
             }))
         );
         assert_eq!(
@@ -360,7 +764,8 @@ mod tests {
                 name: String::from("P"),
                 indexref: Some(1),
                 indexsubref: None,
-                choices: vec![String::from(""), String::from("b")]
+                choices: vec![String::from(""), String::from("b")],
+                choice_spans: vec![(5, 7), (8, 9)],
             }))
         );
         assert_eq!(
@@ -369,7 +774,8 @@ mod tests {
                 name: String::from("P"),
                 indexref: Some(1),
                 indexsubref: None,
-                choices: vec![String::from("a b"), String::from("c")]
+                choices: vec![String::from("a b"), String::from("c")],
+                choice_spans: vec![(5, 10), (11, 14)],
             }))
         );
         assert_eq!(
@@ -378,7 +784,8 @@ mod tests {
                 name: String::from("P"),
                 indexref: Some(1),
                 indexsubref: Some(2),
-                choices: vec![String::from("a"), String::from("b")]
+                choices: vec![String::from("a"), String::from("b")],
+                choice_spans: vec![(7, 8), (9, 10)],
             }))
         );
         assert_eq!(
@@ -387,7 +794,8 @@ mod tests {
                 name: String::from("P"),
                 indexref: Some(1),
                 indexsubref: Some(2),
-                choices: vec![String::from(""), String::from("b")]
+                choices: vec![String::from(""), String::from("b")],
+                choice_spans: vec![(7, 9), (10, 11)],
             }))
         );
         assert_eq!(
@@ -396,7 +804,8 @@ mod tests {
                 name: String::from("P"),
                 indexref: Some(1),
                 indexsubref: Some(2),
-                choices: vec![String::from("a b"), String::from("c")]
+                choices: vec![String::from("a b"), String::from("c")],
+                choice_spans: vec![(7, 12), (13, 16)],
             }))
         );
 
@@ -406,7 +815,8 @@ mod tests {
                 name: String::from("P"),
                 indexref: None,
                 indexsubref: None,
-                choices: vec![String::from("a"), String::from("b"), String::from("c")]
+                choices: vec![String::from("a"), String::from("b"), String::from("c")],
+                choice_spans: vec![(3, 4), (5, 6), (7, 8)],
             }))
         );
         assert_eq!(
@@ -415,7 +825,8 @@ mod tests {
                 name: String::from("P"),
                 indexref: None,
                 indexsubref: None,
-                choices: vec![String::from(""), String::from(""), String::from("b")]
+                choices: vec![String::from(""), String::from(""), String::from("b")],
+                choice_spans: vec![(3, 5), (6, 8), (9, 10)],
             }))
         );
         assert_eq!(
@@ -424,7 +835,8 @@ mod tests {
                 name: String::from("P"),
                 indexref: None,
                 indexsubref: None,
-                choices: vec![String::from("a"), String::from("")]
+                choices: vec![String::from("a"), String::from("")],
+                choice_spans: vec![(3, 4), (5, 7)],
             }))
         );
         assert_eq!(
@@ -433,7 +845,8 @@ mod tests {
                 name: String::from("P"),
                 indexref: Some(1),
                 indexsubref: None,
-                choices: vec![String::from("a"), String::from("b"), String::from("c")]
+                choices: vec![String::from("a"), String::from("b"), String::from("c")],
+                choice_spans: vec![(5, 6), (7, 8), (9, 10)],
             }))
         );
         assert_eq!(
@@ -442,7 +855,8 @@ mod tests {
                 name: String::from("P"),
                 indexref: Some(1),
                 indexsubref: None,
-                choices: vec![String::from(""), String::from(""), String::from("b")]
+                choices: vec![String::from(""), String::from(""), String::from("b")],
+                choice_spans: vec![(5, 7), (8, 10), (11, 12)],
             }))
         );
         assert_eq!(
@@ -451,7 +865,8 @@ mod tests {
                 name: String::from("P"),
                 indexref: Some(1),
                 indexsubref: None,
-                choices: vec![String::from("a"), String::from("")]
+                choices: vec![String::from("a"), String::from("")],
+                choice_spans: vec![(5, 6), (7, 9)],
             }))
         );
         assert_eq!(
@@ -460,7 +875,8 @@ mod tests {
                 name: String::from("P"),
                 indexref: Some(1),
                 indexsubref: Some(2),
-                choices: vec![String::from("a"), String::from("b"), String::from("c")]
+                choices: vec![String::from("a"), String::from("b"), String::from("c")],
+                choice_spans: vec![(7, 8), (9, 10), (11, 12)],
             }))
         );
         assert_eq!(
@@ -469,7 +885,8 @@ mod tests {
                 name: String::from("P"),
                 indexref: Some(1),
                 indexsubref: Some(2),
-                choices: vec![String::from(""), String::from(""), String::from("b")]
+                choices: vec![String::from(""), String::from(""), String::from("b")],
+                choice_spans: vec![(7, 9), (10, 12), (13, 14)],
             }))
         );
         assert_eq!(
@@ -478,7 +895,8 @@ mod tests {
                 name: String::from("P"),
                 indexref: Some(1),
                 indexsubref: Some(2),
-                choices: vec![String::from("a"), String::from("")]
+                choices: vec![String::from("a"), String::from("")],
+                choice_spans: vec![(7, 8), (9, 11)],
             }))
         );
     }
@@ -494,6 +912,74 @@ mod tests {
         assert!(FragmentContent::parse(r##"{P 1:a a b}"##).is_err());
     }
 
+    #[test]
+    fn test_parse_choice_quotes() {
+        assert_eq!(
+            ParsedString::parse(r##"x{P "a b c}"##).err(),
+            Some(ParserError {
+                kind: ParseErrorKind::UnterminatedQuote,
+                pos_begin: 4,
+                pos_end: Some(10),
+                message: String::from("Unterminated quote in choice, '\"' expected."),
+            })
+        );
+        assert_eq!(
+            ParsedString::parse(r##"x{G a b"c d}"##).err(),
+            Some(ParserError {
+                kind: ParseErrorKind::StrayQuote,
+                pos_begin: 7,
+                pos_end: Some(8),
+                message: String::from(
+                    "Stray '\"' in choice, quotes are only allowed around whole choices."
+                ),
+            })
+        );
+        assert_eq!(
+            ParsedString::parse(r##"x{P "a"b c}"##).err(),
+            Some(ParserError {
+                kind: ParseErrorKind::MissingSeparator,
+                pos_begin: 7,
+                pos_end: Some(8),
+                message: String::from("Choices must be separated by whitespace."),
+            })
+        );
+        assert!(ParsedString::parse(r##"x{P "a b" c}"##).is_ok());
+        assert!(ParsedString::parse(r##"x{P "" "c d" }"##).is_ok());
+    }
+
+    #[test]
+    fn test_parse_non_ascii_index() {
+        let message = "Invalid position reference '٠', only ASCII digits '0' to '9' are allowed.";
+        assert_eq!(
+            ParsedString::parse("a {٠:NUM}").err(),
+            Some(ParserError {
+                kind: ParseErrorKind::InvalidPosition,
+                pos_begin: 3,
+                pos_end: Some(4),
+                message: String::from(message),
+            })
+        );
+        assert_eq!(
+            ParsedString::parse("{٠:G=n}").err(),
+            Some(ParserError {
+                kind: ParseErrorKind::InvalidPosition,
+                pos_begin: 1,
+                pos_end: Some(2),
+                message: String::from(message),
+            })
+        );
+        assert_eq!(
+            ParsedString::parse("{P 1:٠ a b}").err(),
+            Some(ParserError {
+                kind: ParseErrorKind::InvalidPosition,
+                pos_begin: 5,
+                pos_end: Some(6),
+                message: String::from(message),
+            })
+        );
+        assert!(ParsedString::parse("{P ٣ كتب}").is_err());
+    }
+
     #[test]
     fn test_compile_cmd() {
         assert_eq!(
@@ -534,17 +1020,27 @@ mod tests {
         );
         assert_eq!(
             GenderDefinition {
+                index: None,
                 gender: String::from("n")
             }
             .compile(),
             "{G=n}"
         );
+        assert_eq!(
+            GenderDefinition {
+                index: Some(1),
+                gender: String::from("n")
+            }
+            .compile(),
+            "{1:G=n}"
+        );
         assert_eq!(
             ChoiceList {
                 name: String::from("P"),
                 indexref: None,
                 indexsubref: None,
-                choices: vec![String::from("a"), String::from("b")]
+                choices: vec![String::from("a"), String::from("b")],
+                choice_spans: vec![],
             }
             .compile(),
             "{P a b}"
@@ -554,7 +1050,8 @@ mod tests {
                 name: String::from("P"),
                 indexref: None,
                 indexsubref: None,
-                choices: vec![String::from(""), String::from(" b")]
+                choices: vec![String::from(""), String::from(" b")],
+                choice_spans: vec![],
             }
             .compile(),
             r##"{P "" " b"}"##
@@ -564,7 +1061,8 @@ mod tests {
                 name: String::from("P"),
                 indexref: Some(1),
                 indexsubref: None,
-                choices: vec![String::from("a"), String::from("b")]
+                choices: vec![String::from("a"), String::from("b")],
+                choice_spans: vec![],
             }
             .compile(),
             "{P 1 a b}"
@@ -574,13 +1072,132 @@ mod tests {
                 name: String::from("P"),
                 indexref: Some(1),
                 indexsubref: Some(2),
-                choices: vec![String::from("a"), String::from("b")]
+                choices: vec![String::from("a"), String::from("b")],
+                choice_spans: vec![],
             }
             .compile(),
             "{P 1:2 a b}"
         );
     }
 
+    #[test]
+    fn test_fragment_iterators() {
+        let parsed =
+            ParsedString::parse("{G=m}{RED}{NUM} {P car cars} and {STRING} {G 1 a b}{P 0 x y}")
+                .unwrap();
+        assert_eq!(
+            parsed
+                .commands()
+                .map(|c| c.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["RED", "NUM", "STRING"]
+        );
+        assert_eq!(parsed.choices().count(), 3);
+        assert_eq!(parsed.choices().filter(|c| c.name == "P").count(), 2);
+        assert_eq!(
+            parsed
+                .genders()
+                .map(|g| g.gender.as_str())
+                .collect::<Vec<_>>(),
+            vec!["m"]
+        );
+
+        let parsed = ParsedString::parse("text only").unwrap();
+        assert_eq!(parsed.commands().count(), 0);
+        assert_eq!(parsed.choices().count(), 0);
+        assert_eq!(parsed.genders().count(), 0);
+    }
+
+    #[test]
+    fn test_map_text() {
+        let curly = |text: &str| {
+            let mut result = String::new();
+            let mut open = true;
+            for c in text.chars() {
+                if c == '"' {
+                    result.push(if open { '“' } else { '”' });
+                    open = !open;
+                } else {
+                    result.push(c);
+                }
+            }
+            result
+        };
+
+        let mut parsed =
+            ParsedString::parse(r##"Say "hello" to {STRING} {P "big dog" dogs}, "friend"."##)
+                .unwrap();
+        parsed.map_text(curly);
+        assert_eq!(
+            parsed.compile(),
+            r##"Say “hello” to {STRING} {P "big dog" dogs}, “friend”."##
+        );
+
+        parsed.map_choices(|value| value.to_uppercase()).unwrap();
+        assert_eq!(
+            parsed.compile(),
+            r##"Say “hello” to {STRING} {P "BIG DOG" DOGS}, “friend”."##
+        );
+
+        // Values, which cannot be compiled, are rejected, and the string is not changed.
+        let err = parsed
+            .map_choices(|value| format!("\"{}\"", value))
+            .unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::InvalidChoiceValue);
+        assert_eq!((err.pos_begin, err.pos_end), (24, Some(42)));
+        assert_eq!(
+            parsed.compile(),
+            r##"Say “hello” to {STRING} {P "BIG DOG" DOGS}, “friend”."##
+        );
+
+        // Hand-built choice lists drop these characters when compiled.
+        let choice = ChoiceList {
+            name: String::from("G"),
+            indexref: None,
+            indexsubref: None,
+            choices: vec![String::from("a\"b"), String::from("c}")],
+            choice_spans: Vec::new(),
+        };
+        assert_eq!(choice.compile(), "{G ab c}");
+    }
+
+    #[test]
+    fn test_escape_choice_value() {
+        assert_eq!(escape_choice_value("a"), "a");
+        assert_eq!(escape_choice_value(""), r##""""##);
+        assert_eq!(escape_choice_value("a b"), r##""a b""##);
+        assert_eq!(escape_choice_value("1st"), r##""1st""##);
+        assert_eq!(escape_choice_value("\u{663}"), "\"\u{663}\"");
+        assert_eq!(escape_choice_value("=m"), r##""=m""##);
+
+        // '"' and '}' cannot be represented, except for '}' ending a string command.
+        assert!(!is_valid_choice_value("a\"b"));
+        assert!(!is_valid_choice_value("a}"));
+        assert!(!is_valid_choice_value("{{}}"));
+        assert!(is_valid_choice_value("{a b"));
+        assert!(is_valid_choice_value("{0:NUM} {G a b}"));
+        assert_eq!(escape_choice_value("a\"b"), "ab");
+        assert_eq!(escape_choice_value("a }"), r##""a ""##);
+        assert_eq!(escape_choice_value("}{NUM}}"), "{NUM}");
+
+        let values = [
+            "", "a", " b", "c d", "e\u{a0}f", "1", "2nd", "{", "g\th", "ä", "\u{663}", "=m",
+        ];
+        for first in values {
+            for second in values {
+                let compiled = format!(
+                    "{{P {} {}}}",
+                    escape_choice_value(first),
+                    escape_choice_value(second)
+                );
+                assert_eq!(
+                    ChoiceList::parse(&compiled).unwrap().map(|c| c.choices),
+                    Some(vec![String::from(first), String::from(second)])
+                );
+            }
+        }
+    }
+
     #[test]
     fn test_parse_str_empty() {
         let case1 = ParsedString::parse("");
@@ -603,6 +1220,7 @@ mod tests {
                     pos_begin: 0,
                     pos_end: 5,
                     content: FragmentContent::Gender(GenderDefinition {
+                        index: None,
                         gender: String::from("n")
                     })
                 },
@@ -635,16 +1253,407 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_codepoint_positions() {
+        // 'é' is two bytes in UTF-8, but positions are codepoint offsets.
+        let parsed = ParsedString::parse("é{NUM}ü{P a b}").unwrap();
+        let positions: Vec<(usize, usize)> = parsed
+            .fragments
+            .iter()
+            .map(|f| (f.pos_begin, f.pos_end))
+            .collect();
+        assert_eq!(positions, vec![(0, 1), (1, 6), (6, 7), (7, 14)]);
+
+        let err = ParsedString::parse("é{FOO BAR}").err().unwrap();
+        assert_eq!((err.pos_begin, err.pos_end), (1, Some(10)));
+    }
+
+    #[test]
+    fn test_parse_comment() {
+        let parsed = ParsedString::parse("{#Note: keep short}{NUM}{#}").unwrap();
+        assert_eq!(
+            parsed.fragments,
+            vec![
+                StringFragment {
+                    pos_begin: 0,
+                    pos_end: 19,
+                    content: FragmentContent::Comment(String::from("Note: keep short")),
+                },
+                StringFragment {
+                    pos_begin: 19,
+                    pos_end: 24,
+                    content: FragmentContent::Command(StringCommand {
+                        index: None,
+                        name: String::from("NUM"),
+                        case: None,
+                    }),
+                },
+                StringFragment {
+                    pos_begin: 24,
+                    pos_end: 27,
+                    content: FragmentContent::Comment(String::new()),
+                },
+            ]
+        );
+        assert_eq!(parsed.compile(), "{#Note: keep short}{NUM}{#}");
+    }
+
+    #[test]
+    fn test_parse_limits() {
+        let long = "a".repeat(100_000);
+        let err = ParsedString::parse(&long).err().unwrap();
+        assert_eq!(
+            err,
+            ParserError {
+                kind: ParseErrorKind::TooLong,
+                pos_begin: 65536,
+                pos_end: Some(100_000),
+                message: String::from("String is too long, at most 65536 characters are allowed."),
+            }
+        );
+
+        let limits = ParserLimits {
+            max_length: 100,
+            max_fragments: 3,
+        };
+        assert!(ParsedString::parse_with_limits("a{NUM}b", &limits).is_ok());
+        let err = ParsedString::parse_with_limits("a{NUM}b{NUM}", &limits)
+            .err()
+            .unwrap();
+        assert_eq!(
+            err,
+            ParserError {
+                kind: ParseErrorKind::TooManyFragments,
+                pos_begin: 7,
+                pos_end: None,
+                message: String::from(
+                    "String has too many parts, at most 3 text fragments and string commands are allowed."
+                ),
+            }
+        );
+        let err = ParsedString::parse_with_limits("{NUM}{NUM}{NUM}b", &limits)
+            .err()
+            .unwrap();
+        assert_eq!(err.pos_begin, 15);
+    }
+
+    #[test]
+    fn test_serde_roundtrip() {
+        let parsed =
+            ParsedString::parse("{G=m}{#note}{1:STRING.gen} {{}x} {P 0:1 \"a b\" c}{G 1 d e}{}")
+                .unwrap();
+        let json = serde_json::to_string(&parsed).unwrap();
+        assert!(json.starts_with(r#"{"fragments":[{"pos_begin":0,"pos_end":5,"content":{"type":"Gender","value":{"index":null,"gender":"m"}}}"#));
+        let deserialized: ParsedString = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, parsed);
+        assert_eq!(deserialized.compile(), parsed.compile());
+    }
+
+    #[test]
+    fn test_parse_braces() {
+        // Like strgen, '{{' is no escape, but the start of a string command.
+        let err = ParsedString::parse("a{{b}}c").unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::InvalidCommand);
+        let err = ParsedString::parse("x{{y").unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::UnterminatedCommand);
+
+        let parsed = ParsedString::parse("{{}x}").unwrap();
+        assert_eq!(
+            parsed.fragments,
+            vec![
+                StringFragment {
+                    pos_begin: 0,
+                    pos_end: 3,
+                    content: FragmentContent::Command(StringCommand {
+                        index: None,
+                        name: String::from("{"),
+                        case: None
+                    })
+                },
+                StringFragment {
+                    pos_begin: 3,
+                    pos_end: 5,
+                    content: FragmentContent::Text(String::from("x}")),
+                },
+            ]
+        );
+
+        for string in ["a}", "Press }", "{{}}", "}}{{}{NUM}}"] {
+            let parsed = ParsedString::parse(string).unwrap();
+            assert_eq!(parsed.compile(), string);
+            assert_eq!(ParsedString::parse(&parsed.compile()).unwrap(), parsed);
+        }
+
+        // A literal '{' in text is compiled as '{{}'.
+        let text = ParsedString {
+            fragments: vec![StringFragment {
+                pos_begin: 0,
+                pos_end: 2,
+                content: FragmentContent::Text(String::from("{}")),
+            }],
+        };
+        assert_eq!(text.compile(), "{{}}");
+        let parsed = ParsedString::parse(&text.compile()).unwrap();
+        assert_eq!(parsed.to_plain_text(), "{}");
+        assert_eq!(parsed.compile(), "{{}}");
+    }
+
+    #[test]
+    fn test_to_plain_text() {
+        let parsed =
+            ParsedString::parse("{G=m}{RED}{NUM} Zug{P \"\" e} {BLACK}fahren{}ab{{}}").unwrap();
+        assert_eq!(parsed.to_plain_text(), " Zug fahren\nab{}");
+        let parsed = ParsedString::parse("{#note}{G Der Die} {STRING}").unwrap();
+        assert_eq!(parsed.to_plain_text(), "Der ");
+    }
+
+    #[test]
+    fn test_parse_error_kind() {
+        let kind = |string: &str| ParsedString::parse(string).err().map(|err| err.kind);
+        assert_eq!(kind("{NUM"), Some(ParseErrorKind::UnterminatedCommand));
+        assert_eq!(kind("{num}"), Some(ParseErrorKind::InvalidCommand));
+        assert_eq!(kind("{P a b}x"), None);
+        assert_eq!(kind("{٠:NUM}"), Some(ParseErrorKind::InvalidPosition));
+        assert_eq!(
+            kind(r##"{P "a b}"##),
+            Some(ParseErrorKind::UnterminatedQuote)
+        );
+        assert_eq!(kind(r##"{P a"b c}"##), Some(ParseErrorKind::StrayQuote));
+        assert_eq!(
+            kind(r##"{P "a"b c}"##),
+            Some(ParseErrorKind::MissingSeparator)
+        );
+
+        let limits = ParserLimits {
+            max_length: 5,
+            max_fragments: 1,
+        };
+        let kind = |string: &str| {
+            ParsedString::parse_with_limits(string, &limits)
+                .err()
+                .map(|err| err.kind)
+        };
+        assert_eq!(kind("abcdef"), Some(ParseErrorKind::TooLong));
+        assert_eq!(kind("a{}"), Some(ParseErrorKind::TooManyFragments));
+    }
+
     #[test]
     fn test_parse_str_err() {
         let case1 = ParsedString::parse("{G=n}{ORANGE OpenTTD");
         assert_eq!(
             case1.err(),
             Some(ParserError {
+                kind: ParseErrorKind::UnterminatedCommand,
                 pos_begin: 5,
                 pos_end: None,
                 message: String::from("Unterminated string command, '}' expected."),
             })
         );
     }
+
+    /// Fragment contents without source positions, for comparing parse results of different strings.
+    fn contents(parsed: &ParsedString) -> Vec<FragmentContent> {
+        parsed
+            .fragments
+            .iter()
+            .map(|f| match &f.content {
+                FragmentContent::Choice(c) => FragmentContent::Choice(ChoiceList {
+                    choice_spans: Vec::new(),
+                    ..c.clone()
+                }),
+                content => content.clone(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_roundtrip_generated() {
+        const PIECES: [&str; 16] = [
+            "a",
+            " ",
+            "ä",
+            "\n",
+            "x}",
+            "}}",
+            "}",
+            "{NUM}",
+            "{1:STRING.gen}",
+            "{ 2 : COMMA }",
+            "{}",
+            "{{}",
+            "{G=m}",
+            "{0:G = f}",
+            "{#note}",
+            "{ RED}",
+        ];
+        const ITEMS: [&str; 12] = [
+            "a",
+            "\"b c\"",
+            "\"\"",
+            "\"1\"",
+            "\"2nd\"",
+            "\"\u{663}\"",
+            "\"=m\"",
+            "\"= f\"",
+            "x:y",
+            "{",
+            "\"\u{a0}\"",
+            "ä",
+        ];
+        const SEPARATORS: [&str; 4] = [" ", "  ", "\n", "\t "];
+        const INDICES: [&str; 4] = ["", " 1", " 01:2", " 0 "];
+
+        /* Deterministic linear congruential generator, so failures are reproducible. */
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = |n: usize| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            ((state >> 33) as usize) % n
+        };
+
+        let mut checked = 0;
+        for _ in 0..5000 {
+            let mut string = String::new();
+            for _ in 0..next(8) {
+                if next(4) == 0 {
+                    string.push_str(if next(2) == 0 { "{P" } else { "{G" });
+                    string.push_str(INDICES[next(INDICES.len())]);
+                    for _ in 0..1 + next(3) {
+                        string.push_str(SEPARATORS[next(SEPARATORS.len())]);
+                        string.push_str(ITEMS[next(ITEMS.len())]);
+                    }
+                    string.push_str(&SEPARATORS[next(SEPARATORS.len())][..next(2)]);
+                    string.push('}');
+                } else {
+                    string.push_str(PIECES[next(PIECES.len())]);
+                }
+            }
+
+            let Ok(parsed) = ParsedString::parse(&string) else {
+                continue;
+            };
+            let compiled = parsed.compile();
+            let reparsed = ParsedString::parse(&compiled)
+                .unwrap_or_else(|err| panic!("{string:?} compiled to {compiled:?}: {err:?}"));
+            assert_eq!(
+                contents(&reparsed),
+                contents(&parsed),
+                "{string:?} -> {compiled:?}"
+            );
+            assert_eq!(reparsed.compile(), compiled, "{string:?}");
+            checked += 1;
+        }
+        assert!(checked > 1000);
+    }
+
+    #[test]
+    fn test_compile_subindex() {
+        let mut choice = ChoiceList {
+            name: String::from("G"),
+            indexref: Some(1),
+            indexsubref: Some(2),
+            choices: vec![String::from("a"), String::from("b")],
+            choice_spans: Vec::new(),
+        };
+        assert_eq!(choice.compile(), "{G 1:2 a b}");
+        choice.indexsubref = None;
+        assert_eq!(choice.compile(), "{G 1 a b}");
+
+        // There is no syntax for a sub-index without position reference.
+        choice.indexref = None;
+        choice.indexsubref = Some(1);
+        assert_eq!(choice.compile(), "{G a b}");
+        assert_eq!(
+            ParsedString::parse("{G :1 a b}").unwrap().fragments[0].content,
+            FragmentContent::Choice(ChoiceList {
+                indexref: None,
+                indexsubref: None,
+                choices: vec![String::from(":1"), String::from("a"), String::from("b")],
+                choice_spans: vec![(3, 5), (6, 7), (8, 9)],
+                ..choice
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_command_in_choice() {
+        // The first '}' ends the choice list, so a choice never contains a complete string command.
+        let parsed = ParsedString::parse("{P {NUM} foo}").unwrap();
+        assert_eq!(
+            parsed.fragments,
+            vec![
+                StringFragment {
+                    pos_begin: 0,
+                    pos_end: 8,
+                    content: FragmentContent::Choice(ChoiceList {
+                        name: String::from("P"),
+                        indexref: None,
+                        indexsubref: None,
+                        choices: vec![String::from("{NUM")],
+                        choice_spans: vec![(3, 7)],
+                    }),
+                },
+                StringFragment {
+                    pos_begin: 8,
+                    pos_end: 13,
+                    content: FragmentContent::Text(String::from(" foo}")),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_choice_contents() {
+        let parsed =
+            ParsedString::parse_with_choice_contents("{P {NUM} \"{G {{}a b} c\"} d}").unwrap();
+        assert_eq!(
+            contents(&parsed),
+            vec![
+                FragmentContent::Choice(ChoiceList {
+                    name: String::from("P"),
+                    indexref: None,
+                    indexsubref: None,
+                    choices: vec![String::from("{NUM}"), String::from("{G {{}a b} c")],
+                    choice_spans: Vec::new(),
+                }),
+                FragmentContent::Text(String::from(" d}")),
+            ]
+        );
+        assert_eq!(parsed.compile(), "{P {NUM} \"{G {{}a b} c\"} d}");
+
+        let choices = parsed.choices().next().unwrap();
+        assert_eq!(choices.choice_spans, vec![(3, 8), (9, 23)]);
+        let nested = ParsedString::parse_with_choice_contents(&choices.choices[1]).unwrap();
+        assert_eq!(nested.compile(), "{G {{}a b} c");
+        assert!(matches!(
+            nested.fragments[0].content,
+            FragmentContent::Choice(_)
+        ));
+
+        // Other commands still end at the first '}'.
+        let parsed = ParsedString::parse_with_choice_contents("{#a {b} {G=m}").unwrap();
+        assert_eq!(
+            contents(&parsed),
+            vec![
+                FragmentContent::Comment(String::from("a {b")),
+                FragmentContent::Text(String::from(" ")),
+                FragmentContent::Gender(GenderDefinition {
+                    index: None,
+                    gender: String::from("m"),
+                }),
+            ]
+        );
+
+        let err = ParsedString::parse_with_choice_contents("a{P {NUM b}").unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::UnterminatedCommand);
+        assert_eq!(err.pos_begin, 1);
+
+        // Without the option, the first '}' ends the choice list.
+        let parsed = ParsedString::parse("{P {NUM} b}").unwrap();
+        assert_eq!(
+            parsed.choices().next().unwrap().choices,
+            vec![String::from("{NUM")]
+        );
+    }
 }
@@ -1,45 +1,329 @@
-use crate::commands::{CommandInfo, Occurence, COMMANDS};
-use crate::parser::{FragmentContent, ParsedString};
+use crate::commands::{CommandInfo, CustomCommand, Occurence, ParameterInfo, COMMANDS};
+use crate::parser::{
+    ChoiceList, FragmentContent, ParsedString, ParserError, StringCommand, StringFragment,
+};
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, HashMap};
+use unicode_normalization::UnicodeNormalization;
 
-#[derive(Debug, PartialEq, Copy, Clone)]
+#[derive(Debug, PartialEq, Copy, Clone, Default)]
 pub enum Dialect {
     NEWGRF,
     GAMESCRIPT,
+    #[default]
     OPENTTD,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Default, Clone)]
 pub struct LanguageConfig {
     pub dialect: Dialect,
     pub cases: Vec<String>,
     pub genders: Vec<String>,
     pub plural_count: usize,
+    #[serde(default)]
+    pub case_plural_counts: HashMap<String, usize>, //< replace 'plural_count' for translations of specific cases.
+    #[serde(default)]
+    pub require_explicit_indices: bool, //< translations must use '{n:CMD}' for all parameters.
+    #[serde(default)]
+    pub base_hygiene: bool, //< warn about translation-only constructs in base strings.
+    #[serde(default)]
+    pub compare_choice_counts: bool, //< warn if choice counts differ between base and translation.
+    #[serde(default)]
+    pub warn_dropped_gender_choice: bool, //< warn if the base has '{G ...}' for a parameter, but the translation does not.
+    #[serde(default)]
+    pub warn_empty_choice: bool, //< warn about empty choices in '{P ...}' and '{G ...}', except the first one.
+    #[serde(default)]
+    pub warn_non_count_plural: bool, //< warn about plurals referencing amounts of money, ids, ...
+    #[serde(default)]
+    pub occurrence_overrides: HashMap<String, Occurence>, //< replace the occurence of commands by command name.
+    #[serde(default)]
+    pub hint_unused_gender: bool, //< inform about '{G=...}', if the base has no gender-dependent output.
+    #[serde(default)]
+    pub compare_ellipsis: bool, //< warn if only one of base and translation ends with an ellipsis.
+    #[serde(default)]
+    pub forbid_reorder: bool, //< translations must use parameters in the same order as the base.
+    #[serde(default)]
+    pub warn_leading_newline: bool, //< warn if the translation starts with '{}', but the base does not.
+    #[serde(default)]
+    pub hint_case_translations: bool, //< inform about cases referenced by the base, when validating the default case.
+    #[serde(default)]
+    pub warn_unused_case: bool, //< inform about case selections of the translation, which the base does not use.
+    #[serde(default)]
+    pub max_plural_count: Option<usize>, //< sanity limit for 'plural_count', defaults to DEFAULT_MAX_PLURAL_COUNT.
+    #[serde(default)]
+    pub max_length: Option<usize>, //< warn about translations with longer plain text, in codepoints.
+    #[serde(default)]
+    pub normalize_on_error: bool, //< also return a normalized preview, if the string has errors.
+    #[serde(default)]
+    pub plural_has_other: bool, //< '{P ...}' may have an extra last choice as catch-all "other" form.
+    #[serde(default)]
+    pub custom_commands: Vec<CustomCommand>, //< string commands in addition to the built-in commands of the dialect.
+    #[serde(default)]
+    pub unknown_command_severity: Severity, //< severity of unknown commands; unless 'Error', they are treated like '{EXACT}' commands without parameters.
+    #[serde(default)]
+    pub gender_front_severity: Option<Severity>, //< severity of misplaced and duplicate '{G=...}', defaults to 'Warning'.
+    #[serde(default)]
+    pub case_insensitive_cases: bool, //< match case and gender names ignoring upper/lower case; normalization uses the spelling of 'cases' and 'genders'.
+    #[serde(default)]
+    pub canonical_command_order: bool, //< normalization sorts colour and font commands at the front of the string by name.
+    #[serde(default)]
+    pub report_normalization: bool, //< inform about each change of normalization, like added position indices.
+    #[serde(default)]
+    pub unicode_nfc: bool, //< normalization composes characters of text and choices to Unicode NFC, like 'e' and U+0301 to 'é'.
+    #[serde(default)]
+    pub parse_choice_contents: bool, //< choices can contain string commands, like '{P "{0:NUM} item" items}', which are validated against the signature.
 }
 
-#[derive(Debug, PartialEq)]
+/// Names of all dialects, as used in configurations.
+const DIALECT_NAMES: [&str; 3] = ["game-script", "newgrf", "openttd"];
+
+/// No language has more plural forms than this, unless configured otherwise.
+pub const DEFAULT_MAX_PLURAL_COUNT: usize = 6;
+
+/// Number of plural forms of the languages shipped with OpenTTD, by isocode.
+/// This follows the '##plural' forms of the OpenTTD language files, not CLDR.
+const LANGUAGE_PLURAL_COUNTS: [(&str, usize); 48] = [
+    ("af_ZA", 2),
+    ("be_BY", 3),
+    ("bg_BG", 2),
+    ("ca_ES", 2),
+    ("cs_CZ", 3),
+    ("da_DK", 2),
+    ("de_DE", 2),
+    ("el_GR", 2),
+    ("en_AU", 2),
+    ("en_GB", 2),
+    ("en_US", 2),
+    ("es_ES", 2),
+    ("es_MX", 2),
+    ("et_EE", 2),
+    ("eu_ES", 2),
+    ("fi_FI", 2),
+    ("fr_FR", 2),
+    ("fy_NL", 2),
+    ("ga_IE", 5),
+    ("gd_GB", 4),
+    ("gl_ES", 2),
+    ("he_IL", 2),
+    ("hr_HR", 3),
+    ("id_ID", 1),
+    ("it_IT", 2),
+    ("ja_JP", 1),
+    ("ko_KR", 2),
+    ("lb_LU", 2),
+    ("lt_LT", 3),
+    ("lv_LV", 3),
+    ("mk_MK", 2),
+    ("mt_MT", 4),
+    ("nb_NO", 2),
+    ("nl_NL", 2),
+    ("nn_NO", 2),
+    ("pl_PL", 3),
+    ("pt_BR", 2),
+    ("pt_PT", 2),
+    ("ro_RO", 3),
+    ("ru_RU", 3),
+    ("sk_SK", 3),
+    ("sl_SI", 4),
+    ("sr_RS", 3),
+    ("sv_SE", 2),
+    ("tr_TR", 1),
+    ("uk_UA", 3),
+    ("vi_VN", 1),
+    ("zh_CN", 1),
+];
+
+#[derive(Debug, PartialEq, Copy, Clone, Default)]
 pub enum Severity {
-    Error,   //< translation is broken, do not commit.
+    #[default]
+    Error, //< translation is broken, do not commit.
     Warning, //< translation has minor issues, but is probably better than no translation.
+    Info,    //< translation is fine, but may contain something unnecessary.
 }
 
-#[derive(Serialize, Debug, PartialEq)]
+/// Stable identifier of the kind of a ValidationError, for tools which must not depend on the message text.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum ErrorCode {
+    ParseError,                  //< the string cannot be parsed.
+    InvalidConfig, //< the language configuration cannot be parsed, like an unknown dialect.
+    InvalidArgument, //< another argument of a WASM API method cannot be parsed, like a list of strings.
+    InvalidBase,     //< the base string of a translation is invalid.
+    UnknownCommand,  //< unknown string command.
+    UnknownOverride, //< unknown string command in the occurrence overrides of the configuration.
+    PluralCountLimit, //< implausible plural count in the configuration.
+    CasesNotSupported, //< the dialect has no cases.
+    UnknownCase,     //< case is not in the language configuration.
+    CaseNotAllowed,  //< the command does not allow a case selection.
+    CaseTranslationsHint, //< the base references cases, which may need translations.
+    CaseInconsistency, //< spacing or markup around a parameter differs between cases.
+    UnexpectedPositionReference, //< the command cannot have a position reference.
+    MissingPositionReference, //< the command has no position reference, but one is required.
+    ParameterReordered, //< the parameters are in a different order than in the base.
+    ParameterMismatch, //< the parameter differs from the base.
+    ParameterOutOfRange, //< the base has no parameter at this position.
+    ParameterLimit,  //< the position is beyond the number of parameters the dialect supports.
+    MissingParameter, //< a parameter of the base is missing.
+    MissingCommand,  //< a non-parameter command of the base is missing.
+    UnexpectedCommand, //< a non-parameter command is not in the base.
+    OccurrenceMismatch, //< a command is used a different number of times than in the base.
+    GendersNotSupported, //< the dialect or language has no genders.
+    GenderNotAtFront, //< the gender definition is not at the front.
+    DuplicateGenderDefinition, //< more than one gender definition.
+    UnusedGenderDefinition, //< gender definition, but the base has no gender-dependent output.
+    UnknownGender,   //< gender is not in the language configuration.
+    MissingSubstringGender, //< inserted string does not define its gender.
+    PluralsNotSupported, //< the language has no plural choices.
+    PluralCountMismatch, //< wrong number of plural choices.
+    GenderCountMismatch, //< wrong number of gender choices.
+    ChoiceCountDiffers, //< different number of choices than the base.
+    DroppedGenderChoice, //< the base has a gender choice for a parameter, but the translation does not.
+    EmptyChoice,         //< a choice other than the first is empty, which is likely a typo.
+    TranslationTooLong,  //< the plain text of the translation exceeds 'max_length'.
+    Normalized,          //< normalization changed a string command, see 'report_normalization'.
+    InvalidChoiceReference, //< the choice list references a position without parameter.
+    InvalidSubindex,     //< the choice list references a sub-parameter, which does not exist.
+    MisplacedChoiceReference, //< the choice list has no position reference, and the inferred parameter is not next to it.
+    PluralNotAllowed,         //< the referenced parameter does not allow plurals.
+    GenderNotAllowed,         //< the referenced parameter does not allow genders.
+    NonCountPlural,           //< the referenced parameter is no count.
+    MissingChoiceParameter, //< the translation does not contain the parameter referenced by a choice list.
+    LineBreakInChoice,      //< a choice contains a line break.
+    CommandInChoice,        //< a choice contains something, which looks like a string command.
+    ControlCharacter,       //< the text contains a raw control character, like a tab.
+    RawLineBreak,           //< the text contains a raw line break, like CRLF, instead of '{}'.
+    TrailingSpaceCommand,   //< a space-like command, like '{NBSP}', at the end of a line.
+    MissingFollowingParameter, //< a modifier command is not directly followed by a number, like '{NUM}'.
+    NoPluralParameter, //< '{P ...}' is used, but the string has no parameter, which allows plurals.
+    UnusedCaseSelection, //< the translation selects cases, which the base does not use.
+    LeadingLineBreak,  //< the translation starts with a line break, but the base does not.
+    LeadingWhitespace, //< the translation starts with different whitespace than the base.
+    EllipsisMismatch,  //< only one of base and translation ends with an ellipsis.
+    UnbalancedColourStack, //< '{PUSH_COLOUR}' and '{POP_COLOUR}' do not match.
+    RedundantColour,   //< a colour command is directly overridden by another colour command.
+    TranslationOnly,   //< the base uses a construct, which only makes sense in translations.
+    MissingTranslation, //< a string of the base language has no translation.
+    UnknownString,     //< the translation is for a string, which is not in the base language.
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
 pub struct ValidationError {
     pub severity: Severity,
+    pub code: ErrorCode, //< kind of error, stable across changes of 'message'.
     pub pos_begin: Option<usize>, //< codepoint offset in input string
     pub pos_end: Option<usize>,
     pub message: String,
     pub suggestion: Option<String>,
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct ValidationResult {
-    pub errors: Vec<ValidationError>,
+    pub errors: Vec<ValidationError>, //< ordered by position and then severity, errors without position last.
     pub normalized: Option<String>,
 }
 
+#[derive(Serialize, Debug)]
+pub struct PairResult {
+    pub base: ValidationResult, //< result of validating the base string on its own.
+    pub translation: ValidationResult, //< result of validating the translation against the base.
+}
+
+#[derive(Serialize, Debug)]
+pub struct AnyValidationResult {
+    pub base_index: Option<usize>, //< index of the base, which the result belongs to. None, if there are no bases.
+    pub result: ValidationResult,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ValidationSummary {
+    pub committable: bool, //< there are no errors, the string can be committed.
+    pub error_count: usize,
+    pub warning_count: usize,
+    pub info_count: usize,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct CommandDescriptor {
+    pub name: &'static str,              //< name of the command.
+    pub norm_name: Option<&'static str>, //< normalized name, if the command is an alias like '{STRING1}'.
+    pub has_parameters: bool,
+    pub allow_case: bool,
+    pub allow_gender: bool, //< some parameter of the command allows '{G ...}'.
+    pub allow_plural: bool, //< some parameter of the command allows '{P ...}'.
+    pub occurence: Occurence,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct NewlineInfo {
+    pub command: &'static str, //< name of the newline command, i.e. "" for '{}'.
+    pub line_break: bool,      //< the newline command starts a new line when rendered.
+    pub strip_trailing_blanks: bool, //< normalization removes blanks at the end of each line.
+}
+
+#[derive(Serialize, Debug)]
+pub struct CommandAnnotation<'a> {
+    pub pos_begin: usize, //< codepoint offset in input string
+    pub pos_end: usize,
+    pub info: Option<CommandInfo<'a>>, //< None, if the command is unknown.
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct CommandDiff {
+    pub added: Vec<String>, //< normalized commands, which are only in the translation.
+    pub removed: Vec<String>, //< normalized commands, which are only in the base.
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct CountChange {
+    pub command: String, //< normalized command, like '{0:NUM}' or '{RED}'.
+    pub base_count: usize,
+    pub translation_count: usize,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct SignatureDiff {
+    pub added: Vec<String>, //< normalized commands, which are only in the translation.
+    pub removed: Vec<String>, //< normalized commands, which are only in the base.
+    pub count_changed: Vec<CountChange>, //< commands in both, but used a different number of times.
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct SignatureParameter {
+    pub position: usize,
+    pub name: String, //< normalized name of the command.
+    pub allow_case: bool,
+    pub parameters: Vec<ParameterInfo>, //< sub-parameters, like cargo type and amount of '{CARGO_LONG}'.
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct DialectSupport {
+    pub command: String,           //< name of the command, as written in the base.
+    pub rejected_by: Vec<Dialect>, //< dialects, in which the command is an error.
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct CoverageEntry<'a> {
+    pub choice: &'static str, //< "P" for plurals, "G" for genders.
+    pub pos: usize,           //< position of the parameter in the base.
+    pub subindex: usize, //< sub-parameter of the parameter, like the cargo amount of '{CARGO_LONG}'.
+    pub command: &'a str,
+    pub covered: bool, //< the translation has a choice list for this parameter.
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct CoverageReport<'a> {
+    pub parameters: Vec<CoverageEntry<'a>>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct AnnotationResult<'a> {
+    pub errors: Vec<ValidationError>,
+    pub commands: Vec<CommandAnnotation<'a>>,
+}
+
 impl Dialect {
+    /// All dialects, in the order of 'DIALECT_NAMES'.
+    pub const ALL: [Dialect; 3] = [Self::GAMESCRIPT, Self::NEWGRF, Self::OPENTTD];
+
     pub fn allow_cases(&self) -> bool {
         *self != Self::GAMESCRIPT
     }
@@ -48,6 +332,15 @@ impl Dialect {
         *self != Self::GAMESCRIPT
     }
 
+    /// Number of parameters a string can have, i.e. the highest position is one less.
+    pub fn max_parameters(&self) -> usize {
+        match self {
+            Self::NEWGRF => 16,     // NML string parameters
+            Self::GAMESCRIPT => 20, // ScriptText parameters
+            Self::OPENTTD => 32,    // strgen parameters
+        }
+    }
+
     pub fn as_str(&self) -> &'static str {
         match self {
             Self::NEWGRF => "newgrf",
@@ -57,6 +350,201 @@ impl Dialect {
     }
 }
 
+impl ErrorCode {
+    const ALL: [ErrorCode; 59] = [
+        Self::ParseError,
+        Self::InvalidConfig,
+        Self::InvalidArgument,
+        Self::InvalidBase,
+        Self::UnknownCommand,
+        Self::UnknownOverride,
+        Self::PluralCountLimit,
+        Self::CasesNotSupported,
+        Self::UnknownCase,
+        Self::CaseNotAllowed,
+        Self::CaseTranslationsHint,
+        Self::CaseInconsistency,
+        Self::UnexpectedPositionReference,
+        Self::MissingPositionReference,
+        Self::ParameterReordered,
+        Self::ParameterMismatch,
+        Self::ParameterOutOfRange,
+        Self::ParameterLimit,
+        Self::MissingParameter,
+        Self::MissingCommand,
+        Self::UnexpectedCommand,
+        Self::OccurrenceMismatch,
+        Self::GendersNotSupported,
+        Self::GenderNotAtFront,
+        Self::DuplicateGenderDefinition,
+        Self::UnusedGenderDefinition,
+        Self::UnknownGender,
+        Self::MissingSubstringGender,
+        Self::PluralsNotSupported,
+        Self::PluralCountMismatch,
+        Self::GenderCountMismatch,
+        Self::ChoiceCountDiffers,
+        Self::DroppedGenderChoice,
+        Self::EmptyChoice,
+        Self::TranslationTooLong,
+        Self::Normalized,
+        Self::InvalidChoiceReference,
+        Self::InvalidSubindex,
+        Self::MisplacedChoiceReference,
+        Self::PluralNotAllowed,
+        Self::GenderNotAllowed,
+        Self::NonCountPlural,
+        Self::MissingChoiceParameter,
+        Self::LineBreakInChoice,
+        Self::CommandInChoice,
+        Self::ControlCharacter,
+        Self::RawLineBreak,
+        Self::TrailingSpaceCommand,
+        Self::MissingFollowingParameter,
+        Self::NoPluralParameter,
+        Self::UnusedCaseSelection,
+        Self::LeadingLineBreak,
+        Self::LeadingWhitespace,
+        Self::EllipsisMismatch,
+        Self::UnbalancedColourStack,
+        Self::RedundantColour,
+        Self::TranslationOnly,
+        Self::MissingTranslation,
+        Self::UnknownString,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ParseError => "parse-error",
+            Self::InvalidConfig => "invalid-config",
+            Self::InvalidArgument => "invalid-argument",
+            Self::InvalidBase => "invalid-base",
+            Self::UnknownCommand => "unknown-command",
+            Self::UnknownOverride => "unknown-override",
+            Self::PluralCountLimit => "plural-count-limit",
+            Self::CasesNotSupported => "cases-not-supported",
+            Self::UnknownCase => "unknown-case",
+            Self::CaseNotAllowed => "case-not-allowed",
+            Self::CaseTranslationsHint => "case-translations-hint",
+            Self::CaseInconsistency => "case-inconsistency",
+            Self::UnexpectedPositionReference => "unexpected-position-reference",
+            Self::MissingPositionReference => "missing-position-reference",
+            Self::ParameterReordered => "parameter-reordered",
+            Self::ParameterMismatch => "parameter-mismatch",
+            Self::ParameterOutOfRange => "parameter-out-of-range",
+            Self::ParameterLimit => "parameter-limit",
+            Self::MissingParameter => "missing-parameter",
+            Self::MissingCommand => "missing-command",
+            Self::UnexpectedCommand => "unexpected-command",
+            Self::OccurrenceMismatch => "occurrence-mismatch",
+            Self::GendersNotSupported => "genders-not-supported",
+            Self::GenderNotAtFront => "gender-not-at-front",
+            Self::DuplicateGenderDefinition => "duplicate-gender-definition",
+            Self::UnusedGenderDefinition => "unused-gender-definition",
+            Self::UnknownGender => "unknown-gender",
+            Self::MissingSubstringGender => "missing-substring-gender",
+            Self::PluralsNotSupported => "plurals-not-supported",
+            Self::PluralCountMismatch => "plural-count-mismatch",
+            Self::GenderCountMismatch => "gender-count-mismatch",
+            Self::ChoiceCountDiffers => "choice-count-differs",
+            Self::DroppedGenderChoice => "dropped-gender-choice",
+            Self::EmptyChoice => "empty-choice",
+            Self::TranslationTooLong => "translation-too-long",
+            Self::Normalized => "normalized",
+            Self::InvalidChoiceReference => "invalid-choice-reference",
+            Self::InvalidSubindex => "invalid-subindex",
+            Self::MisplacedChoiceReference => "misplaced-choice-reference",
+            Self::PluralNotAllowed => "plural-not-allowed",
+            Self::GenderNotAllowed => "gender-not-allowed",
+            Self::NonCountPlural => "non-count-plural",
+            Self::MissingChoiceParameter => "missing-choice-parameter",
+            Self::LineBreakInChoice => "line-break-in-choice",
+            Self::CommandInChoice => "command-in-choice",
+            Self::ControlCharacter => "control-character",
+            Self::RawLineBreak => "raw-line-break",
+            Self::TrailingSpaceCommand => "trailing-space-command",
+            Self::MissingFollowingParameter => "missing-following-parameter",
+            Self::NoPluralParameter => "no-plural-parameter",
+            Self::UnusedCaseSelection => "unused-case-selection",
+            Self::LeadingLineBreak => "leading-line-break",
+            Self::LeadingWhitespace => "leading-whitespace",
+            Self::EllipsisMismatch => "ellipsis-mismatch",
+            Self::UnbalancedColourStack => "unbalanced-colour-stack",
+            Self::RedundantColour => "redundant-colour",
+            Self::TranslationOnly => "translation-only",
+            Self::MissingTranslation => "missing-translation",
+            Self::UnknownString => "unknown-string",
+        }
+    }
+}
+
+impl LanguageConfig {
+    /// Get the occurence of a command, taking 'occurrence_overrides' into account.
+    fn get_occurence(&self, info: &CommandInfo) -> Occurence {
+        self.occurrence_overrides
+            .get(info.name)
+            .copied()
+            .unwrap_or(info.occurence)
+    }
+
+    /// Find a string command by name, see 'find_command'.
+    fn find_command(&self, name: &str) -> Option<CommandInfo<'_>> {
+        find_command(&self.dialect, &self.custom_commands, name)
+    }
+
+    /// Find the configured spelling of a case or gender name, taking 'case_insensitive_cases' into account.
+    fn find_name<'a>(&self, names: &'a [String], name: &str) -> Option<&'a String> {
+        names.iter().find(|n| *n == name).or_else(|| {
+            let lower = name.to_lowercase();
+            names
+                .iter()
+                .find(|n| self.case_insensitive_cases && n.to_lowercase() == lower)
+        })
+    }
+
+    /// Get the number of plural forms for translations of a case, taking 'case_plural_counts' into account.
+    fn get_plural_count(&self, case: &str) -> usize {
+        self.case_plural_counts
+            .get(case)
+            .copied()
+            .unwrap_or(self.plural_count)
+    }
+}
+
+impl ValidationResult {
+    /// Whether any message has severity 'Error', i.e. the string must not be committed.
+    pub fn has_errors(&self) -> bool {
+        self.errors.iter().any(|e| e.severity == Severity::Error)
+    }
+
+    /// Whether any message has severity 'Warning'.
+    pub fn has_warnings(&self) -> bool {
+        self.errors.iter().any(|e| e.severity == Severity::Warning)
+    }
+
+    /// Whether there are neither errors nor warnings. Messages with severity 'Info' are allowed.
+    pub fn is_clean(&self) -> bool {
+        !self.has_errors() && !self.has_warnings()
+    }
+
+    /// Count the errors by severity, and decide whether the string can be committed.
+    pub fn summary(&self) -> ValidationSummary {
+        let count = |severity: Severity| {
+            self.errors
+                .iter()
+                .filter(|e| e.severity == severity)
+                .count()
+        };
+        let error_count = count(Severity::Error);
+        ValidationSummary {
+            committable: error_count == 0,
+            error_count,
+            warning_count: count(Severity::Warning),
+            info_count: count(Severity::Info),
+        }
+    }
+}
+
 impl TryFrom<&str> for Dialect {
     type Error = String;
 
@@ -65,11 +553,23 @@ impl TryFrom<&str> for Dialect {
             "newgrf" => Ok(Dialect::NEWGRF),
             "game-script" => Ok(Dialect::GAMESCRIPT),
             "openttd" => Ok(Dialect::OPENTTD),
-            _ => Err(String::from("Unknown dialect")),
+            _ => Err(format!(
+                "Unknown dialect '{}'. Known dialects are: '{}'",
+                value,
+                DIALECT_NAMES.join("', '")
+            )),
         }
     }
 }
 
+impl std::str::FromStr for Dialect {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Dialect::try_from(value)
+    }
+}
+
 impl<'de> Deserialize<'de> for Dialect {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -77,12 +577,7 @@ impl<'de> Deserialize<'de> for Dialect {
     {
         let string = String::deserialize(deserializer)?;
         let value = Dialect::try_from(string.as_str());
-        value.map_err(|_| {
-            serde::de::Error::unknown_variant(
-                string.as_str(),
-                &["game-script", "newgrf", "openttd"],
-            )
-        })
+        value.map_err(|_| serde::de::Error::unknown_variant(string.as_str(), &DIALECT_NAMES))
     }
 }
 
@@ -95,6 +590,28 @@ impl Serialize for Dialect {
     }
 }
 
+impl Serialize for ErrorCode {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for ErrorCode {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let string = String::deserialize(deserializer)?;
+        Self::ALL
+            .into_iter()
+            .find(|code| code.as_str() == string)
+            .ok_or_else(|| serde::de::Error::custom(format!("unknown error code '{}'", string)))
+    }
+}
+
 impl Serialize for Severity {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -103,8 +620,144 @@ impl Serialize for Severity {
         serializer.serialize_str(match self {
             Self::Error => "error",
             Self::Warning => "warning",
+            Self::Info => "info",
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for Severity {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let string = String::deserialize(deserializer)?;
+        match string.as_str() {
+            "error" => Ok(Self::Error),
+            "warning" => Ok(Self::Warning),
+            "info" => Ok(Self::Info),
+            _ => Err(serde::de::Error::unknown_variant(
+                string.as_str(),
+                &["error", "warning", "info"],
+            )),
+        }
+    }
+}
+
+/**
+ * Look up the number of plural forms of a language.
+ *
+ * @param code The isocode of the language, like "en_GB" or "en-GB", or only the language part, like "en".
+ *
+ * @returns The number of plural forms, or None if the language is unknown.
+ */
+pub fn plural_count_for_language(code: &str) -> Option<usize> {
+    let code = code.replace('-', "_");
+    LANGUAGE_PLURAL_COUNTS
+        .iter()
+        .find(|(isocode, _)| *isocode == code || isocode.split('_').next() == Some(code.as_str()))
+        .map(|(_, count)| *count)
+}
+
+/**
+ * List the string commands, which are available in a dialect, for example for auto-completion.
+ *
+ * @param dialect The dialect of the string.
+ *
+ * @returns One descriptor per command, including aliases like '{STRING1}'.
+ */
+pub fn list_commands(dialect: &Dialect) -> Vec<CommandDescriptor> {
+    COMMANDS
+        .iter()
+        .filter(|info| info.dialects.contains(dialect))
+        .map(|info| CommandDescriptor {
+            name: info.name,
+            norm_name: info.norm_name,
+            has_parameters: !info.parameters.is_empty(),
+            allow_case: info.allow_case,
+            allow_gender: info.parameters.iter().any(|par| par.allow_gender),
+            allow_plural: info.parameters.iter().any(|par| par.allow_plural),
+            occurence: info.occurence,
         })
+        .collect()
+}
+
+/**
+ * Describe how the newline command '{}' behaves in a dialect.
+ *
+ * @param dialect The dialect of the string.
+ *
+ * @returns The newline behaviour of the dialect.
+ */
+pub fn newline_info(dialect: &Dialect) -> NewlineInfo {
+    match dialect {
+        // NML, strgen for game-scripts and strgen for OpenTTD all translate '{}' into a line break.
+        Dialect::NEWGRF | Dialect::GAMESCRIPT | Dialect::OPENTTD => NewlineInfo {
+            command: "",
+            line_break: true,
+            strip_trailing_blanks: true,
+        },
+    }
+}
+
+/**
+ * Check the language configuration for mistakes, which would affect the validation of every string.
+ *
+ * @param config The language configuration to check.
+ *
+ * @returns A list of error messages, if the configuration is implausible or invalid.
+ */
+pub fn validate_config(config: &LanguageConfig) -> Vec<ValidationError> {
+    let mut errors = validate_occurrence_overrides(config);
+    errors.extend(validate_custom_commands(config));
+    let max_plural_count = config.max_plural_count.unwrap_or(DEFAULT_MAX_PLURAL_COUNT);
+    if config.plural_count > max_plural_count {
+        errors.push(ValidationError {
+            severity: Severity::Warning,
+            code: ErrorCode::PluralCountLimit,
+            pos_begin: None,
+            pos_end: None,
+            message: format!(
+                "Plural count {} exceeds the maximum of {} plural forms.",
+                config.plural_count, max_plural_count
+            ),
+            suggestion: Some(String::from(
+                "Check the plural count of the language configuration.",
+            )),
+        });
+    }
+    let mut case_plural_counts: Vec<(&String, &usize)> = config.case_plural_counts.iter().collect();
+    case_plural_counts.sort();
+    for (case, &plural_count) in case_plural_counts {
+        if plural_count > max_plural_count {
+            errors.push(ValidationError {
+                severity: Severity::Warning,
+                code: ErrorCode::PluralCountLimit,
+                pos_begin: None,
+                pos_end: None,
+                message: format!(
+                    "Plural count {} of case '{}' exceeds the maximum of {} plural forms.",
+                    plural_count, case, max_plural_count
+                ),
+                suggestion: Some(String::from(
+                    "Check the plural count of the language configuration.",
+                )),
+            });
+        }
     }
+    errors
+}
+
+/// Order errors by position, errors without position last; and errors at the same position by severity.
+/// The sort is stable, so otherwise the errors keep the order in which they were found.
+fn sort_errors(errors: &mut [ValidationError]) {
+    errors.sort_by_key(|e| {
+        let rank = match e.severity {
+            Severity::Error => 0,
+            Severity::Warning => 1,
+            Severity::Info => 2,
+        };
+        (e.pos_begin.unwrap_or(usize::MAX), rank)
+    });
 }
 
 /**
@@ -115,12 +768,13 @@ impl Serialize for Severity {
  *
  * @returns A normalized form of the base string for translators, and a list of error messages, if the base is invalid.
  */
-pub fn validate_base(config: &LanguageConfig, base: &String) -> ValidationResult {
-    let mut base = match ParsedString::parse(&base) {
+pub fn validate_base(config: &LanguageConfig, base: &str) -> ValidationResult {
+    let mut base = match parse_string(config, base) {
         Err(err) => {
             return ValidationResult {
                 errors: vec![ValidationError {
                     severity: Severity::Error,
+                    code: ErrorCode::ParseError,
                     pos_begin: Some(err.pos_begin),
                     pos_end: err.pos_end,
                     message: err.message,
@@ -131,20 +785,17 @@ pub fn validate_base(config: &LanguageConfig, base: &String) -> ValidationResult
         }
         Ok(parsed) => parsed,
     };
-    let errs = validate_string(&config, &base, None);
-    if errs.iter().any(|e| e.severity == Severity::Error) {
-        ValidationResult {
-            errors: errs,
-            normalized: None,
-        }
-    } else {
-        sanitize_whitespace(&mut base);
-        normalize_string(&config.dialect, &mut base);
-        ValidationResult {
-            errors: errs,
-            normalized: Some(base.compile()),
-        }
+    let mut result = ValidationResult {
+        errors: validate_string(&config, &base, None),
+        normalized: None,
+    };
+    if config.normalize_on_error || !result.has_errors() {
+        let notices = normalize_parsed(config, &mut base);
+        result.errors.extend(notices);
+        result.normalized = Some(base.compile());
     }
+    sort_errors(&mut result.errors);
+    result
 }
 
 /**
@@ -159,30 +810,173 @@ pub fn validate_base(config: &LanguageConfig, base: &String) -> ValidationResult
  */
 pub fn validate_translation(
     config: &LanguageConfig,
-    base: &String,
-    case: &String,
-    translation: &String,
+    base: &str,
+    case: &str,
+    translation: &str,
 ) -> ValidationResult {
-    let base = match ParsedString::parse(&base) {
+    let base = match parse_string(config, base) {
         Err(_) => {
             return ValidationResult {
-                errors: vec![ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: None,
-                    pos_end: None,
-                    message: String::from("Base language text is invalid."),
-                    suggestion: Some(String::from("This is a bug; wait until it is fixed.")),
-                }],
+                errors: vec![invalid_base_error()],
                 normalized: None,
             };
         }
         Ok(parsed) => parsed,
     };
+    let signature = get_signature(config, &base).ok();
+    validate_parsed_translation(config, &base, signature.as_ref(), case, translation)
+}
+
+/**
+ * Validate a base string and a translation in one call.
+ * The base is validated like by 'validate_base' for the base language: without cases and genders, and with 2 plural forms.
+ *
+ * @param config The language configuration of the translation.
+ * @param base The base string.
+ * @param case The case of the translation. Use "default" for the default case.
+ * @param translation The translation to validate.
+ *
+ * @returns The results for base and translation. If the base has errors, the translation is not validated.
+ */
+pub fn validate_pair(
+    config: &LanguageConfig,
+    base: &str,
+    case: &str,
+    translation: &str,
+) -> PairResult {
+    let base_config = LanguageConfig {
+        cases: vec![],
+        genders: vec![],
+        plural_count: 2,
+        case_plural_counts: HashMap::new(),
+        ..config.clone()
+    };
+    let base_result = validate_base(&base_config, base);
+    let translation_result = if base_result.has_errors() {
+        ValidationResult {
+            errors: vec![ValidationError {
+                severity: Severity::Error,
+                code: ErrorCode::InvalidBase,
+                pos_begin: None,
+                pos_end: None,
+                message: String::from(
+                    "The translation was not validated, because the base string is invalid.",
+                ),
+                suggestion: Some(String::from("Fix the errors of the base string first.")),
+            }],
+            normalized: None,
+        }
+    } else {
+        validate_translation(config, base, case, translation)
+    };
+    PairResult {
+        base: base_result,
+        translation: translation_result,
+    }
+}
+
+/**
+ * Validate whether a translation is valid for any of several acceptable base strings.
+ *
+ * @param config The language configuration to validate against.
+ * @param bases The base strings to validate against, like singular and plural variants.
+ * @param case The case of the translation. Use "default" for the default case.
+ * @param translation The translation to validate.
+ *
+ * @returns The result for the first base without any errors, warnings and notices are allowed. If there is none, the result with the fewest errors.
+ *          Ties are resolved in favour of the earlier base.
+ */
+pub fn validate_translation_any(
+    config: &LanguageConfig,
+    bases: &[String],
+    case: &str,
+    translation: &str,
+) -> AnyValidationResult {
+    // rank results by number of errors, then warnings, then infos
+    let rank = |result: &ValidationResult| {
+        let summary = result.summary();
+        (
+            summary.error_count,
+            summary.warning_count,
+            summary.info_count,
+        )
+    };
+    let mut best: Option<(usize, ValidationResult)> = None;
+    for (index, base) in bases.iter().enumerate() {
+        let result = validate_translation(config, base, case, translation);
+        if !result.has_errors() {
+            best = Some((index, result));
+            break;
+        }
+        let is_better = best.as_ref().is_none_or(|(_, b)| rank(&result) < rank(b));
+        if is_better {
+            best = Some((index, result));
+        }
+    }
+    match best {
+        Some((index, result)) => AnyValidationResult {
+            base_index: Some(index),
+            result,
+        },
+        None => AnyValidationResult {
+            base_index: None,
+            result: ValidationResult {
+                errors: vec![invalid_base_error()],
+                normalized: None,
+            },
+        },
+    }
+}
+
+/**
+ * Validate multiple translations for the same base string.
+ * This is the same as calling 'validate_translation' for each translation, but parses the base string only once.
+ *
+ * @param config The language configuration to validate against.
+ * @param base The base string to validate against.
+ * @param translations The translations to validate as pairs of case and translation.
+ *
+ * @returns For each translation in order, a normalized form of the translation, and a list of error messages.
+ */
+pub fn validate_translation_batch(
+    config: &LanguageConfig,
+    base: &str,
+    translations: &[(String, String)],
+) -> Vec<ValidationResult> {
+    let base = match parse_string(config, base) {
+        Err(_) => {
+            return translations
+                .iter()
+                .map(|_| ValidationResult {
+                    errors: vec![invalid_base_error()],
+                    normalized: None,
+                })
+                .collect();
+        }
+        Ok(parsed) => parsed,
+    };
+    let signature = get_signature(config, &base).ok();
+    translations
+        .iter()
+        .map(|(case, translation)| {
+            validate_parsed_translation(config, &base, signature.as_ref(), case, translation)
+        })
+        .collect()
+}
+
+fn validate_parsed_translation(
+    config: &LanguageConfig,
+    base: &ParsedString,
+    signature: Option<&StringSignature>,
+    case: &str,
+    translation: &str,
+) -> ValidationResult {
     if case != "default" {
         if !config.dialect.allow_cases() {
             return ValidationResult {
                 errors: vec![ValidationError {
                     severity: Severity::Error,
+                    code: ErrorCode::CasesNotSupported,
                     pos_begin: None,
                     pos_end: None,
                     message: String::from("No cases allowed."),
@@ -190,10 +984,11 @@ pub fn validate_translation(
                 }],
                 normalized: None,
             };
-        } else if !config.cases.contains(&case) {
+        } else if config.find_name(&config.cases, case).is_none() {
             return ValidationResult {
                 errors: vec![ValidationError {
                     severity: Severity::Error,
+                    code: ErrorCode::UnknownCase,
                     pos_begin: None,
                     pos_end: None,
                     message: format!("Unknown case '{}'.", case),
@@ -203,11 +998,12 @@ pub fn validate_translation(
             };
         }
     }
-    let mut translation = match ParsedString::parse(&translation) {
+    let mut translation = match parse_string(config, translation) {
         Err(err) => {
             return ValidationResult {
                 errors: vec![ValidationError {
                     severity: Severity::Error,
+                    code: ErrorCode::ParseError,
                     pos_begin: Some(err.pos_begin),
                     pos_end: err.pos_end,
                     message: err.message,
@@ -218,1390 +1014,6799 @@ pub fn validate_translation(
         }
         Ok(parsed) => parsed,
     };
-    let errs = validate_string(&config, &translation, Some(&base));
-    if errs.iter().any(|e| e.severity == Severity::Error) {
-        ValidationResult {
-            errors: errs,
-            normalized: None,
-        }
-    } else {
-        sanitize_whitespace(&mut translation);
-        normalize_string(&config.dialect, &mut translation);
-        ValidationResult {
-            errors: errs,
-            normalized: Some(translation.compile()),
+    let mut errs = validate_occurrence_overrides(config);
+    errs.extend(validate_custom_commands(config));
+    if errs.is_empty() {
+        errs = match signature {
+            Some(signature) => validate_string_signature(
+                config,
+                &translation,
+                Some(base),
+                signature,
+                config.get_plural_count(case),
+                &mut Vec::new(),
+            ),
+            None => vec![invalid_base_error()],
+        };
+    }
+    if case == "default" && config.hint_case_translations && !config.cases.is_empty() {
+        let mut base_cases: Vec<&String> = base
+            .commands()
+            .filter_map(|cmd| cmd.case.as_ref())
+            .collect();
+        base_cases.sort();
+        base_cases.dedup();
+        if !base_cases.is_empty() {
+            let cases = base_cases
+                .iter()
+                .map(|c| c.as_str())
+                .collect::<Vec<_>>()
+                .join("', '");
+            errs.push(ValidationError {
+                severity: Severity::Info,
+                code: ErrorCode::CaseTranslationsHint,
+                pos_begin: None,
+                pos_end: None,
+                message: format!(
+                    "The base references the cases '{}', case-specific translations may be required.",
+                    cases
+                ),
+                suggestion: Some(format!("Also translate the cases '{}'.", cases)),
+            });
         }
     }
+    let mut result = ValidationResult {
+        errors: errs,
+        normalized: None,
+    };
+    if config.normalize_on_error || !result.has_errors() {
+        let notices = normalize_parsed(config, &mut translation);
+        result.errors.extend(notices);
+        result.normalized = Some(translation.compile());
+    }
+    sort_errors(&mut result.errors);
+    result
 }
 
-fn remove_ascii_ctrl(t: &mut String) {
-    *t = t.replace(|c| char::is_ascii_control(&c), " ");
-}
-
-fn remove_trailing_blanks(t: &mut String) {
-    t.truncate(t.trim_end().len());
-}
-
-/// Replace all ASCII control codes with blank.
-/// Remove trailing blanks at end of each line.
-fn sanitize_whitespace(parsed: &mut ParsedString) {
-    let mut is_eol = true;
-    for i in (0..parsed.fragments.len()).rev() {
-        let mut is_nl = false;
-        match &mut parsed.fragments[i].content {
-            FragmentContent::Text(t) => {
-                remove_ascii_ctrl(t);
-                if is_eol {
-                    remove_trailing_blanks(t);
-                }
-            }
-            FragmentContent::Choice(c) => {
-                for t in &mut c.choices {
-                    remove_ascii_ctrl(t);
-                }
+/**
+ * Validate a string, and resolve the command information of each string command.
+ *
+ * @param config The language configuration to validate against.
+ * @param base The base string to validate against, or None if 'string' is a base string itself.
+ * @param string The string to validate and annotate.
+ *
+ * @returns The resolved command information for each string command in order, and a list of error messages.
+ */
+pub fn annotate_commands<'a>(
+    config: &'a LanguageConfig,
+    base: Option<&str>,
+    string: &str,
+) -> AnnotationResult<'a> {
+    let base = match base.map(ParsedString::parse).transpose() {
+        Err(_) => {
+            return AnnotationResult {
+                errors: vec![invalid_base_error()],
+                commands: Vec::new(),
+            };
+        }
+        Ok(parsed) => parsed,
+    };
+    let string = match ParsedString::parse(string) {
+        Err(err) => {
+            return AnnotationResult {
+                errors: vec![ValidationError {
+                    severity: Severity::Error,
+                    code: ErrorCode::ParseError,
+                    pos_begin: Some(err.pos_begin),
+                    pos_end: err.pos_end,
+                    message: err.message,
+                    suggestion: None,
+                }],
+                commands: Vec::new(),
+            };
+        }
+        Ok(parsed) => parsed,
+    };
+    let mut commands = Vec::new();
+    let errors = validate_string_annotated(config, &string, base.as_ref(), &mut commands);
+    AnnotationResult { errors, commands }
+}
+
+/**
+ * Check whether normalization would change a string, without validating it.
+ *
+ * @param config The language configuration of the string.
+ * @param string The string to check.
+ *
+ * @returns Whether the normalized form differs from the input, or the parser error.
+ */
+pub fn would_normalize_change(
+    config: &LanguageConfig,
+    string: &str,
+) -> Result<bool, ValidationError> {
+    Ok(normalize(config, string)? != string)
+}
+
+/**
+ * Normalize multiple strings, without validating them.
+ *
+ * @param config The language configuration of the strings.
+ * @param strings The strings to normalize.
+ *
+ * @returns For each string in order, the normalized form, or the parser error.
+ */
+pub fn normalize_batch(
+    config: &LanguageConfig,
+    strings: &[String],
+) -> Vec<Result<String, ValidationError>> {
+    strings.iter().map(|s| normalize(config, s)).collect()
+}
+
+/**
+ * Compare which string commands a translation adds or removes compared to the base.
+ *
+ * @param config The language configuration of the strings.
+ * @param base The base string.
+ * @param translation The translation.
+ *
+ * @returns The normalized commands, which are only in the translation or only in the base, or the parser error.
+ */
+pub fn command_diff(
+    config: &LanguageConfig,
+    base: &str,
+    translation: &str,
+) -> Result<CommandDiff, ValidationError> {
+    let get_commands = |string: &str| -> Result<Vec<String>, ValidationError> {
+        let mut parsed = parse_string(config, string).map_err(|err| ValidationError {
+            severity: Severity::Error,
+            code: ErrorCode::ParseError,
+            pos_begin: Some(err.pos_begin),
+            pos_end: err.pos_end,
+            message: err.message,
+            suggestion: None,
+        })?;
+        normalize_parsed(config, &mut parsed);
+        Ok(parsed
+            .fragments
+            .iter()
+            .filter(|f| matches!(f.content, FragmentContent::Command(_)))
+            .map(|f| f.content.compile())
+            .collect())
+    };
+    let mut removed = get_commands(base)?;
+    let mut added = Vec::new();
+    for cmd in get_commands(translation)? {
+        match removed.iter().position(|c| *c == cmd) {
+            Some(i) => {
+                removed.remove(i);
             }
-            FragmentContent::Command(c) => {
-                is_nl = c.name.is_empty();
+            None => added.push(cmd),
+        }
+    }
+    Ok(CommandDiff { added, removed })
+}
+
+/**
+ * Compare the signatures of base and translation: the parameters by position, and the other commands by name.
+ *
+ * @param config The language configuration of the translation.
+ * @param base The base string.
+ * @param translation The translation.
+ *
+ * @returns The commands, which the translation adds, removes or uses a different number of times, or the first error of the translation.
+ */
+pub fn signature_diff(
+    config: &LanguageConfig,
+    base: &str,
+    translation: &str,
+) -> Result<SignatureDiff, ValidationError> {
+    let base = ParsedString::parse(base).map_err(|_| invalid_base_error())?;
+    let base_sig = get_signature(config, &base).map_err(|_| invalid_base_error())?;
+    let translation = ParsedString::parse(translation).map_err(|err| ValidationError {
+        severity: Severity::Error,
+        code: ErrorCode::ParseError,
+        pos_begin: Some(err.pos_begin),
+        pos_end: err.pos_end,
+        message: err.message,
+        suggestion: None,
+    })?;
+    let trans_sig = get_signature(config, &translation).map_err(|mut errs| errs.remove(0))?;
+
+    let get_counts = |sig: &StringSignature| -> BTreeMap<String, usize> {
+        let positional = sig
+            .parameters
+            .iter()
+            .map(|(pos, (info, count))| (format!("{{{}:{}}}", pos, info.get_norm_name()), *count));
+        let nonpositional = sig
+            .nonpositional_count
+            .iter()
+            .map(|(name, (_, count))| (format!("{{{}}}", name), *count));
+        positional.chain(nonpositional).collect()
+    };
+    let base_counts = get_counts(&base_sig);
+    let trans_counts = get_counts(&trans_sig);
+
+    let mut diff = SignatureDiff {
+        added: Vec::new(),
+        removed: Vec::new(),
+        count_changed: Vec::new(),
+    };
+    for (command, &base_count) in &base_counts {
+        match trans_counts.get(command) {
+            None => diff.removed.push(command.clone()),
+            Some(&translation_count) if translation_count != base_count => {
+                diff.count_changed.push(CountChange {
+                    command: command.clone(),
+                    base_count,
+                    translation_count,
+                })
             }
-            _ => (),
+            Some(_) => (),
         }
-        is_eol = is_nl;
     }
+    diff.added = trans_counts
+        .into_keys()
+        .filter(|command| !base_counts.contains_key(command))
+        .collect();
+    Ok(diff)
 }
 
-struct StringSignature {
-    parameters: HashMap<usize, (&'static CommandInfo<'static>, usize)>,
-    nonpositional_count: BTreeMap<String, (Occurence, usize)>,
-    // TODO track color/lineno/colorstack for positional parameters
+/**
+ * List the parameters of a base string, for example to generate typed accessors for it.
+ *
+ * @param config The language configuration of the base language.
+ * @param base The base string.
+ *
+ * @returns The parameters ordered by position, or the errors of the base string.
+ */
+pub fn base_signature(
+    config: &LanguageConfig,
+    base: &str,
+) -> Result<Vec<SignatureParameter>, Vec<ValidationError>> {
+    let base = ParsedString::parse(base).map_err(|err| {
+        vec![ValidationError {
+            severity: Severity::Error,
+            code: ErrorCode::ParseError,
+            pos_begin: Some(err.pos_begin),
+            pos_end: err.pos_end,
+            message: err.message,
+            suggestion: None,
+        }]
+    })?;
+    let signature = get_signature(config, &base)?;
+    let mut parameters: Vec<SignatureParameter> = signature
+        .parameters
+        .iter()
+        .map(|(pos, (info, _))| SignatureParameter {
+            position: *pos,
+            name: String::from(info.get_norm_name()),
+            allow_case: info.allow_case,
+            parameters: info.parameters.to_vec(),
+        })
+        .collect();
+    parameters.sort_by_key(|p| p.position);
+    Ok(parameters)
 }
 
-fn get_signature(
-    dialect: &Dialect,
-    base: &ParsedString,
-) -> Result<StringSignature, Vec<ValidationError>> {
-    let mut errors = Vec::new();
-    let mut signature = StringSignature {
-        parameters: HashMap::new(),
-        nonpositional_count: BTreeMap::new(),
-    };
+/**
+ * Validate a base string against every dialect, for example for base strings shared between OpenTTD and NewGRFs.
+ *
+ * @param config The language configuration of the base language. Its dialect is ignored.
+ * @param base The base string.
+ *
+ * @returns For each distinct command of the base in order of appearance, the dialects which reject it;
+ *          or the parser error of the base string.
+ */
+pub fn validate_base_all_dialects(
+    config: &LanguageConfig,
+    base: &str,
+) -> Result<Vec<DialectSupport>, Vec<ValidationError>> {
+    let base = ParsedString::parse(base).map_err(|err| {
+        vec![ValidationError {
+            severity: Severity::Error,
+            code: ErrorCode::ParseError,
+            pos_begin: Some(err.pos_begin),
+            pos_end: err.pos_end,
+            message: err.message,
+            suggestion: None,
+        }]
+    })?;
 
-    let mut pos = 0;
+    let mut result: Vec<DialectSupport> = Vec::new();
     for fragment in &base.fragments {
         if let FragmentContent::Command(cmd) = &fragment.content {
-            if let Some(info) = COMMANDS
-                .into_iter()
-                .find(|ci| ci.name == cmd.name && ci.dialects.contains(&dialect))
-            {
-                if info.parameters.is_empty() {
-                    if let Some(index) = cmd.index {
-                        errors.push(ValidationError {
-                            severity: Severity::Error,
-                            pos_begin: Some(fragment.pos_begin),
-                            pos_end: Some(fragment.pos_end),
-                            message: format!(
-                                "Command '{{{}}}' cannot have a position reference.",
-                                cmd.name
-                            ),
-                            suggestion: Some(format!("Remove '{}:'.", index)),
-                        });
-                    }
-                    let norm_name = String::from(info.get_norm_name());
-                    if let Some(existing) = signature.nonpositional_count.get_mut(&norm_name) {
-                        existing.1 += 1;
-                    } else {
-                        signature
-                            .nonpositional_count
-                            .insert(norm_name, (info.occurence.clone(), 1));
-                    }
-                } else {
-                    if let Some(index) = cmd.index {
-                        pos = index;
-                    }
-                    if let Some(existing) = signature.parameters.get_mut(&pos) {
-                        existing.1 += 1;
-                    } else {
-                        signature.parameters.insert(pos, (info, 1));
+            if !result.iter().any(|r| r.command == cmd.name) {
+                result.push(DialectSupport {
+                    command: cmd.name.clone(),
+                    rejected_by: Vec::new(),
+                });
+            }
+        }
+    }
+
+    for dialect in Dialect::ALL {
+        let dialect_config = LanguageConfig {
+            dialect,
+            ..config.clone()
+        };
+        let errors = get_signature(&dialect_config, &base)
+            .err()
+            .unwrap_or_default();
+        for fragment in &base.fragments {
+            let FragmentContent::Command(cmd) = &fragment.content else {
+                continue;
+            };
+            let rejected = errors
+                .iter()
+                .any(|e| e.severity == Severity::Error && e.pos_begin == Some(fragment.pos_begin));
+            let entry = result.iter_mut().find(|r| r.command == cmd.name).unwrap();
+            if rejected && !entry.rejected_by.contains(&dialect) {
+                entry.rejected_by.push(dialect);
+            }
+        }
+    }
+    Ok(result)
+}
+
+/**
+ * Check for each plural- and gender-capable parameter of the base, whether the translation has a choice list for it.
+ *
+ * @param config The language configuration of the translation.
+ * @param base The base string.
+ * @param translation The translation.
+ *
+ * @returns One entry per plural- or gender-capable parameter of the base, or the parser error.
+ */
+pub fn coverage_report<'a>(
+    config: &'a LanguageConfig,
+    base: &str,
+    translation: &str,
+) -> Result<CoverageReport<'a>, ValidationError> {
+    let base = ParsedString::parse(base).map_err(|_| invalid_base_error())?;
+    let signature = get_signature(config, &base).map_err(|_| invalid_base_error())?;
+    let translation = ParsedString::parse(translation).map_err(|err| ValidationError {
+        severity: Severity::Error,
+        code: ErrorCode::ParseError,
+        pos_begin: Some(err.pos_begin),
+        pos_end: err.pos_end,
+        message: err.message,
+        suggestion: None,
+    })?;
+
+    let mut covered = Vec::new();
+    let mut pos = 0;
+    for fragment in &translation.fragments {
+        match &fragment.content {
+            FragmentContent::Command(cmd) => {
+                if let Some(info) = config.find_command(&cmd.name) {
+                    if !info.parameters.is_empty() {
+                        pos = cmd.index.unwrap_or(pos) + 1;
                     }
-                    pos += 1;
                 }
-            } else {
-                errors.push(ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: Some(fragment.pos_begin),
-                    pos_end: Some(fragment.pos_end),
-                    message: format!("Unknown string command '{{{}}}'.", cmd.name),
-                    suggestion: None,
-                });
             }
+            FragmentContent::Choice(cmd) => {
+                let opt_ref_pos = match cmd.name.as_str() {
+                    "P" => pos.checked_sub(1),
+                    "G" => Some(pos),
+                    _ => panic!(),
+                };
+                if let Some(ref_pos) = cmd.indexref.or(opt_ref_pos) {
+                    let def_subindex = match (cmd.name.as_str(), signature.parameters.get(&ref_pos))
+                    {
+                        ("P", Some((info, _))) => info.def_plural_subindex.unwrap_or(0),
+                        _ => 0,
+                    };
+                    covered.push((
+                        cmd.name.clone(),
+                        ref_pos,
+                        cmd.indexsubref.unwrap_or(def_subindex),
+                    ));
+                }
+            }
+            _ => (),
         }
     }
 
-    if errors.is_empty() {
-        Ok(signature)
-    } else {
-        Err(errors)
+    let mut positions: Vec<&usize> = signature.parameters.keys().collect();
+    positions.sort();
+    let mut parameters = Vec::new();
+    for pos in positions {
+        let info = signature.parameters[pos].0;
+        for (subindex, par_info) in info.parameters.iter().enumerate() {
+            for (choice, allowed) in [("P", par_info.allow_plural), ("G", par_info.allow_gender)] {
+                if allowed {
+                    parameters.push(CoverageEntry {
+                        choice,
+                        pos: *pos,
+                        subindex,
+                        command: info.get_norm_name(),
+                        covered: covered.contains(&(String::from(choice), *pos, subindex)),
+                    });
+                }
+            }
+        }
     }
+    Ok(CoverageReport { parameters })
 }
 
-fn validate_string(
+fn normalize(config: &LanguageConfig, string: &str) -> Result<String, ValidationError> {
+    let mut parsed = parse_string(config, string).map_err(|err| ValidationError {
+        severity: Severity::Error,
+        code: ErrorCode::ParseError,
+        pos_begin: Some(err.pos_begin),
+        pos_end: err.pos_end,
+        message: err.message,
+        suggestion: None,
+    })?;
+    normalize_parsed(config, &mut parsed);
+    Ok(parsed.compile())
+}
+
+/// Apply all normalizations of 'config' to a parsed string, like for the 'normalized' result of validations.
+/// Returns an 'Info' message for each change, if 'config.report_normalization' is set.
+fn normalize_parsed(config: &LanguageConfig, parsed: &mut ParsedString) -> Vec<ValidationError> {
+    sanitize_whitespace(&config.dialect, parsed);
+    if config.unicode_nfc {
+        compose_unicode(parsed);
+    }
+    if config.case_insensitive_cases {
+        canonicalize_names(config, parsed);
+    }
+    let before = config.report_normalization.then(|| parsed.clone());
+    normalize_string(&config.dialect, &config.custom_commands, parsed);
+    let notices = before.map_or_else(Vec::new, |before| normalization_notices(&before, parsed));
+    if config.canonical_command_order {
+        sort_leading_commands(config, parsed);
+    }
+    notices
+}
+
+/**
+ * Check that all cases of a translation use the same spacing and markup around parameters.
+ *
+ * @param config The language configuration of the translations.
+ * @param translations The translations of a single string as pairs of case and translation.
+ *
+ * @returns A list of warnings for parameters with inconsistent surroundings.
+ */
+pub fn validate_case_consistency(
     config: &LanguageConfig,
-    test: &ParsedString,
-    base: Option<&ParsedString>,
+    translations: &[(String, String)],
 ) -> Vec<ValidationError> {
-    let signature: StringSignature;
-    match get_signature(&config.dialect, base.unwrap_or(test)) {
-        Ok(sig) => signature = sig,
-        Err(msgs) => {
-            if base.is_some() {
-                return vec![ValidationError {
-                    severity: Severity::Error,
+    let mut errors = Vec::new();
+    let mut ref_case: Option<&String> = None;
+    let mut ref_surroundings: HashMap<usize, (String, String)> = HashMap::new();
+    for (case, translation) in translations {
+        let parsed = match ParsedString::parse(translation) {
+            Ok(parsed) => parsed,
+            Err(_) => continue,
+        };
+        let surroundings = get_parameter_surroundings(config, &parsed);
+        if let Some(ref_case) = ref_case {
+            let mut positions: Vec<&usize> = surroundings
+                .iter()
+                .filter(|(pos, s)| ref_surroundings.get(*pos).is_some_and(|r| r != *s))
+                .map(|(pos, _)| pos)
+                .collect();
+            positions.sort();
+            for pos in positions {
+                errors.push(ValidationError {
+                    severity: Severity::Warning,
+                    code: ErrorCode::CaseInconsistency,
                     pos_begin: None,
                     pos_end: None,
-                    message: String::from("Base language text is invalid."),
-                    suggestion: Some(String::from("This is a bug; wait until it is fixed.")),
-                }];
-            } else {
-                return msgs;
+                    message: format!(
+                        "Spacing or markup around position {} differs between case '{}' and case '{}'.",
+                        pos, ref_case, case
+                    ),
+                    suggestion: Some(format!("Use the same spacing as case '{}'.", ref_case)),
+                });
             }
+        } else {
+            ref_case = Some(case);
+            ref_surroundings = surroundings;
         }
     }
+    errors
+}
 
-    let mut errors = Vec::new();
-    let mut positional_count: HashMap<usize, usize> = HashMap::new();
-    let mut nonpositional_count: BTreeMap<String, (Occurence, usize)> = BTreeMap::new();
+/// Describe the spacing and markup directly before and after the first occurrence of each parameter.
+fn get_parameter_surroundings(
+    config: &LanguageConfig,
+    parsed: &ParsedString,
+) -> HashMap<usize, (String, String)> {
+    let describe =
+        |fragment: Option<&StringFragment>, before: bool| match fragment.map(|f| &f.content) {
+            Some(FragmentContent::Text(t)) => {
+                let c = if before {
+                    t.chars().last()
+                } else {
+                    t.chars().next()
+                };
+                String::from(if c.is_some_and(char::is_whitespace) {
+                    " "
+                } else {
+                    ""
+                })
+            }
+            Some(FragmentContent::Command(cmd)) => match config.find_command(&cmd.name) {
+                Some(info) if info.parameters.is_empty() => format!("{{{}}}", cmd.name),
+                _ => String::new(),
+            },
+            _ => String::new(),
+        };
+
+    let mut result = HashMap::new();
     let mut pos = 0;
-    let mut front = 0;
-    for fragment in &test.fragments {
-        match &fragment.content {
-            FragmentContent::Command(cmd) => {
-                let opt_expected = signature
-                    .parameters
-                    .get(&cmd.index.unwrap_or(pos))
-                    .map(|v| (*v).0);
-                let opt_info =
-                    opt_expected
-                        .filter(|ex| ex.get_norm_name() == cmd.name)
-                        .or(COMMANDS.into_iter().find(|ci| {
-                            ci.name == cmd.name && ci.dialects.contains(&config.dialect)
-                        }));
-                if let Some(info) = opt_info {
-                    if let Some(c) = &cmd.case {
-                        if !config.dialect.allow_cases() {
-                            errors.push(ValidationError {
-                                severity: Severity::Error,
-                                pos_begin: Some(fragment.pos_begin),
-                                pos_end: Some(fragment.pos_end),
-                                message: String::from("No case selections allowed."),
-                                suggestion: Some(format!("Remove '.{}'.", c)),
-                            });
-                        } else if !info.allow_case {
-                            errors.push(ValidationError {
-                                severity: Severity::Error,
-                                pos_begin: Some(fragment.pos_begin),
-                                pos_end: Some(fragment.pos_end),
-                                message: format!(
-                                    "No case selection allowed for '{{{}}}'.",
-                                    cmd.name
-                                ),
-                                suggestion: Some(format!("Remove '.{}'.", c)),
-                            });
-                        } else if !config.cases.contains(&c) {
-                            errors.push(ValidationError {
-                                severity: Severity::Error,
-                                pos_begin: Some(fragment.pos_begin),
-                                pos_end: Some(fragment.pos_end),
-                                message: format!("Unknown case '{}'.", c),
-                                suggestion: Some(format!(
-                                    "Known cases are: '{}'",
-                                    config.cases.join("', '")
-                                )),
-                            });
-                        }
-                    }
+    for (i, fragment) in parsed.fragments.iter().enumerate() {
+        if let FragmentContent::Command(cmd) = &fragment.content {
+            if let Some(info) = config.find_command(&cmd.name) {
+                if !info.parameters.is_empty() {
+                    pos = cmd.index.unwrap_or(pos);
+                    let before = describe(i.checked_sub(1).map(|j| &parsed.fragments[j]), true);
+                    let after = describe(parsed.fragments.get(i + 1), false);
+                    result.entry(pos).or_insert((before, after));
+                    pos += 1;
+                }
+            }
+        }
+    }
+    result
+}
 
-                    if info.parameters.is_empty() {
-                        if let Some(index) = cmd.index {
-                            errors.push(ValidationError {
-                                severity: Severity::Error,
-                                pos_begin: Some(fragment.pos_begin),
-                                pos_end: Some(fragment.pos_end),
-                                message: format!(
-                                    "Command '{{{}}}' cannot have a position reference.",
-                                    cmd.name
-                                ),
-                                suggestion: Some(format!("Remove '{}:'.", index)),
-                            });
-                        }
+/**
+ * Check that a string defines its gender, if it is inserted into a string, which has gender choices for it.
+ *
+ * @param config The language configuration of both strings.
+ * @param host The string, into which 'substring' is inserted.
+ * @param pos The position of the parameter in 'host', which 'substring' is inserted for.
+ * @param substring The inserted string.
+ *
+ * @returns A warning, if 'host' has '{G ...}' choices for position 'pos', but 'substring' does not start with '{G=...}'.
+ */
+pub fn validate_substring_gender(
+    config: &LanguageConfig,
+    host: &str,
+    pos: usize,
+    substring: &str,
+) -> Vec<ValidationError> {
+    if !config.dialect.allow_genders() || config.genders.len() < 2 {
+        return Vec::new();
+    }
+    let (host, substring) = match (ParsedString::parse(host), ParsedString::parse(substring)) {
+        (Ok(host), Ok(substring)) => (host, substring),
+        _ => return Vec::new(),
+    };
+    let gender_choices = get_choice_counts(config, &host).contains_key(&(String::from("G"), pos));
+    let has_gender = matches!(
+        substring.fragments.first().map(|f| &f.content),
+        Some(FragmentContent::Gender(_))
+    );
+    if gender_choices && !has_gender {
+        vec![ValidationError {
+            severity: Severity::Warning,
+            code: ErrorCode::MissingSubstringGender,
+            pos_begin: None,
+            pos_end: None,
+            message: format!(
+                "The string is inserted into position {} of a string with gender choices, but does not define its gender.",
+                pos
+            ),
+            suggestion: Some(String::from("Add '{G=...}' to the front of the string.")),
+        }]
+    } else {
+        Vec::new()
+    }
+}
 
-                        let norm_name = String::from(info.get_norm_name());
-                        if let Some(existing) = nonpositional_count.get_mut(&norm_name) {
-                            existing.1 += 1;
-                        } else {
-                            nonpositional_count.insert(norm_name, (info.occurence.clone(), 1));
-                        }
-                    } else {
-                        if let Some(index) = cmd.index {
-                            pos = index;
-                        }
+/**
+ * Describe in human-readable form how a string is interpreted.
+ *
+ * @param config The language configuration of the string.
+ * @param string The string to describe.
+ *
+ * @returns One line of explanation per parameter, gender definition and choice list.
+ */
+pub fn explain(config: &LanguageConfig, string: &str) -> Vec<String> {
+    let mut parsed = match ParsedString::parse(string) {
+        Ok(parsed) => parsed,
+        Err(err) => return vec![format!("Invalid string: {}", err.message)],
+    };
+    normalize_parsed(config, &mut parsed);
 
-                        if let Some(expected) = opt_expected {
-                            if expected.get_norm_name() == info.get_norm_name() {
-                                if let Some(existing) = positional_count.get_mut(&pos) {
-                                    *existing += 1;
-                                } else {
-                                    positional_count.insert(pos, 1);
-                                }
-                            } else {
-                                errors.push(ValidationError {
-                                    severity: Severity::Error,
-                                    pos_begin: Some(fragment.pos_begin),
-                                    pos_end: Some(fragment.pos_end),
-                                    message: format!(
-                                        "Expected '{{{}:{}}}', found '{{{}}}'.",
-                                        pos, expected.name, cmd.name
-                                    ),
-                                    suggestion: None,
-                                })
-                            }
+    let mut lines = Vec::new();
+    let mut explained = Vec::new();
+    for fragment in &parsed.fragments {
+        match &fragment.content {
+            FragmentContent::Command(cmd) => match config.find_command(&cmd.name) {
+                Some(info) => {
+                    // commands without parameters, like '{1:RED}', keep an invalid index during normalization
+                    let index = cmd.index.filter(|_| !info.parameters.is_empty());
+                    if let Some(index) = index.filter(|i| !explained.contains(i)) {
+                        explained.push(index);
+                        let kind = if info.count_like {
+                            "a number"
+                        } else if info.parameters[0].allow_gender {
+                            "a name or text"
                         } else {
-                            errors.push(ValidationError {
-                                severity: Severity::Error,
-                                pos_begin: Some(fragment.pos_begin),
-                                pos_end: Some(fragment.pos_end),
-                                message: format!(
-                                    "There is no parameter in position {}, found '{{{}}}'.",
-                                    pos, cmd.name
-                                ),
-                                suggestion: None,
-                            });
-                        }
-
-                        pos += 1;
+                            "a value"
+                        };
+                        lines.push(format!("Position {}: {} ({}).", index, cmd.name, kind));
                     }
-                } else {
-                    errors.push(ValidationError {
-                        severity: Severity::Error,
-                        pos_begin: Some(fragment.pos_begin),
-                        pos_end: Some(fragment.pos_end),
-                        message: format!("Unknown string command '{{{}}}'.", cmd.name),
-                        suggestion: None,
-                    });
                 }
-                front = 2;
-            }
+                None => lines.push(format!("Unknown string command '{{{}}}'.", cmd.name)),
+            },
             FragmentContent::Gender(g) => {
-                if !config.dialect.allow_genders() || config.genders.len() < 2 {
-                    errors.push(ValidationError {
-                        severity: Severity::Error,
-                        pos_begin: Some(fragment.pos_begin),
-                        pos_end: Some(fragment.pos_end),
-                        message: String::from("No gender definitions allowed."),
-                        suggestion: Some(String::from("Remove '{G=...}'.")),
-                    });
-                } else if front == 2 {
-                    errors.push(ValidationError {
-                        severity: Severity::Warning,
-                        pos_begin: Some(fragment.pos_begin),
-                        pos_end: Some(fragment.pos_end),
-                        message: String::from("Gender definitions must be at the front."),
-                        suggestion: Some(String::from(
-                            "Move '{G=...}' to the front of the translation.",
-                        )),
-                    });
-                } else if front == 1 {
-                    errors.push(ValidationError {
+                lines.push(format!("The gender of this string is '{}'.", g.gender));
+            }
+            FragmentContent::Choice(cmd) => {
+                let kind = match cmd.name.as_str() {
+                    "P" => "plural",
+                    "G" => "gender",
+                    _ => panic!(),
+                };
+                let reference = match (cmd.indexref, cmd.indexsubref) {
+                    (Some(i), Some(s)) => format!("position {}:{}", i, s),
+                    (Some(i), None) => format!("position {}", i),
+                    (None, _) => String::from("no position"),
+                };
+                lines.push(format!(
+                    "'{{{}}}' selects one of {} {} forms by {}.",
+                    cmd.name,
+                    cmd.choices.len(),
+                    kind,
+                    reference
+                ));
+            }
+            FragmentContent::Text(_) | FragmentContent::Comment(_) => (),
+        }
+    }
+    lines
+}
+
+/**
+ * Validate all translations of a language against the base language.
+ *
+ * @param config The language configuration to validate against.
+ * @param base_dict The base strings by string name.
+ * @param trans_dict The translations of the default case by string name.
+ *
+ * @returns The validation result by string name, including missing and unknown translations.
+ */
+pub fn validate_dictionary(
+    config: &LanguageConfig,
+    base_dict: &HashMap<String, String>,
+    trans_dict: &HashMap<String, String>,
+) -> HashMap<String, ValidationResult> {
+    let default_case = String::from("default");
+    let mut results = HashMap::new();
+    for (name, base) in base_dict {
+        let result = match trans_dict.get(name) {
+            Some(translation) => validate_translation(config, base, &default_case, translation),
+            None => ValidationResult {
+                errors: vec![ValidationError {
+                    severity: Severity::Warning,
+                    code: ErrorCode::MissingTranslation,
+                    pos_begin: None,
+                    pos_end: None,
+                    message: format!("Translation of '{}' is missing.", name),
+                    suggestion: None,
+                }],
+                normalized: None,
+            },
+        };
+        results.insert(name.clone(), result);
+    }
+    for name in trans_dict.keys() {
+        if !base_dict.contains_key(name) {
+            results.insert(
+                name.clone(),
+                ValidationResult {
+                    errors: vec![ValidationError {
                         severity: Severity::Warning,
-                        pos_begin: Some(fragment.pos_begin),
-                        pos_end: Some(fragment.pos_end),
-                        message: String::from("Duplicate gender definition."),
-                        suggestion: Some(String::from("Remove the second '{G=...}'.")),
-                    });
+                        code: ErrorCode::UnknownString,
+                        pos_begin: None,
+                        pos_end: None,
+                        message: format!("String '{}' does not exist in the base language.", name),
+                        suggestion: Some(String::from("Remove this translation.")),
+                    }],
+                    normalized: None,
+                },
+            );
+        }
+    }
+    results
+}
+
+/// Replace control characters with spaces. A CRLF line break is replaced with a single space, like LF and CR.
+fn remove_ascii_ctrl(t: &mut String) {
+    *t = t
+        .replace("\r\n", "\n")
+        .replace(|c| char::is_ascii_control(&c), " ");
+}
+
+fn remove_trailing_blanks(t: &mut String) {
+    t.truncate(t.trim_end().len());
+}
+
+/// Replace all ASCII control codes with blank.
+/// Remove trailing blanks at end of each line.
+fn sanitize_whitespace(dialect: &Dialect, parsed: &mut ParsedString) {
+    let newline = newline_info(dialect);
+    let mut is_eol = newline.strip_trailing_blanks;
+    for i in (0..parsed.fragments.len()).rev() {
+        let mut is_nl = false;
+        match &mut parsed.fragments[i].content {
+            FragmentContent::Text(t) => {
+                remove_ascii_ctrl(t);
+                if is_eol {
+                    remove_trailing_blanks(t);
+                }
+            }
+            FragmentContent::Choice(c) => {
+                for t in &mut c.choices {
+                    remove_ascii_ctrl(t);
+                }
+            }
+            FragmentContent::Command(c) => {
+                is_nl = newline.strip_trailing_blanks && c.name == newline.command;
+            }
+            _ => (),
+        }
+        is_eol = is_nl;
+    }
+}
+
+/// Compose the characters of text and choice values to Unicode NFC. Command names are not changed.
+fn compose_unicode(parsed: &mut ParsedString) {
+    parsed.map_text(|t| t.nfc().collect());
+    // composing never creates '"' or '}', so the choices stay valid
+    parsed
+        .map_choices(|t| t.nfc().collect())
+        .expect("NFC keeps choices valid");
+}
+
+/// Replace case and gender names with their spelling in the language configuration.
+fn canonicalize_names(config: &LanguageConfig, parsed: &mut ParsedString) {
+    for fragment in &mut parsed.fragments {
+        let (names, name) = match &mut fragment.content {
+            FragmentContent::Command(StringCommand {
+                case: Some(case), ..
+            }) => (&config.cases, case),
+            FragmentContent::Gender(g) => (&config.genders, &mut g.gender),
+            _ => continue,
+        };
+        if let Some(canonical) = config.find_name(names, name) {
+            *name = canonical.clone();
+        }
+    }
+}
+
+/// Describe the changes of 'normalize_string' from 'before' to 'after'.
+fn normalization_notices(before: &ParsedString, after: &ParsedString) -> Vec<ValidationError> {
+    let mut notices = Vec::new();
+    for old in &before.fragments {
+        let new = after
+            .fragments
+            .iter()
+            .find(|f| f.pos_begin == old.pos_begin);
+        let message = match (&old.content, new.map(|f| &f.content)) {
+            (FragmentContent::Comment(_), None) => String::from("Removed comment."),
+            (FragmentContent::Command(o), Some(FragmentContent::Command(n))) => {
+                if o.name != n.name {
+                    format!("Replaced '{{{}}}' with '{{{}}}'.", o.name, n.name)
+                } else if let (None, Some(index)) = (o.index, n.index) {
+                    format!("Added position index {} to '{{{}}}'.", index, n.name)
                 } else {
-                    front = 1;
-                    if !config.genders.contains(&g.gender) {
+                    continue;
+                }
+            }
+            (FragmentContent::Choice(o), Some(FragmentContent::Choice(n))) => {
+                if let (None, Some(index)) = (o.indexref, n.indexref) {
+                    format!(
+                        "Added position reference {} to '{{{} ...}}'.",
+                        index, n.name
+                    )
+                } else if o.indexsubref.is_some() && n.indexsubref.is_none() {
+                    format!("Removed default sub-index from '{{{} ...}}'.", n.name)
+                } else {
+                    continue;
+                }
+            }
+            _ => continue,
+        };
+        notices.push(ValidationError {
+            severity: Severity::Info,
+            code: ErrorCode::Normalized,
+            pos_begin: Some(old.pos_begin),
+            pos_end: Some(old.pos_end),
+            message,
+            suggestion: None,
+        });
+    }
+    notices
+}
+
+/// Sort the colour and font commands at the front of the string by name.
+/// If there are multiple colours or multiple fonts, the last one wins, so the order is kept.
+fn sort_leading_commands(config: &LanguageConfig, parsed: &mut ParsedString) {
+    let kind = |fragment: &StringFragment| match &fragment.content {
+        FragmentContent::Command(cmd) if cmd.index.is_none() && cmd.case.is_none() => config
+            .find_command(&cmd.name)
+            .filter(|info| info.parameters.is_empty())
+            .and_then(|info| {
+                if info.sets_colour {
+                    Some("colour")
+                } else if info.sets_font {
+                    Some("font")
+                } else {
+                    None
+                }
+            }),
+        _ => None,
+    };
+    let kinds: Vec<&str> = parsed.fragments.iter().map_while(kind).collect();
+    let unique = kinds
+        .iter()
+        .all(|k| kinds.iter().filter(|o| *o == k).count() == 1);
+    if kinds.len() > 1 && unique {
+        parsed.fragments[..kinds.len()].sort_by_key(|f| f.content.compile());
+    }
+}
+
+struct StringSignature<'a> {
+    parameters: HashMap<usize, (CommandInfo<'a>, usize)>,
+    nonpositional_count: BTreeMap<String, (Occurence, usize)>,
+    // TODO track the colour and line number of positional parameters. Balanced colour stacks are checked by validate_string_signature.
+}
+
+fn get_signature<'a>(
+    config: &'a LanguageConfig,
+    base: &ParsedString,
+) -> Result<StringSignature<'a>, Vec<ValidationError>> {
+    let mut errors = Vec::new();
+    let mut signature = StringSignature {
+        parameters: HashMap::new(),
+        nonpositional_count: BTreeMap::new(),
+    };
+
+    let mut pos = 0;
+    for fragment in &base.fragments {
+        if let FragmentContent::Command(cmd) = &fragment.content {
+            if let Some(info) = config.find_command(&cmd.name) {
+                if info.parameters.is_empty() {
+                    if let Some(index) = cmd.index {
                         errors.push(ValidationError {
                             severity: Severity::Error,
+                            code: ErrorCode::UnexpectedPositionReference,
                             pos_begin: Some(fragment.pos_begin),
                             pos_end: Some(fragment.pos_end),
-                            message: format!("Unknown gender '{}'.", g.gender),
+                            message: format!(
+                                "Command '{{{}}}' cannot have a position reference.",
+                                cmd.name
+                            ),
+                            suggestion: Some(format!("Remove '{}:'.", index)),
+                        });
+                    }
+                    let norm_name = String::from(info.get_norm_name());
+                    if let Some(existing) = signature.nonpositional_count.get_mut(&norm_name) {
+                        existing.1 += 1;
+                    } else {
+                        signature
+                            .nonpositional_count
+                            .insert(norm_name, (config.get_occurence(&info), 1));
+                    }
+                } else {
+                    if let Some(index) = cmd.index {
+                        pos = index;
+                    }
+                    let max_parameters = config.dialect.max_parameters();
+                    if pos >= max_parameters {
+                        errors.push(ValidationError {
+                            severity: Severity::Error,
+                            code: ErrorCode::ParameterLimit,
+                            pos_begin: Some(fragment.pos_begin),
+                            pos_end: Some(fragment.pos_end),
+                            message: format!(
+                                "Position {} is out of range, {} strings have at most {} parameters.",
+                                pos,
+                                config.dialect.as_str(),
+                                max_parameters
+                            ),
                             suggestion: Some(format!(
-                                "Known genders are: '{}'",
-                                config.genders.join("', '")
+                                "Use positions 0 to {}.",
+                                max_parameters - 1
                             )),
                         });
                     }
+                    if let Some(existing) = signature.parameters.get_mut(&pos) {
+                        existing.1 += 1;
+                    } else {
+                        signature.parameters.insert(pos, (info, 1));
+                    }
+                    pos += 1;
+                }
+            } else {
+                errors.push(unknown_command_error(config, &cmd.name, fragment));
+                if config.unknown_command_severity != Severity::Error {
+                    // treat as opaque command without parameters
+                    signature
+                        .nonpositional_count
+                        .entry(cmd.name.clone())
+                        .or_insert((Occurence::EXACT, 0))
+                        .1 += 1;
+                }
+            }
+        }
+    }
+
+    if errors.iter().all(|e| e.severity != Severity::Error) {
+        Ok(signature)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Count the choices of each '{P}' and '{G}' by choice type and referenced position.
+fn get_choice_counts(
+    config: &LanguageConfig,
+    parsed: &ParsedString,
+) -> HashMap<(String, usize), usize> {
+    let mut counts = HashMap::new();
+    let mut pos = 0;
+    for fragment in &parsed.fragments {
+        match &fragment.content {
+            FragmentContent::Command(cmd) => {
+                if let Some(info) = config.find_command(&cmd.name) {
+                    if !info.parameters.is_empty() {
+                        pos = cmd.index.unwrap_or(pos) + 1;
+                    }
                 }
             }
             FragmentContent::Choice(cmd) => {
                 let opt_ref_pos = match cmd.name.as_str() {
-                    "P" => {
-                        if pos == 0 {
-                            None
-                        } else {
-                            Some(pos - 1)
-                        }
-                    }
+                    "P" => pos.checked_sub(1),
                     "G" => Some(pos),
                     _ => panic!(),
                 };
-                let opt_ref_pos = cmd.indexref.or(opt_ref_pos);
-                if cmd.name == "G" && (!config.dialect.allow_genders() || config.genders.len() < 2)
-                {
-                    errors.push(ValidationError {
-                        severity: Severity::Error,
-                        pos_begin: Some(fragment.pos_begin),
-                        pos_end: Some(fragment.pos_end),
-                        message: String::from("No gender choices allowed."),
-                        suggestion: Some(String::from("Remove '{G ...}'.")),
-                    });
-                } else if cmd.name == "P" && config.plural_count < 2 {
-                    errors.push(ValidationError {
-                        severity: Severity::Error,
-                        pos_begin: Some(fragment.pos_begin),
-                        pos_end: Some(fragment.pos_end),
-                        message: String::from("No plural choices allowed."),
-                        suggestion: Some(String::from("Remove '{P ...}'.")),
-                    });
-                } else {
-                    match cmd.name.as_str() {
-                        "P" => {
-                            if cmd.choices.len() != config.plural_count {
-                                errors.push(ValidationError {
-                                    severity: Severity::Error,
-                                    pos_begin: Some(fragment.pos_begin),
-                                    pos_end: Some(fragment.pos_end),
-                                    message: format!(
-                                        "Expected {} plural choices, found {}.",
-                                        config.plural_count,
-                                        cmd.choices.len()
-                                    ),
-                                    suggestion: None,
-                                });
-                            }
-                        }
-                        "G" => {
-                            if cmd.choices.len() != config.genders.len() {
-                                errors.push(ValidationError {
-                                    severity: Severity::Error,
-                                    pos_begin: Some(fragment.pos_begin),
-                                    pos_end: Some(fragment.pos_end),
-                                    message: format!(
-                                        "Expected {} gender choices, found {}.",
-                                        config.genders.len(),
-                                        cmd.choices.len()
-                                    ),
-                                    suggestion: None,
-                                });
-                            }
-                        }
-                        _ => panic!(),
-                    };
+                if let Some(ref_pos) = cmd.indexref.or(opt_ref_pos) {
+                    counts.insert((cmd.name.clone(), ref_pos), cmd.choices.len());
+                }
+            }
+            _ => (),
+        }
+    }
+    counts
+}
 
-                    if let Some(ref_info) = opt_ref_pos
-                        .and_then(|ref_pos| signature.parameters.get(&ref_pos).map(|v| v.0))
-                    {
-                        let ref_pos = opt_ref_pos.unwrap();
-                        let ref_norm_name = ref_info.get_norm_name();
-                        let ref_subpos = match cmd.name.as_str() {
-                            "P" => cmd
-                                .indexsubref
-                                .or(ref_info.def_plural_subindex)
-                                .unwrap_or(0),
-                            "G" => cmd.indexsubref.unwrap_or(0),
-                            _ => panic!(),
-                        };
-                        if let Some(par_info) = ref_info.parameters.get(ref_subpos) {
-                            match cmd.name.as_str() {
-                                "P" => {
-                                    if !par_info.allow_plural {
-                                        errors.push(ValidationError{
-                                            severity: Severity::Error,
-                                            pos_begin: Some(fragment.pos_begin),
-                                            pos_end: Some(fragment.pos_end),
-                                            message: format!(
-                                                "'{{{}}}' references position '{}:{}', but '{{{}:{}}}' does not allow plurals.",
-                                                cmd.name, ref_pos, ref_subpos, ref_pos, ref_norm_name
-                                            ),
-                                            suggestion: None,
-                                        });
-                                    }
-                                }
-                                "G" => {
-                                    if !par_info.allow_gender {
-                                        errors.push(ValidationError{
-                                            severity: Severity::Error,
-                                            pos_begin: Some(fragment.pos_begin),
-                                            pos_end: Some(fragment.pos_end),
-                                            message: format!(
-                                                "'{{{}}}' references position '{}:{}', but '{{{}:{}}}' does not allow genders.",
-                                                cmd.name, ref_pos, ref_subpos, ref_pos, ref_norm_name
-                                            ),
-                                            suggestion: None,
-                                        });
-                                    }
-                                }
-                                _ => panic!(),
-                            };
-                        } else {
-                            errors.push(ValidationError{
-                                severity: Severity::Error,
-                                pos_begin: Some(fragment.pos_begin),
-                                pos_end: Some(fragment.pos_end),
-                                message: format!(
-                                    "'{{{}}}' references position '{}:{}', but '{{{}:{}}}' only has {} subindices.",
-                                    cmd.name, ref_pos, ref_subpos, ref_pos, ref_norm_name, ref_info.parameters.len()
-                                ),
-                                suggestion: None,
-                            });
-                        }
-                    } else {
+/// Check whether a string contains '{G ...}' choices or parameters, which can have a gender.
+fn has_gender_output(config: &LanguageConfig, parsed: &ParsedString) -> bool {
+    parsed
+        .fragments
+        .iter()
+        .any(|fragment| match &fragment.content {
+            FragmentContent::Choice(cmd) => cmd.name == "G",
+            FragmentContent::Command(cmd) => config
+                .find_command(&cmd.name)
+                .is_some_and(|info| info.parameters.iter().any(|p| p.allow_gender)),
+            _ => false,
+        })
+}
+
+/// Get the positions of all parameters in order of their first appearance.
+fn get_parameter_order(config: &LanguageConfig, parsed: &ParsedString) -> Vec<usize> {
+    let mut order = Vec::new();
+    let mut pos = 0;
+    for fragment in &parsed.fragments {
+        if let FragmentContent::Command(cmd) = &fragment.content {
+            if let Some(info) = config.find_command(&cmd.name) {
+                if !info.parameters.is_empty() {
+                    pos = cmd.index.unwrap_or(pos);
+                    if !order.contains(&pos) {
+                        order.push(pos);
+                    }
+                    pos += 1;
+                }
+            }
+        }
+    }
+    order
+}
+
+/// Find a string command by name, preferring built-in commands of the dialect over custom commands.
+fn find_command<'a>(
+    dialect: &Dialect,
+    custom_commands: &'a [CustomCommand],
+    name: &str,
+) -> Option<CommandInfo<'a>> {
+    COMMANDS
+        .iter()
+        .find(|ci| ci.name == name && ci.dialects.contains(dialect))
+        .copied()
+        .or_else(|| {
+            custom_commands
+                .iter()
+                .find(|cc| cc.name == name)
+                .map(CustomCommand::info)
+        })
+}
+
+/// Number of single-character edits to turn 'a' into 'b'.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diag = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let subst = diag + usize::from(ca != *cb);
+            diag = row[j + 1];
+            row[j + 1] = subst.min(diag + 1).min(row[j] + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/**
+ * Find the string command, which was most likely meant by an unknown command name.
+ *
+ * @param config Language configuration, which determines the dialect and custom commands.
+ * @param name Name of the unknown command, like 'REED'.
+ *
+ * @returns The closest known command name, like 'RED'. None, if no command is close enough.
+ */
+pub fn suggest_command(config: &LanguageConfig, name: &str) -> Option<String> {
+    // allow one typo in short names, two in longer ones
+    let threshold = if name.chars().count() <= 4 { 1 } else { 2 };
+    COMMANDS
+        .iter()
+        .filter(|ci| ci.dialects.contains(&config.dialect))
+        .map(|ci| ci.name)
+        .chain(config.custom_commands.iter().map(|cc| cc.name.as_str()))
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= threshold)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| String::from(candidate))
+}
+
+/// Parse a string for validation, with string commands inside choices, if 'parse_choice_contents' is set.
+fn parse_string(config: &LanguageConfig, string: &str) -> Result<ParsedString, ParserError> {
+    if config.parse_choice_contents {
+        ParsedString::parse_with_choice_contents(string)
+    } else {
+        ParsedString::parse(string)
+    }
+}
+
+/// Validate the string commands inside the choices of a choice list against the signature, for 'parse_choice_contents'.
+/// 'pos_begin' is the codepoint offset of the choice list.
+/// Parameters inside choices need a position reference, since an implicit position would depend on the chosen choice.
+/// They do not count towards the occurrence of the parameter.
+fn validate_choice_contents(
+    config: &LanguageConfig,
+    choice: &ChoiceList,
+    pos_begin: usize,
+    signature: &StringSignature,
+) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+    for (value, (begin, end)) in choice.choices.iter().zip(&choice.choice_spans) {
+        // skip the opening quote of quoted choices
+        let offset = pos_begin + begin + usize::from(end - begin > value.chars().count());
+        let mut nested = match ParsedString::parse_with_choice_contents(value) {
+            Ok(nested) => nested,
+            Err(err) => {
+                errors.push(ValidationError {
+                    severity: Severity::Error,
+                    code: ErrorCode::ParseError,
+                    pos_begin: Some(offset + err.pos_begin),
+                    pos_end: err.pos_end.map(|p| offset + p),
+                    message: err.message,
+                    suggestion: None,
+                });
+                continue;
+            }
+        };
+        for fragment in &mut nested.fragments {
+            fragment.pos_begin += offset;
+            fragment.pos_end += offset;
+        }
+        for fragment in &nested.fragments {
+            match &fragment.content {
+                FragmentContent::Command(cmd) => {
+                    let Some(info) = config.find_command(&cmd.name) else {
+                        errors.push(unknown_command_error(config, &cmd.name, fragment));
+                        continue;
+                    };
+                    if info.parameters.is_empty() {
+                        continue;
+                    }
+                    let norm_name = info.get_norm_name();
+                    let Some(index) = cmd.index else {
+                        let expected_pos = signature
+                            .parameters
+                            .iter()
+                            .filter(|(_, (expected, _))| expected.get_norm_name() == norm_name)
+                            .map(|(pos, _)| *pos)
+                            .min();
                         errors.push(ValidationError {
                             severity: Severity::Error,
+                            code: ErrorCode::MissingPositionReference,
                             pos_begin: Some(fragment.pos_begin),
                             pos_end: Some(fragment.pos_end),
                             message: format!(
-                                "'{{{}}}' references position '{}', which has no parameter.",
-                                cmd.name,
-                                opt_ref_pos
-                                    .and_then(|v| isize::try_from(v).ok())
-                                    .unwrap_or(-1)
+                                "Command '{{{}}}' inside a choice needs a position reference.",
+                                cmd.name
                             ),
-                            suggestion: if cmd.indexref.is_none() {
-                                Some(String::from("Add a position reference."))
-                            } else {
-                                None
-                            },
+                            suggestion: expected_pos
+                                .map(|pos| format!("Use '{{{}:{}}}'.", pos, norm_name)),
                         });
+                        continue;
+                    };
+                    match signature.parameters.get(&index) {
+                        Some((expected, _)) if expected.get_norm_name() == norm_name => (),
+                        Some((expected, _)) => errors.push(ValidationError {
+                            severity: Severity::Error,
+                            code: ErrorCode::ParameterMismatch,
+                            pos_begin: Some(fragment.pos_begin),
+                            pos_end: Some(fragment.pos_end),
+                            message: format!(
+                                "Expected '{{{}:{}}}', found '{{{}}}'.",
+                                index, expected.name, cmd.name
+                            ),
+                            suggestion: None,
+                        }),
+                        None => errors.push(ValidationError {
+                            severity: Severity::Error,
+                            code: ErrorCode::ParameterOutOfRange,
+                            pos_begin: Some(fragment.pos_begin),
+                            pos_end: Some(fragment.pos_end),
+                            message: format!(
+                                "There is no parameter in position {}, found '{{{}}}'.",
+                                index, cmd.name
+                            ),
+                            suggestion: None,
+                        }),
                     }
                 }
-                front = 2;
-            }
-            FragmentContent::Text(_) => {
-                front = 2;
+                FragmentContent::Choice(nested_choice) => errors.extend(validate_choice_contents(
+                    config,
+                    nested_choice,
+                    fragment.pos_begin,
+                    signature,
+                )),
+                _ => (),
             }
         }
     }
+    errors
+}
 
-    for (pos, (info, ex_count)) in &signature.parameters {
-        let norm_name = info.get_norm_name();
-        let found_count = positional_count.get(pos).cloned().unwrap_or(0);
-        if info.occurence != Occurence::ANY && found_count == 0 {
-            errors.push(ValidationError {
-                severity: Severity::Error,
-                pos_begin: None,
-                pos_end: None,
-                message: format!("String command '{{{}:{}}}' is missing.", pos, norm_name),
-                suggestion: None,
-            });
-        } else if info.occurence == Occurence::EXACT && *ex_count != found_count {
-            errors.push(ValidationError {
-                severity: Severity::Warning,
-                pos_begin: None,
-                pos_end: None,
-                message: format!(
-                    "String command '{{{}:{}}}': expected {} times, found {} times.",
-                    pos, norm_name, ex_count, found_count
-                ),
-                suggestion: None,
-            });
+/// Error for an unknown string command, with a suggestion if there is a similar known command.
+fn unknown_command_error(
+    config: &LanguageConfig,
+    name: &str,
+    fragment: &StringFragment,
+) -> ValidationError {
+    ValidationError {
+        severity: config.unknown_command_severity,
+        code: ErrorCode::UnknownCommand,
+        pos_begin: Some(fragment.pos_begin),
+        pos_end: Some(fragment.pos_end),
+        message: format!("Unknown string command '{{{}}}'.", name),
+        suggestion: suggest_command(config, name).map(|s| format!("Did you mean '{{{}}}'?", s)),
+    }
+}
+
+/// Check that 'custom_commands' do not replace built-in commands.
+fn validate_custom_commands(config: &LanguageConfig) -> Vec<ValidationError> {
+    config
+        .custom_commands
+        .iter()
+        .filter(|cc| {
+            COMMANDS
+                .iter()
+                .any(|ci| ci.name == cc.name && ci.dialects.contains(&config.dialect))
+        })
+        .map(|cc| ValidationError {
+            severity: Severity::Error,
+            code: ErrorCode::InvalidConfig,
+            pos_begin: None,
+            pos_end: None,
+            message: format!(
+                "Custom string command '{{{}}}' conflicts with a built-in command.",
+                cc.name
+            ),
+            suggestion: Some(String::from("Fix the language configuration.")),
+        })
+        .collect()
+}
+
+/// Check that 'occurrence_overrides' only contains known commands.
+fn validate_occurrence_overrides(config: &LanguageConfig) -> Vec<ValidationError> {
+    config
+        .occurrence_overrides
+        .keys()
+        .filter(|name| {
+            !COMMANDS.iter().any(|ci| ci.name == name.as_str())
+                && !config.custom_commands.iter().any(|cc| &cc.name == *name)
+        })
+        .map(|name| ValidationError {
+            severity: Severity::Error,
+            code: ErrorCode::UnknownOverride,
+            pos_begin: None,
+            pos_end: None,
+            message: format!(
+                "Unknown string command '{{{}}}' in occurrence overrides.",
+                name
+            ),
+            suggestion: Some(String::from("Fix the language configuration.")),
+        })
+        .collect()
+}
+
+fn validate_string(
+    config: &LanguageConfig,
+    test: &ParsedString,
+    base: Option<&ParsedString>,
+) -> Vec<ValidationError> {
+    validate_string_annotated(config, test, base, &mut Vec::new())
+}
+
+fn validate_string_annotated<'a>(
+    config: &'a LanguageConfig,
+    test: &ParsedString,
+    base: Option<&ParsedString>,
+    annotations: &mut Vec<CommandAnnotation<'a>>,
+) -> Vec<ValidationError> {
+    let mut config_errors = validate_occurrence_overrides(config);
+    config_errors.extend(validate_custom_commands(config));
+    if !config_errors.is_empty() {
+        return config_errors;
+    }
+
+    let signature: StringSignature;
+    match get_signature(config, base.unwrap_or(test)) {
+        Ok(sig) => signature = sig,
+        Err(msgs) => {
+            if base.is_some() {
+                return vec![invalid_base_error()];
+            } else {
+                return msgs;
+            }
         }
     }
 
-    for (norm_name, (occurence, ex_count)) in &signature.nonpositional_count {
-        let found_count = nonpositional_count.get(norm_name).map(|v| v.1).unwrap_or(0);
-        if *occurence != Occurence::ANY && found_count == 0 {
-            errors.push(ValidationError {
-                severity: Severity::Warning,
-                pos_begin: None,
-                pos_end: None,
-                message: format!("String command '{{{}}}' is missing.", norm_name),
-                suggestion: None,
-            });
-        } else if *occurence == Occurence::EXACT && *ex_count != found_count {
-            errors.push(ValidationError {
+    validate_string_signature(
+        config,
+        test,
+        base,
+        &signature,
+        config.plural_count,
+        annotations,
+    )
+}
+
+fn invalid_base_error() -> ValidationError {
+    ValidationError {
+        severity: Severity::Error,
+        code: ErrorCode::InvalidBase,
+        pos_begin: None,
+        pos_end: None,
+        message: String::from("Base language text is invalid."),
+        suggestion: Some(String::from("This is a bug; wait until it is fixed.")),
+    }
+}
+
+/// Validate a string against the signature of 'base', or against its own signature, if 'base' is None.
+/// 'plural_count' is the number of plural forms of the case, which 'test' is a translation for.
+fn validate_string_signature<'a>(
+    config: &'a LanguageConfig,
+    test: &ParsedString,
+    base: Option<&ParsedString>,
+    signature: &StringSignature<'a>,
+    plural_count: usize,
+    annotations: &mut Vec<CommandAnnotation<'a>>,
+) -> Vec<ValidationError> {
+    let base_choice_counts = match base {
+        Some(base) if config.compare_choice_counts => get_choice_counts(config, base),
+        _ => HashMap::new(),
+    };
+
+    let mut base_order = match base {
+        Some(base) if config.forbid_reorder => get_parameter_order(config, base),
+        _ => Vec::new(),
+    };
+    let mut max_rank = None;
+
+    let mut errors = Vec::new();
+    let mut choice_refs = Vec::new();
+    let mut positional_count: HashMap<usize, usize> = HashMap::new();
+    let mut nonpositional_count: BTreeMap<String, (Occurence, usize, (usize, usize))> =
+        BTreeMap::new();
+    let mut colour_stack = Vec::new();
+    let mut pos = 0;
+    let mut front = 0;
+    for fragment in &test.fragments {
+        match &fragment.content {
+            FragmentContent::Command(cmd) => {
+                let opt_expected = signature
+                    .parameters
+                    .get(&cmd.index.unwrap_or(pos))
+                    .map(|v| (*v).0);
+                let opt_info = opt_expected
+                    .filter(|ex| ex.get_norm_name() == cmd.name)
+                    .or_else(|| config.find_command(&cmd.name));
+                annotations.push(CommandAnnotation {
+                    pos_begin: fragment.pos_begin,
+                    pos_end: fragment.pos_end,
+                    info: opt_info,
+                });
+                match cmd.name.as_str() {
+                    "PUSH_COLOUR" => colour_stack.push(fragment),
+                    "POP_COLOUR" if colour_stack.pop().is_none() => {
+                        errors.push(ValidationError {
+                            severity: Severity::Error,
+                            code: ErrorCode::UnbalancedColourStack,
+                            pos_begin: Some(fragment.pos_begin),
+                            pos_end: Some(fragment.pos_end),
+                            message: String::from(
+                                "'{POP_COLOUR}' without preceding '{PUSH_COLOUR}'.",
+                            ),
+                            suggestion: Some(String::from("Remove '{POP_COLOUR}'.")),
+                        });
+                    }
+                    _ => (),
+                }
+                if let Some(info) = opt_info {
+                    if let Some(c) = &cmd.case {
+                        if !config.dialect.allow_cases() {
+                            errors.push(ValidationError {
+                                severity: Severity::Error,
+                                code: ErrorCode::CasesNotSupported,
+                                pos_begin: Some(fragment.pos_begin),
+                                pos_end: Some(fragment.pos_end),
+                                message: String::from("No case selections allowed."),
+                                suggestion: Some(format!("Remove '.{}'.", c)),
+                            });
+                        } else if !info.allow_case {
+                            errors.push(ValidationError {
+                                severity: Severity::Error,
+                                code: ErrorCode::CaseNotAllowed,
+                                pos_begin: Some(fragment.pos_begin),
+                                pos_end: Some(fragment.pos_end),
+                                message: format!(
+                                    "No case selection allowed for '{{{}}}'.",
+                                    cmd.name
+                                ),
+                                suggestion: Some(format!("Remove '.{}'.", c)),
+                            });
+                        } else if config.find_name(&config.cases, c).is_none() {
+                            errors.push(ValidationError {
+                                severity: Severity::Error,
+                                code: ErrorCode::UnknownCase,
+                                pos_begin: Some(fragment.pos_begin),
+                                pos_end: Some(fragment.pos_end),
+                                message: format!("Unknown case '{}'.", c),
+                                suggestion: Some(format!(
+                                    "Known cases are: '{}'",
+                                    config.cases.join("', '")
+                                )),
+                            });
+                        }
+                    }
+
+                    if info.parameters.is_empty() {
+                        if let Some(index) = cmd.index {
+                            errors.push(ValidationError {
+                                severity: Severity::Error,
+                                code: ErrorCode::UnexpectedPositionReference,
+                                pos_begin: Some(fragment.pos_begin),
+                                pos_end: Some(fragment.pos_end),
+                                message: format!(
+                                    "Command '{{{}}}' cannot have a position reference.",
+                                    cmd.name
+                                ),
+                                suggestion: Some(format!("Remove '{}:'.", index)),
+                            });
+                        }
+
+                        let norm_name = String::from(info.get_norm_name());
+                        if let Some(existing) = nonpositional_count.get_mut(&norm_name) {
+                            existing.1 += 1;
+                        } else {
+                            nonpositional_count.insert(
+                                norm_name,
+                                (
+                                    config.get_occurence(&info),
+                                    1,
+                                    (fragment.pos_begin, fragment.pos_end),
+                                ),
+                            );
+                        }
+                    } else {
+                        if let Some(index) = cmd.index {
+                            pos = index;
+                        } else if config.require_explicit_indices && base.is_some() {
+                            errors.push(ValidationError {
+                                severity: Severity::Warning,
+                                code: ErrorCode::MissingPositionReference,
+                                pos_begin: Some(fragment.pos_begin),
+                                pos_end: Some(fragment.pos_end),
+                                message: format!(
+                                    "Command '{{{}}}' has no position reference.",
+                                    cmd.name
+                                ),
+                                suggestion: Some(format!(
+                                    "Use '{{{}:{}{}}}'.",
+                                    pos,
+                                    info.get_norm_name(),
+                                    cmd.case
+                                        .as_ref()
+                                        .map_or(String::new(), |c| format!(".{}", c))
+                                )),
+                            });
+                        }
+
+                        if let Some(expected) = opt_expected {
+                            if expected.get_norm_name() == info.get_norm_name() {
+                                let rank = base_order.iter().position(|p| *p == pos);
+                                if rank.is_some() && !positional_count.contains_key(&pos) {
+                                    if rank < max_rank {
+                                        errors.push(ValidationError {
+                                            severity: Severity::Error,
+                                            code: ErrorCode::ParameterReordered,
+                                            pos_begin: Some(fragment.pos_begin),
+                                            pos_end: Some(fragment.pos_end),
+                                            message: format!(
+                                                "Parameter '{{{}:{}}}' is out of order, reordering parameters is not allowed.",
+                                                pos,
+                                                info.get_norm_name()
+                                            ),
+                                            suggestion: Some(String::from(
+                                                "Use the parameters in the same order as the base.",
+                                            )),
+                                        });
+                                        // Only report the first parameter out of order.
+                                        base_order.clear();
+                                    }
+                                    max_rank = max_rank.max(rank);
+                                }
+                                if let Some(existing) = positional_count.get_mut(&pos) {
+                                    *existing += 1;
+                                } else {
+                                    positional_count.insert(pos, 1);
+                                }
+                            } else {
+                                errors.push(ValidationError {
+                                    severity: Severity::Error,
+                                    code: ErrorCode::ParameterMismatch,
+                                    pos_begin: Some(fragment.pos_begin),
+                                    pos_end: Some(fragment.pos_end),
+                                    message: format!(
+                                        "Expected '{{{}:{}}}', found '{{{}}}'.",
+                                        pos, expected.name, cmd.name
+                                    ),
+                                    suggestion: None,
+                                })
+                            }
+                        } else {
+                            errors.push(ValidationError {
+                                severity: Severity::Error,
+                                code: ErrorCode::ParameterOutOfRange,
+                                pos_begin: Some(fragment.pos_begin),
+                                pos_end: Some(fragment.pos_end),
+                                message: format!(
+                                    "There is no parameter in position {}, found '{{{}}}'.",
+                                    pos, cmd.name
+                                ),
+                                suggestion: None,
+                            });
+                        }
+
+                        pos += 1;
+                    }
+                } else {
+                    errors.push(unknown_command_error(config, &cmd.name, fragment));
+                    if config.unknown_command_severity != Severity::Error {
+                        // treat as opaque command without parameters
+                        nonpositional_count
+                            .entry(cmd.name.clone())
+                            .or_insert((
+                                Occurence::EXACT,
+                                0,
+                                (fragment.pos_begin, fragment.pos_end),
+                            ))
+                            .1 += 1;
+                    }
+                }
+                front = 2;
+            }
+            FragmentContent::Gender(g) => {
+                if let Some(index) = g.index {
+                    errors.push(ValidationError {
+                        severity: Severity::Error,
+                        code: ErrorCode::UnexpectedPositionReference,
+                        pos_begin: Some(fragment.pos_begin),
+                        pos_end: Some(fragment.pos_end),
+                        message: String::from(
+                            "Gender definitions cannot have a position reference.",
+                        ),
+                        suggestion: Some(format!("Remove '{}:'.", index)),
+                    });
+                }
+                if base.is_none() {
+                    errors.push(ValidationError {
+                        severity: Severity::Error,
+                        code: ErrorCode::TranslationOnly,
+                        pos_begin: Some(fragment.pos_begin),
+                        pos_end: Some(fragment.pos_end),
+                        message: String::from(
+                            "Gender definitions belong in translations, the base language has no genders.",
+                        ),
+                        suggestion: Some(String::from("Remove '{G=...}'.")),
+                    });
+                } else if !config.dialect.allow_genders() || config.genders.len() < 2 {
+                    errors.push(ValidationError {
+                        severity: Severity::Error,
+                        code: ErrorCode::GendersNotSupported,
+                        pos_begin: Some(fragment.pos_begin),
+                        pos_end: Some(fragment.pos_end),
+                        message: String::from("No gender definitions allowed."),
+                        suggestion: Some(String::from("Remove '{G=...}'.")),
+                    });
+                } else if front == 2 {
+                    errors.push(ValidationError {
+                        severity: config.gender_front_severity.unwrap_or(Severity::Warning),
+                        code: ErrorCode::GenderNotAtFront,
+                        pos_begin: Some(fragment.pos_begin),
+                        pos_end: Some(fragment.pos_end),
+                        message: String::from("Gender definitions must be at the front."),
+                        suggestion: Some(String::from(
+                            "Move '{G=...}' to the front of the translation.",
+                        )),
+                    });
+                } else if front == 1 {
+                    errors.push(ValidationError {
+                        severity: config.gender_front_severity.unwrap_or(Severity::Warning),
+                        code: ErrorCode::DuplicateGenderDefinition,
+                        pos_begin: Some(fragment.pos_begin),
+                        pos_end: Some(fragment.pos_end),
+                        message: String::from("Duplicate gender definition."),
+                        suggestion: Some(String::from("Remove the second '{G=...}'.")),
+                    });
+                } else {
+                    front = 1;
+                    if config.hint_unused_gender
+                        && base.is_some_and(|base| !has_gender_output(config, base))
+                    {
+                        errors.push(ValidationError {
+                            severity: Severity::Info,
+                            code: ErrorCode::UnusedGenderDefinition,
+                            pos_begin: Some(fragment.pos_begin),
+                            pos_end: Some(fragment.pos_end),
+                            message: String::from(
+                                "The base string has no gender-dependent output, the gender definition may be unnecessary.",
+                            ),
+                            suggestion: Some(String::from("Remove '{G=...}'.")),
+                        });
+                    }
+                    if config.find_name(&config.genders, &g.gender).is_none() {
+                        errors.push(ValidationError {
+                            severity: Severity::Error,
+                            code: ErrorCode::UnknownGender,
+                            pos_begin: Some(fragment.pos_begin),
+                            pos_end: Some(fragment.pos_end),
+                            message: format!("Unknown gender '{}'.", g.gender),
+                            suggestion: Some(format!(
+                                "Known genders are: '{}'",
+                                config.genders.join("', '")
+                            )),
+                        });
+                    }
+                }
+            }
+            FragmentContent::Choice(cmd) => {
+                let opt_ref_pos = match cmd.name.as_str() {
+                    "P" => {
+                        if pos == 0 {
+                            None
+                        } else {
+                            Some(pos - 1)
+                        }
+                    }
+                    "G" => Some(pos),
+                    _ => panic!(),
+                };
+                let opt_ref_pos = cmd.indexref.or(opt_ref_pos);
+                if cmd.name == "G" && (!config.dialect.allow_genders() || config.genders.len() < 2)
+                {
+                    errors.push(ValidationError {
+                        severity: Severity::Error,
+                        code: ErrorCode::GendersNotSupported,
+                        pos_begin: Some(fragment.pos_begin),
+                        pos_end: Some(fragment.pos_end),
+                        message: String::from("No gender choices allowed."),
+                        suggestion: Some(String::from("Remove '{G ...}'.")),
+                    });
+                } else if cmd.name == "P" && plural_count == 1 && base.is_some() {
+                    errors.push(ValidationError {
+                        severity: Severity::Error,
+                        code: ErrorCode::PluralsNotSupported,
+                        pos_begin: Some(fragment.pos_begin),
+                        pos_end: Some(fragment.pos_end),
+                        message: String::from(
+                            "No plural choices allowed, this language has only a single plural form.",
+                        ),
+                        suggestion: Some(String::from(
+                            "Replace '{P ...}' with the text of the single plural form.",
+                        )),
+                    });
+                } else if cmd.name == "P" && plural_count < 2 {
+                    errors.push(ValidationError {
+                        severity: Severity::Error,
+                        code: ErrorCode::PluralsNotSupported,
+                        pos_begin: Some(fragment.pos_begin),
+                        pos_end: Some(fragment.pos_end),
+                        message: String::from("No plural choices allowed."),
+                        suggestion: Some(String::from("Remove '{P ...}'.")),
+                    });
+                } else {
+                    // Point at the first extra choice, if there are too many.
+                    let count_span = |expected: usize| match cmd.choice_spans.get(expected) {
+                        Some((begin, end)) => {
+                            (fragment.pos_begin + begin, fragment.pos_begin + end)
+                        }
+                        None => (fragment.pos_begin, fragment.pos_end),
+                    };
+                    match cmd.name.as_str() {
+                        "P" => {
+                            // The "other" form is always the last choice, after the regular plural forms.
+                            let max_count = plural_count + usize::from(config.plural_has_other);
+                            if cmd.choices.len() != plural_count && cmd.choices.len() != max_count {
+                                let (pos_begin, pos_end) = count_span(max_count);
+                                let expected = if max_count != plural_count {
+                                    format!("{} or {}", plural_count, max_count)
+                                } else {
+                                    plural_count.to_string()
+                                };
+                                errors.push(ValidationError {
+                                    severity: Severity::Error,
+                                    code: ErrorCode::PluralCountMismatch,
+                                    pos_begin: Some(pos_begin),
+                                    pos_end: Some(pos_end),
+                                    message: format!(
+                                        "Expected {} plural choices, found {}.",
+                                        expected,
+                                        cmd.choices.len()
+                                    ),
+                                    suggestion: None,
+                                });
+                            }
+                        }
+                        "G" => {
+                            if cmd.choices.len() != config.genders.len() {
+                                let (pos_begin, pos_end) = count_span(config.genders.len());
+                                errors.push(ValidationError {
+                                    severity: Severity::Error,
+                                    code: ErrorCode::GenderCountMismatch,
+                                    pos_begin: Some(pos_begin),
+                                    pos_end: Some(pos_end),
+                                    message: format!(
+                                        "Expected {} gender choices, found {}.",
+                                        config.genders.len(),
+                                        cmd.choices.len()
+                                    ),
+                                    suggestion: Some(format!(
+                                        "Use one choice per gender in this order: '{}'.",
+                                        config.genders.join("', '")
+                                    )),
+                                });
+                            }
+                        }
+                        _ => panic!(),
+                    };
+
+                    let has_plural_parameter = signature
+                        .parameters
+                        .values()
+                        .any(|(info, _)| info.parameters.iter().any(|par| par.allow_plural));
+                    if cmd.name == "P" && !has_plural_parameter {
+                        errors.push(ValidationError {
+                            severity: Severity::Error,
+                            code: ErrorCode::NoPluralParameter,
+                            pos_begin: Some(fragment.pos_begin),
+                            pos_end: Some(fragment.pos_end),
+                            message: String::from(
+                                "Cannot use a plural choice: the base string has no number parameter.",
+                            ),
+                            suggestion: Some(String::from(
+                                "Replace '{P ...}' with the text of a single plural form.",
+                            )),
+                        });
+                    } else if let Some(ref_info) = opt_ref_pos
+                        .and_then(|ref_pos| signature.parameters.get(&ref_pos).map(|v| v.0))
+                    {
+                        let ref_pos = opt_ref_pos.unwrap();
+                        let ref_norm_name = ref_info.get_norm_name();
+                        choice_refs.push((fragment, cmd.name.as_str(), ref_pos, ref_norm_name));
+                        let ref_subpos = match cmd.name.as_str() {
+                            "P" => cmd
+                                .indexsubref
+                                .or(ref_info.def_plural_subindex)
+                                .unwrap_or(0),
+                            "G" => cmd.indexsubref.unwrap_or(0),
+                            _ => panic!(),
+                        };
+                        if let Some(par_info) = ref_info.parameters.get(ref_subpos) {
+                            match cmd.name.as_str() {
+                                "P" => {
+                                    let ref_plural =
+                                        ref_info.parameters.iter().any(|par| par.allow_plural);
+                                    if cmd.indexref.is_none() && !ref_plural {
+                                        // implicit reference to a preceding parameter, which is no number at all
+                                        let number_pos = signature
+                                            .parameters
+                                            .iter()
+                                            .filter(|(pos, (info, _))| {
+                                                **pos < ref_pos
+                                                    && info
+                                                        .parameters
+                                                        .iter()
+                                                        .any(|par| par.allow_plural)
+                                            })
+                                            .map(|(pos, _)| *pos)
+                                            .max();
+                                        errors.push(ValidationError{
+                                            severity: Severity::Error,
+                                            code: ErrorCode::PluralNotAllowed,
+                                            pos_begin: Some(fragment.pos_begin),
+                                            pos_end: Some(fragment.pos_end),
+                                            message: format!(
+                                                "'{{{}}}' refers to the preceding parameter '{{{}:{}}}', which is not a number.",
+                                                cmd.name, ref_pos, ref_norm_name
+                                            ),
+                                            suggestion: number_pos.map(|pos| format!(
+                                                "Add a position reference to the number, like '{{P {} ...}}'.",
+                                                pos
+                                            )),
+                                        });
+                                    } else if !par_info.allow_plural {
+                                        errors.push(ValidationError{
+                                            severity: Severity::Error,
+                                            code: ErrorCode::PluralNotAllowed,
+                                            pos_begin: Some(fragment.pos_begin),
+                                            pos_end: Some(fragment.pos_end),
+                                            message: format!(
+                                                "'{{{}}}' references position '{}:{}', but '{{{}:{}}}' does not allow plurals.",
+                                                cmd.name, ref_pos, ref_subpos, ref_pos, ref_norm_name
+                                            ),
+                                            suggestion: None,
+                                        });
+                                    } else if config.warn_non_count_plural && !ref_info.count_like {
+                                        errors.push(ValidationError{
+                                            severity: Severity::Warning,
+                                            code: ErrorCode::NonCountPlural,
+                                            pos_begin: Some(fragment.pos_begin),
+                                            pos_end: Some(fragment.pos_end),
+                                            message: format!(
+                                                "'{{{}}}' references position '{}:{}', but '{{{}:{}}}' is not a count.",
+                                                cmd.name, ref_pos, ref_subpos, ref_pos, ref_norm_name
+                                            ),
+                                            suggestion: Some(String::from("Plural selection on this parameter is probably wrong.")),
+                                        });
+                                    }
+                                }
+                                "G" => {
+                                    if !par_info.allow_gender {
+                                        errors.push(ValidationError{
+                                            severity: Severity::Error,
+                                            code: ErrorCode::GenderNotAllowed,
+                                            pos_begin: Some(fragment.pos_begin),
+                                            pos_end: Some(fragment.pos_end),
+                                            message: format!(
+                                                "'{{{}}}' references position '{}:{}', but '{{{}:{}}}' does not allow genders.",
+                                                cmd.name, ref_pos, ref_subpos, ref_pos, ref_norm_name
+                                            ),
+                                            suggestion: None,
+                                        });
+                                    }
+                                }
+                                _ => panic!(),
+                            };
+                        } else {
+                            errors.push(ValidationError{
+                                severity: Severity::Error,
+                                code: ErrorCode::InvalidSubindex,
+                                pos_begin: Some(fragment.pos_begin),
+                                pos_end: Some(fragment.pos_end),
+                                message: format!(
+                                    "'{{{}}}' references position '{}:{}', but '{{{}:{}}}' only has {} subindices.",
+                                    cmd.name, ref_pos, ref_subpos, ref_pos, ref_norm_name, ref_info.parameters.len()
+                                ),
+                                suggestion: None,
+                            });
+                        }
+                    } else {
+                        errors.push(ValidationError {
+                            severity: Severity::Error,
+                            code: ErrorCode::InvalidChoiceReference,
+                            pos_begin: Some(fragment.pos_begin),
+                            pos_end: Some(fragment.pos_end),
+                            message: match opt_ref_pos {
+                                None => format!(
+                                    "'{{{}}}' has no preceding parameter to refer to.",
+                                    cmd.name
+                                ),
+                                Some(ref_pos) => format!(
+                                    "'{{{}}}' references position '{}', which has no parameter.",
+                                    cmd.name, ref_pos
+                                ),
+                            },
+                            suggestion: if cmd.indexref.is_none() {
+                                Some(String::from("Add a position reference."))
+                            } else {
+                                None
+                            },
+                        });
+                    }
+                }
+                if let Some(base_count) = opt_ref_pos
+                    .and_then(|ref_pos| base_choice_counts.get(&(cmd.name.clone(), ref_pos)))
+                {
+                    if *base_count != cmd.choices.len() {
+                        errors.push(ValidationError {
+                            severity: Severity::Warning,
+                            code: ErrorCode::ChoiceCountDiffers,
+                            pos_begin: Some(fragment.pos_begin),
+                            pos_end: Some(fragment.pos_end),
+                            message: format!(
+                                "'{{{}}}' has {} choices, but the base has {} choices for position {}.",
+                                cmd.name,
+                                cmd.choices.len(),
+                                base_count,
+                                opt_ref_pos.unwrap()
+                            ),
+                            suggestion: Some(String::from(
+                                "Check the language configuration of base and translation.",
+                            )),
+                        });
+                    }
+                }
+                for (i, choice) in cmd.choices.iter().enumerate() {
+                    if choice.contains(['\n', '\r']) {
+                        errors.push(ValidationError {
+                            severity: Severity::Warning,
+                            code: ErrorCode::LineBreakInChoice,
+                            pos_begin: Some(fragment.pos_begin),
+                            pos_end: Some(fragment.pos_end),
+                            message: format!(
+                                "Choice {} of '{{{}}}' contains a line break, which will be replaced by a space.",
+                                i, cmd.name
+                            ),
+                            suggestion: Some(String::from("Remove the line break from the choice.")),
+                        });
+                    }
+                    // Choices are plain text, the first '}' ends the choice list.
+                    let looks_like_command = choice.split('{').skip(1).any(|rest| {
+                        rest.starts_with(|c: char| {
+                            c.is_ascii_uppercase() || c.is_ascii_digit() || c == '}'
+                        })
+                    });
+                    if looks_like_command && !config.parse_choice_contents {
+                        let (begin, end) = cmd.choice_spans[i];
+                        errors.push(ValidationError {
+                            severity: Severity::Warning,
+                            code: ErrorCode::CommandInChoice,
+                            pos_begin: Some(fragment.pos_begin + begin),
+                            pos_end: Some(fragment.pos_begin + end),
+                            message: format!(
+                                "Choice {} of '{{{}}}' contains '{{', but string commands are not supported inside choices.",
+                                i, cmd.name
+                            ),
+                            suggestion: Some(String::from(
+                                "Move the string command out of the choice list.",
+                            )),
+                        });
+                    }
+                }
+                if config.parse_choice_contents {
+                    errors.extend(validate_choice_contents(
+                        config,
+                        cmd,
+                        fragment.pos_begin,
+                        signature,
+                    ));
+                }
+                front = 2;
+            }
+            FragmentContent::Text(text) => {
+                let newline = newline_info(&config.dialect).command;
+                let mut chars = text.chars().enumerate().peekable();
+                while let Some((i, c)) = chars.next() {
+                    let line_break = match c {
+                        '\r' if chars.next_if(|(_, next)| *next == '\n').is_some() => {
+                            Some(("CRLF", 2))
+                        }
+                        '\r' => Some(("CR", 1)),
+                        '\n' => Some(("LF", 1)),
+                        _ => None,
+                    };
+                    if let Some((kind, len)) = line_break {
+                        errors.push(ValidationError {
+                            severity: Severity::Warning,
+                            code: ErrorCode::RawLineBreak,
+                            pos_begin: Some(fragment.pos_begin + i),
+                            pos_end: Some(fragment.pos_begin + i + len),
+                            message: format!(
+                                "The text contains a raw line break ({}), which will be replaced by a space.",
+                                kind
+                            ),
+                            suggestion: Some(format!("Use '{{{}}}' for line breaks.", newline)),
+                        });
+                    } else if c.is_ascii_control() {
+                        errors.push(ValidationError {
+                            severity: Severity::Warning,
+                            code: ErrorCode::ControlCharacter,
+                            pos_begin: Some(fragment.pos_begin + i),
+                            pos_end: Some(fragment.pos_begin + i + 1),
+                            message: format!(
+                                "The text contains the control character U+{:04X}, which will be replaced by a space.",
+                                c as u32
+                            ),
+                            suggestion: Some(String::from("Remove the control character.")),
+                        });
+                    }
+                }
+                front = 2;
+            }
+            FragmentContent::Comment(_) => (),
+        }
+    }
+
+    for fragment in colour_stack {
+        errors.push(ValidationError {
+            severity: Severity::Error,
+            code: ErrorCode::UnbalancedColourStack,
+            pos_begin: Some(fragment.pos_begin),
+            pos_end: Some(fragment.pos_end),
+            message: String::from("'{PUSH_COLOUR}' without following '{POP_COLOUR}'."),
+            suggestion: Some(String::from("Add '{POP_COLOUR}'.")),
+        });
+    }
+
+    if base.is_some() {
+        for (fragment, name, ref_pos, ref_norm_name) in choice_refs {
+            if !positional_count.contains_key(&ref_pos) {
+                errors.push(ValidationError {
+                    severity: Severity::Warning,
+                    code: ErrorCode::MissingChoiceParameter,
+                    pos_begin: Some(fragment.pos_begin),
+                    pos_end: Some(fragment.pos_end),
+                    message: format!(
+                        "'{{{}}}' references position '{}', but the translation does not contain '{{{}:{}}}'.",
+                        name, ref_pos, ref_pos, ref_norm_name
+                    ),
+                    suggestion: Some(format!("Add '{{{}:{}}}'.", ref_pos, ref_norm_name)),
+                });
+            }
+        }
+    }
+
+    for (pos, (info, ex_count)) in &signature.parameters {
+        let norm_name = info.get_norm_name();
+        let found_count = positional_count.get(pos).cloned().unwrap_or(0);
+        let occurence = config.get_occurence(info);
+        if occurence != Occurence::ANY && found_count == 0 {
+            errors.push(ValidationError {
+                severity: Severity::Error,
+                code: ErrorCode::MissingParameter,
+                pos_begin: None,
+                pos_end: None,
+                message: format!("String command '{{{}:{}}}' is missing.", pos, norm_name),
+                suggestion: None,
+            });
+        } else if occurence == Occurence::EXACT && *ex_count != found_count {
+            errors.push(ValidationError {
+                severity: Severity::Warning,
+                code: ErrorCode::OccurrenceMismatch,
+                pos_begin: None,
+                pos_end: None,
+                message: format!(
+                    "String command '{{{}:{}}}': expected {} times, found {} times.",
+                    pos, norm_name, ex_count, found_count
+                ),
+                suggestion: None,
+            });
+        }
+    }
+
+    for (norm_name, (occurence, ex_count)) in &signature.nonpositional_count {
+        let found_count = nonpositional_count.get(norm_name).map(|v| v.1).unwrap_or(0);
+        if *occurence != Occurence::ANY && found_count == 0 {
+            errors.push(ValidationError {
+                severity: Severity::Warning,
+                code: ErrorCode::MissingCommand,
+                pos_begin: None,
+                pos_end: None,
+                message: format!("String command '{{{}}}' is missing.", norm_name),
+                suggestion: None,
+            });
+        } else if *occurence == Occurence::EXACT && *ex_count != found_count {
+            errors.push(ValidationError {
+                severity: Severity::Warning,
+                code: ErrorCode::OccurrenceMismatch,
+                pos_begin: None,
+                pos_end: None,
+                message: format!(
+                    "String command '{{{}}}': expected {} times, found {} times.",
+                    norm_name, ex_count, found_count
+                ),
+                suggestion: None,
+            });
+        }
+    }
+    for (norm_name, (occurence, _, (first_begin, first_end))) in &nonpositional_count {
+        if *occurence != Occurence::ANY && signature.nonpositional_count.get(norm_name).is_none() {
+            errors.push(ValidationError {
+                severity: Severity::Warning,
+                code: ErrorCode::UnexpectedCommand,
+                pos_begin: Some(*first_begin),
+                pos_end: Some(*first_end),
+                message: format!("String command '{{{}}}' is unexpected.", norm_name),
+                suggestion: Some(String::from("Remove this command.")),
+            });
+        }
+    }
+
+    errors.extend(validate_redundant_colours(config, test));
+    errors.extend(validate_trailing_space_commands(config, test));
+    errors.extend(validate_following_parameters(config, test));
+    errors.extend(validate_choice_order(config, test));
+    if config.warn_empty_choice {
+        errors.extend(validate_empty_choices(test));
+    }
+
+    if let Some(base) = base {
+        errors.extend(validate_leading_whitespace(config, base, test));
+        if config.compare_ellipsis {
+            errors.extend(validate_ellipsis(base, test));
+        }
+        if config.warn_dropped_gender_choice {
+            errors.extend(validate_dropped_gender_choices(config, base, test));
+        }
+        if config.warn_unused_case {
+            errors.extend(validate_unused_cases(config, base, test));
+        }
+        if let Some(max_length) = config.max_length {
+            let length = test.to_plain_text().chars().count();
+            if length > max_length {
+                errors.push(ValidationError {
+                    severity: Severity::Warning,
+                    code: ErrorCode::TranslationTooLong,
+                    pos_begin: None,
+                    pos_end: None,
+                    message: format!(
+                        "The translation is {} characters long, at most {} are allowed.",
+                        length, max_length
+                    ),
+                    suggestion: Some(String::from("Shorten the translation.")),
+                });
+            }
+        }
+        if config.warn_leading_newline {
+            let newline = newline_info(&config.dialect).command;
+            let starts_with_newline = |parsed: &ParsedString| match parsed.fragments.first() {
+                Some(StringFragment {
+                    content: FragmentContent::Command(cmd),
+                    ..
+                }) => cmd.name == newline,
+                _ => false,
+            };
+            if starts_with_newline(test) && !starts_with_newline(base) {
+                errors.push(ValidationError {
+                    severity: Severity::Warning,
+                    code: ErrorCode::LeadingLineBreak,
+                    pos_begin: Some(test.fragments[0].pos_begin),
+                    pos_end: Some(test.fragments[0].pos_end),
+                    message: String::from(
+                        "The translation starts with a line break, but the base does not.",
+                    ),
+                    suggestion: Some(format!("Remove the leading '{{{}}}'.", newline)),
+                });
+            }
+        }
+    }
+
+    if base.is_none() && config.base_hygiene {
+        errors.extend(validate_base_hygiene(test));
+    }
+
+    errors
+}
+
+/// Find the position of a trailing ellipsis, either '…' or '...', ignoring trailing whitespace.
+fn find_trailing_ellipsis(parsed: &ParsedString) -> Option<(usize, usize)> {
+    let fragment = parsed.fragments.last()?;
+    if let FragmentContent::Text(text) = &fragment.content {
+        let trimmed = text.trim_end();
+        let ellipsis = ["…", "..."].into_iter().find(|e| trimmed.ends_with(e))?;
+        let pos_end = fragment.pos_begin + trimmed.chars().count();
+        Some((pos_end - ellipsis.chars().count(), pos_end))
+    } else {
+        None
+    }
+}
+
+/// Find the text at the start of the string, before any parameter or choice, and its leading whitespace.
+fn find_leading_whitespace<'a>(
+    config: &LanguageConfig,
+    parsed: &'a ParsedString,
+) -> Option<(&'a StringFragment, &'a str)> {
+    for fragment in &parsed.fragments {
+        match &fragment.content {
+            FragmentContent::Text(text) => {
+                return Some((fragment, &text[..text.len() - text.trim_start().len()]));
+            }
+            FragmentContent::Gender(_) | FragmentContent::Comment(_) => (),
+            FragmentContent::Command(cmd)
+                if config
+                    .find_command(&cmd.name)
+                    .is_some_and(|info| info.parameters.is_empty()) => {}
+            _ => return None,
+        }
+    }
+    None
+}
+
+/// Compare the leading whitespace of the text at the start of base and translation.
+fn validate_leading_whitespace(
+    config: &LanguageConfig,
+    base: &ParsedString,
+    translation: &ParsedString,
+) -> Vec<ValidationError> {
+    let base_ws = find_leading_whitespace(config, base).map_or("", |(_, ws)| ws);
+    match find_leading_whitespace(config, translation) {
+        Some((fragment, ws)) if ws != base_ws => {
+            // Point at the whitespace, or at the whole text, if the whitespace is missing.
+            let pos_end = if ws.is_empty() {
+                fragment.pos_end
+            } else {
+                fragment.pos_begin + ws.chars().count()
+            };
+            vec![ValidationError {
+                severity: Severity::Warning,
+                code: ErrorCode::LeadingWhitespace,
+                pos_begin: Some(fragment.pos_begin),
+                pos_end: Some(pos_end),
+                message: String::from(
+                    "The text starts with different whitespace than in the base.",
+                ),
+                suggestion: Some(String::from(if base_ws.is_empty() {
+                    "Remove the leading whitespace."
+                } else {
+                    "Use the same leading whitespace as the base."
+                })),
+            }]
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Compare whether base and translation both end with an ellipsis.
+fn validate_ellipsis(base: &ParsedString, translation: &ParsedString) -> Vec<ValidationError> {
+    match (
+        find_trailing_ellipsis(base),
+        find_trailing_ellipsis(translation),
+    ) {
+        (Some(_), None) => vec![ValidationError {
+            severity: Severity::Warning,
+            code: ErrorCode::EllipsisMismatch,
+            pos_begin: translation.fragments.last().map(|f| f.pos_begin),
+            pos_end: translation.fragments.last().map(|f| f.pos_end),
+            message: String::from("The base ends with an ellipsis, but the translation does not."),
+            suggestion: Some(String::from("Add '…' to the end of the translation.")),
+        }],
+        (None, Some((pos_begin, pos_end))) => vec![ValidationError {
+            severity: Severity::Warning,
+            code: ErrorCode::EllipsisMismatch,
+            pos_begin: Some(pos_begin),
+            pos_end: Some(pos_end),
+            message: String::from("The translation ends with an ellipsis, but the base does not."),
+            suggestion: Some(String::from("Remove the ellipsis.")),
+        }],
+        _ => Vec::new(),
+    }
+}
+
+/// Find '{P}' and '{G}' without position reference, whose inferred parameter is on the wrong side of the choice.
+/// '{P}' refers to the previous parameter, so it must appear before the choice.
+/// '{G}' refers to the next parameter, so it must appear after the choice.
+fn validate_choice_order(config: &LanguageConfig, parsed: &ParsedString) -> Vec<ValidationError> {
+    let mut parameters = Vec::new();
+    let mut choices = Vec::new();
+    let mut pos = 0;
+    for (i, fragment) in parsed.fragments.iter().enumerate() {
+        match &fragment.content {
+            FragmentContent::Command(cmd) => {
+                if let Some(info) = config.find_command(&cmd.name) {
+                    if !info.parameters.is_empty() {
+                        pos = cmd.index.unwrap_or(pos);
+                        parameters.push((i, pos));
+                        pos += 1;
+                    }
+                }
+            }
+            FragmentContent::Choice(cmd) if cmd.indexref.is_none() => {
+                let opt_ref_pos = match cmd.name.as_str() {
+                    "P" => pos.checked_sub(1),
+                    _ => Some(pos),
+                };
+                if let Some(ref_pos) = opt_ref_pos {
+                    choices.push((i, fragment, cmd, ref_pos));
+                }
+            }
+            _ => (),
+        }
+    }
+
+    let mut errors = Vec::new();
+    for (i, fragment, cmd, ref_pos) in choices {
+        let appearances: Vec<usize> = parameters
+            .iter()
+            .filter(|(_, p)| *p == ref_pos)
+            .map(|(j, _)| *j)
+            .collect();
+        // Missing parameters are reported elsewhere.
+        let (placed, side) = match cmd.name.as_str() {
+            "P" => (appearances.iter().any(|j| *j < i), "precede"),
+            _ => (appearances.iter().any(|j| *j > i), "follow"),
+        };
+        if !appearances.is_empty() && !placed {
+            errors.push(ValidationError {
+                severity: Severity::Warning,
+                code: ErrorCode::MisplacedChoiceReference,
+                pos_begin: Some(fragment.pos_begin),
+                pos_end: Some(fragment.pos_end),
+                message: format!(
+                    "'{{{} ...}}' refers to position {}, but the parameter does not {} it.",
+                    cmd.name, ref_pos, side
+                ),
+                suggestion: Some(format!(
+                    "Add a position reference, like '{{{} {} ...}}', or move the choice.",
+                    cmd.name, ref_pos
+                )),
+            });
+        }
+    }
+    errors
+}
+
+/// Find empty choices in '{P ...}' and '{G ...}'.
+/// An empty first choice is common, like '{P "" s}', so only later empty choices are reported.
+fn validate_empty_choices(parsed: &ParsedString) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+    for fragment in &parsed.fragments {
+        if let FragmentContent::Choice(cmd) = &fragment.content {
+            for (i, (choice, span)) in cmd.choices.iter().zip(&cmd.choice_spans).enumerate() {
+                if i > 0 && choice.is_empty() {
+                    errors.push(ValidationError {
+                        severity: Severity::Warning,
+                        code: ErrorCode::EmptyChoice,
+                        pos_begin: Some(fragment.pos_begin + span.0),
+                        pos_end: Some(fragment.pos_begin + span.1),
+                        message: format!("Choice {} of '{{{} ...}}' is empty.", i + 1, cmd.name),
+                        suggestion: Some(String::from("Check whether a word is missing.")),
+                    });
+                }
+            }
+        }
+    }
+    errors
+}
+
+/// List the known cases, which the translation selects with '{STRING.case}', but the base does not.
+fn validate_unused_cases(
+    config: &LanguageConfig,
+    base: &ParsedString,
+    translation: &ParsedString,
+) -> Option<ValidationError> {
+    let base_cases: Vec<&String> = base
+        .commands()
+        .filter_map(|cmd| cmd.case.as_ref())
+        .collect();
+    let mut cases: Vec<&str> = Vec::new();
+    for case in translation.commands().filter_map(|cmd| cmd.case.as_ref()) {
+        let known = config.find_name(&config.cases, case).is_some();
+        if known && !base_cases.contains(&case) && !cases.contains(&case.as_str()) {
+            cases.push(case);
+        }
+    }
+    if cases.is_empty() {
+        return None;
+    }
+    Some(ValidationError {
+        severity: Severity::Info,
+        code: ErrorCode::UnusedCaseSelection,
+        pos_begin: None,
+        pos_end: None,
+        message: format!(
+            "The translation selects the cases '{}', which the base does not use.",
+            cases.join("', '")
+        ),
+        suggestion: None,
+    })
+}
+
+/// Find parameters, for which the base has a '{G ...}' choice, but the translation does not.
+fn validate_dropped_gender_choices(
+    config: &LanguageConfig,
+    base: &ParsedString,
+    translation: &ParsedString,
+) -> Vec<ValidationError> {
+    let trans_counts = get_choice_counts(config, translation);
+    let mut dropped: Vec<usize> = get_choice_counts(config, base)
+        .into_keys()
+        .filter(|key| key.0 == "G" && !trans_counts.contains_key(key))
+        .map(|(_, pos)| pos)
+        .collect();
+    dropped.sort();
+    dropped
+        .into_iter()
+        .map(|pos| ValidationError {
+            severity: Severity::Warning,
+            code: ErrorCode::DroppedGenderChoice,
+            pos_begin: None,
+            pos_end: None,
+            message: format!(
+                "The base has a gender choice for position {}, but the translation has none.",
+                pos
+            ),
+            suggestion: Some(format!(
+                "Add '{{G {} ...}}', if the translation depends on the gender.",
+                pos
+            )),
+        })
+        .collect()
+}
+
+/// Find colour commands, which have no effect, because another colour command follows without text in between.
+fn validate_redundant_colours(
+    config: &LanguageConfig,
+    parsed: &ParsedString,
+) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+    let mut last_colour: Option<(&StringFragment, &String)> = None;
+    for fragment in &parsed.fragments {
+        let colour = match &fragment.content {
+            FragmentContent::Text(text) if text.is_empty() => continue,
+            FragmentContent::Command(cmd)
+                if cmd.index.is_none()
+                    && config
+                        .find_command(&cmd.name)
+                        .is_some_and(|info| info.sets_colour) =>
+            {
+                Some((fragment, &cmd.name))
+            }
+            _ => None,
+        };
+        if let (Some((prev, prev_name)), Some((_, name))) = (last_colour, colour) {
+            errors.push(ValidationError {
+                severity: Severity::Warning,
+                code: ErrorCode::RedundantColour,
+                pos_begin: Some(prev.pos_begin),
+                pos_end: Some(prev.pos_end),
+                message: format!(
+                    "'{{{}}}' has no effect, because it is directly followed by '{{{}}}'.",
+                    prev_name, name
+                ),
+                suggestion: Some(format!("Remove '{{{}}}'.", prev_name)),
+            });
+        }
+        last_colour = colour;
+    }
+    errors
+}
+
+/// Find space-like commands, like '{NBSP}', at the end of a line or the end of the string.
+/// Trailing blanks are removed from text, but not from commands.
+fn validate_trailing_space_commands(
+    config: &LanguageConfig,
+    parsed: &ParsedString,
+) -> Vec<ValidationError> {
+    let newline = newline_info(&config.dialect).command;
+    let mut errors = Vec::new();
+    let mut last_space: Option<(&StringFragment, &String)> = None;
+    for fragment in &parsed.fragments {
+        match &fragment.content {
+            FragmentContent::Text(text) if text.trim().is_empty() => continue,
+            FragmentContent::Comment(_) => continue,
+            FragmentContent::Command(cmd) if cmd.name == newline => {
+                errors.extend(last_space.map(|(f, n)| trailing_space_error(f, n, "line")));
+                last_space = None;
+            }
+            FragmentContent::Command(cmd)
+                if config
+                    .find_command(&cmd.name)
+                    .is_some_and(|info| info.is_space_like) =>
+            {
+                last_space = Some((fragment, &cmd.name));
+            }
+            _ => last_space = None,
+        }
+    }
+    errors.extend(last_space.map(|(f, n)| trailing_space_error(f, n, "string")));
+    errors
+}
+
+/// Find modifier commands, which are not directly followed by a number, like '{NUM}'.
+fn validate_following_parameters(
+    config: &LanguageConfig,
+    parsed: &ParsedString,
+) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+    for (i, fragment) in parsed.fragments.iter().enumerate() {
+        let FragmentContent::Command(cmd) = &fragment.content else {
+            continue;
+        };
+        if !config
+            .find_command(&cmd.name)
+            .is_some_and(|info| info.requires_following)
+        {
+            continue;
+        }
+        let followed = match parsed.fragments.get(i + 1).map(|f| &f.content) {
+            Some(FragmentContent::Command(next)) => config
+                .find_command(&next.name)
+                .is_some_and(|info| info.parameters.iter().any(|par| par.allow_plural)),
+            _ => false,
+        };
+        if !followed {
+            errors.push(ValidationError {
+                severity: Severity::Error,
+                code: ErrorCode::MissingFollowingParameter,
+                pos_begin: Some(fragment.pos_begin),
+                pos_end: Some(fragment.pos_end),
+                message: format!(
+                    "'{{{}}}' must be directly followed by a number, like '{{NUM}}'.",
+                    cmd.name
+                ),
+                suggestion: Some(format!(
+                    "Move '{{{}}}' directly in front of the number.",
+                    cmd.name
+                )),
+            });
+        }
+    }
+    errors
+}
+
+/// Warning for a space-like command at the end of a line or string.
+fn trailing_space_error(fragment: &StringFragment, name: &str, end: &str) -> ValidationError {
+    ValidationError {
+        severity: Severity::Warning,
+        code: ErrorCode::TrailingSpaceCommand,
+        pos_begin: Some(fragment.pos_begin),
+        pos_end: Some(fragment.pos_end),
+        message: format!(
+            "'{{{}}}' at the end of the {} has no visible effect.",
+            name, end
+        ),
+        suggestion: Some(format!("Remove '{{{}}}'.", name)),
+    }
+}
+
+/// Find constructs in a base string, which only make sense in translations.
+fn validate_base_hygiene(base: &ParsedString) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+    for fragment in &base.fragments {
+        // Gender definitions are always an error in base strings, so only case selections are left here.
+        if let FragmentContent::Command(cmd) = &fragment.content {
+            if let Some(c) = &cmd.case {
+                errors.push(ValidationError {
+                    severity: Severity::Warning,
+                    code: ErrorCode::TranslationOnly,
+                    pos_begin: Some(fragment.pos_begin),
+                    pos_end: Some(fragment.pos_end),
+                    message: String::from(
+                        "Case selections belong in translations, the base language has no cases.",
+                    ),
+                    suggestion: Some(format!("Remove '.{}'.", c)),
+                });
+            }
+        }
+    }
+    errors
+}
+
+fn normalize_string(
+    dialect: &Dialect,
+    custom_commands: &[CustomCommand],
+    parsed: &mut ParsedString,
+) {
+    // comments are only for the build step
+    parsed
+        .fragments
+        .retain(|f| !matches!(f.content, FragmentContent::Comment(_)));
+
+    let mut parameters = HashMap::new();
+
+    let mut pos = 0;
+    for fragment in &mut parsed.fragments {
+        match &mut fragment.content {
+            FragmentContent::Command(cmd) => {
+                if let Some(info) = find_command(dialect, custom_commands, &cmd.name) {
+                    if let Some(norm_name) = info.norm_name {
+                        // normalize name
+                        cmd.name = String::from(norm_name);
+                    }
+                    if !info.parameters.is_empty() {
+                        if let Some(index) = cmd.index {
+                            pos = index;
+                        } else {
+                            // add missing indices
+                            cmd.index = Some(pos);
+                        }
+                        parameters.insert(pos, info);
+                        pos += 1;
+                    }
+                }
+            }
+            FragmentContent::Choice(cmd) => {
+                match cmd.name.as_str() {
+                    "P" => {
+                        if cmd.indexref.is_none() && pos > 0 {
+                            // add missing indices
+                            cmd.indexref = Some(pos - 1);
+                        }
+                    }
+                    "G" => {
+                        if cmd.indexref.is_none() {
+                            // add missing indices
+                            cmd.indexref = Some(pos);
+                        }
+                    }
+                    _ => panic!(),
+                };
+            }
+            _ => (),
+        }
+    }
+
+    for fragment in &mut parsed.fragments {
+        if let FragmentContent::Choice(cmd) = &mut fragment.content {
+            if let Some(ref_info) = cmd.indexref.and_then(|pos| parameters.get(&pos)) {
+                let def_subindex = match cmd.name.as_str() {
+                    "P" => ref_info.def_plural_subindex.unwrap_or(0),
+                    _ => 0,
+                };
+                // keep the subindex, if the command has multiple sub-parameters to choose from
+                let candidates = ref_info
+                    .parameters
+                    .iter()
+                    .filter(|par| match cmd.name.as_str() {
+                        "P" => par.allow_plural,
+                        _ => par.allow_gender,
+                    })
+                    .count();
+                if candidates <= 1 && cmd.indexsubref == Some(def_subindex) {
+                    // remove subindex, if default
+                    cmd.indexsubref = None;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize() {
+        let mut s1 = String::from("");
+        let mut s2 = String::from(" a b c ");
+        let mut s3 = String::from("\0a\tb\rc\r\n");
+        let mut s4 = String::from("abc\u{b3}");
+        remove_ascii_ctrl(&mut s1);
+        remove_ascii_ctrl(&mut s2);
+        remove_ascii_ctrl(&mut s3);
+        remove_ascii_ctrl(&mut s4);
+        assert_eq!(s1, String::from(""));
+        assert_eq!(s2, String::from(" a b c "));
+        assert_eq!(s3, String::from(" a b c "));
+        assert_eq!(s4, String::from("abc\u{b3}"));
+        remove_trailing_blanks(&mut s1);
+        remove_trailing_blanks(&mut s2);
+        remove_trailing_blanks(&mut s3);
+        remove_trailing_blanks(&mut s4);
+        assert_eq!(s1, String::from(""));
+        assert_eq!(s2, String::from(" a b c"));
+        assert_eq!(s3, String::from(" a b c"));
+        assert_eq!(s4, String::from("abc\u{b3}"));
+    }
+
+    #[test]
+    fn test_newline_info() {
+        for dialect in [Dialect::NEWGRF, Dialect::GAMESCRIPT, Dialect::OPENTTD] {
+            assert_eq!(
+                newline_info(&dialect),
+                NewlineInfo {
+                    command: "",
+                    line_break: true,
+                    strip_trailing_blanks: true,
+                }
+            );
+        }
+
+        let mut parsed = ParsedString::parse("a {}b\t{RED} {}{NBSP} ").unwrap();
+        sanitize_whitespace(&Dialect::NEWGRF, &mut parsed);
+        assert_eq!(parsed.compile(), "a{}b {RED}{}{NBSP}");
+    }
+
+    #[test]
+    fn test_signature_empty() {
+        let parsed = ParsedString::parse("").unwrap();
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            ..Default::default()
+        };
+        let sig = get_signature(&config, &parsed).unwrap();
+        assert!(sig.parameters.is_empty());
+        assert!(sig.nonpositional_count.is_empty());
+    }
+
+    #[test]
+    fn test_signature_pos() {
+        let parsed = ParsedString::parse("{P a b}{RED}{NUM}{NBSP}{MONO_FONT}{5:STRING.foo}{RED}{2:STRING3.bar}{RAW_STRING}{3:RAW_STRING}{G c d}").unwrap();
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            ..Default::default()
+        };
+        let sig = get_signature(&config, &parsed).unwrap();
+        assert_eq!(sig.parameters.len(), 4);
+        assert_eq!(sig.parameters.get(&0).unwrap().0.name, "NUM");
+        assert_eq!(sig.parameters.get(&0).unwrap().1, 1);
+        assert_eq!(sig.parameters.get(&5).unwrap().0.name, "STRING");
+        assert_eq!(sig.parameters.get(&5).unwrap().1, 1);
+        assert_eq!(sig.parameters.get(&2).unwrap().0.name, "STRING3");
+        assert_eq!(sig.parameters.get(&2).unwrap().1, 1);
+        assert_eq!(sig.parameters.get(&3).unwrap().0.name, "RAW_STRING");
+        assert_eq!(sig.parameters.get(&3).unwrap().1, 2);
+        assert_eq!(sig.nonpositional_count.len(), 3);
+        assert_eq!(
+            sig.nonpositional_count.get("RED"),
+            Some(&(Occurence::NONZERO, 2))
+        );
+        assert_eq!(
+            sig.nonpositional_count.get("MONO_FONT"),
+            Some(&(Occurence::EXACT, 1))
+        );
+        assert_eq!(
+            sig.nonpositional_count.get("NBSP"),
+            Some(&(Occurence::ANY, 1))
+        );
+    }
+
+    #[test]
+    fn test_signature_dialect() {
+        let parsed = ParsedString::parse("{RAW_STRING}").unwrap();
+
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            ..Default::default()
+        };
+        let sig = get_signature(&config, &parsed).unwrap();
+        assert_eq!(sig.parameters.len(), 1);
+        assert_eq!(sig.parameters.get(&0).unwrap().0.name, "RAW_STRING");
+        assert_eq!(sig.parameters.get(&0).unwrap().1, 1);
+        assert_eq!(sig.nonpositional_count.len(), 0);
+
+        let err = get_signature(
+            &LanguageConfig {
+                dialect: Dialect::NEWGRF,
+                ..Default::default()
+            },
+            &parsed,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(err.len(), 1);
+        assert_eq!(
+            err[0],
+            ValidationError {
+                severity: Severity::Error,
+                code: ErrorCode::UnknownCommand,
+                pos_begin: Some(0),
+                pos_end: Some(12),
+                message: String::from("Unknown string command '{RAW_STRING}'."),
+                suggestion: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_signature_unknown() {
+        let parsed = ParsedString::parse("{FOOBAR}").unwrap();
+        let err = get_signature(
+            &LanguageConfig {
+                dialect: Dialect::OPENTTD,
+                ..Default::default()
+            },
+            &parsed,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(err.len(), 1);
+        assert_eq!(
+            err[0],
+            ValidationError {
+                severity: Severity::Error,
+                code: ErrorCode::UnknownCommand,
+                pos_begin: Some(0),
+                pos_end: Some(8),
+                message: String::from("Unknown string command '{FOOBAR}'."),
+                suggestion: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_signature_nonpos() {
+        let parsed = ParsedString::parse("{1:RED}").unwrap();
+        let err = get_signature(
+            &LanguageConfig {
+                dialect: Dialect::OPENTTD,
+                ..Default::default()
+            },
+            &parsed,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(err.len(), 1);
+        assert_eq!(
+            err[0],
+            ValidationError {
+                severity: Severity::Error,
+                code: ErrorCode::UnexpectedPositionReference,
+                pos_begin: Some(0),
+                pos_end: Some(7),
+                message: String::from("Command '{RED}' cannot have a position reference."),
+                suggestion: Some(String::from("Remove '1:'.")),
+            }
+        );
+    }
+
+    #[test]
+    fn test_validate_empty() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 0,
+            ..Default::default()
+        };
+        let base = ParsedString::parse("").unwrap();
+
+        let val_base = validate_string(&config, &base, None);
+        assert_eq!(val_base.len(), 0);
+
+        let val_trans = validate_string(&config, &base, Some(&base));
+        assert_eq!(val_trans.len(), 0);
+    }
+
+    #[test]
+    fn test_validate_invalid() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 0,
+            ..Default::default()
+        };
+        let base = ParsedString::parse("{FOOBAR}").unwrap();
+
+        let val_base = validate_string(&config, &base, None);
+        assert_eq!(val_base.len(), 1);
+        assert_eq!(
+            val_base[0],
+            ValidationError {
+                severity: Severity::Error,
+                code: ErrorCode::UnknownCommand,
+                pos_begin: Some(0),
+                pos_end: Some(8),
+                message: String::from("Unknown string command '{FOOBAR}'."),
+                suggestion: None,
+            }
+        );
+
+        let val_trans = validate_string(&config, &base, Some(&base));
+        assert_eq!(val_trans.len(), 1);
+        assert_eq!(
+            val_trans[0],
+            ValidationError {
+                severity: Severity::Error,
+                code: ErrorCode::InvalidBase,
+                pos_begin: None,
+                pos_end: None,
+                message: String::from("Base language text is invalid."),
+                suggestion: Some(String::from("This is a bug; wait until it is fixed.")),
+            }
+        );
+    }
+
+    #[test]
+    fn test_validate_positional() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 0,
+            ..Default::default()
+        };
+        let base = ParsedString::parse("{NUM}").unwrap();
+        let val_base = validate_string(&config, &base, None);
+        assert_eq!(val_base.len(), 0);
+
+        {
+            let trans = ParsedString::parse("{0:NUM}").unwrap();
+            let val_trans = validate_string(&config, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 0);
+        }
+        {
+            let trans = ParsedString::parse("{FOOBAR}{NUM}").unwrap();
+            let val_trans = validate_string(&config, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 1);
+            assert_eq!(
+                val_trans[0],
+                ValidationError {
+                    severity: Severity::Error,
+                    code: ErrorCode::UnknownCommand,
+                    pos_begin: Some(0),
+                    pos_end: Some(8),
+                    message: String::from("Unknown string command '{FOOBAR}'."),
+                    suggestion: None,
+                }
+            );
+        }
+        {
+            let trans = ParsedString::parse("{1:NUM}").unwrap();
+            let val_trans = validate_string(&config, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 2);
+            assert_eq!(
+                val_trans[0],
+                ValidationError {
+                    severity: Severity::Error,
+                    code: ErrorCode::ParameterOutOfRange,
+                    pos_begin: Some(0),
+                    pos_end: Some(7),
+                    message: String::from("There is no parameter in position 1, found '{NUM}'."),
+                    suggestion: None,
+                }
+            );
+            assert_eq!(
+                val_trans[1],
+                ValidationError {
+                    severity: Severity::Error,
+                    code: ErrorCode::MissingParameter,
+                    pos_begin: None,
+                    pos_end: None,
+                    message: String::from("String command '{0:NUM}' is missing."),
+                    suggestion: None,
+                }
+            );
+        }
+        {
+            let trans = ParsedString::parse("{COMMA}").unwrap();
+            let val_trans = validate_string(&config, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 2);
+            assert_eq!(
+                val_trans[0],
+                ValidationError {
+                    severity: Severity::Error,
+                    code: ErrorCode::ParameterMismatch,
+                    pos_begin: Some(0),
+                    pos_end: Some(7),
+                    message: String::from("Expected '{0:NUM}', found '{COMMA}'."),
+                    suggestion: None,
+                }
+            );
+            assert_eq!(
+                val_trans[1],
+                ValidationError {
+                    severity: Severity::Error,
+                    code: ErrorCode::MissingParameter,
+                    pos_begin: None,
+                    pos_end: None,
+                    message: String::from("String command '{0:NUM}' is missing."),
+                    suggestion: None,
+                }
+            );
+        }
+        {
+            let trans = ParsedString::parse("{0:NUM}{0:NUM}").unwrap();
+            let val_trans = validate_string(&config, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 1);
+            assert_eq!(
+                val_trans[0],
+                ValidationError {
+                    severity: Severity::Warning,
+                    code: ErrorCode::OccurrenceMismatch,
+                    pos_begin: None,
+                    pos_end: None,
+                    message: String::from(
+                        "String command '{0:NUM}': expected 1 times, found 2 times."
+                    ),
+                    suggestion: None,
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_unused_gender() {
+        let mut config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![String::from("m"), String::from("f")],
+            plural_count: 2,
+            ..Default::default()
+        };
+        let base = ParsedString::parse("{NUM} apples").unwrap();
+        let trans = ParsedString::parse("{G=f}{NUM} Äpfel").unwrap();
+        let val_trans = validate_string(&config, &trans, Some(&base));
+        assert_eq!(val_trans.len(), 0);
+
+        config.hint_unused_gender = true;
+        let val_trans = validate_string(&config, &trans, Some(&base));
+        assert_eq!(
+            val_trans,
+            vec![ValidationError {
+                severity: Severity::Info,
+                code: ErrorCode::UnusedGenderDefinition,
+                pos_begin: Some(0),
+                pos_end: Some(5),
+                message: String::from(
+                    "The base string has no gender-dependent output, the gender definition may be unnecessary."
+                ),
+                suggestion: Some(String::from("Remove '{G=...}'.")),
+            }]
+        );
+
+        let base = ParsedString::parse("{STRING} apples").unwrap();
+        let trans = ParsedString::parse("{G=f}{STRING} Äpfel").unwrap();
+        let val_trans = validate_string(&config, &trans, Some(&base));
+        assert_eq!(val_trans.len(), 0);
+    }
+
+    #[test]
+    fn test_validate_front() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![String::from("a"), String::from("b")],
+            plural_count: 0,
+            ..Default::default()
+        };
+        let base = ParsedString::parse("{BIG_FONT}foo{NUM}").unwrap();
+        let val_base = validate_string(&config, &base, None);
+        assert_eq!(val_base.len(), 0);
+
+        {
+            let trans = ParsedString::parse("{G=a}{BIG_FONT}bar{NUM}").unwrap();
+            let val_trans = validate_string(&config, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 0);
+        }
+        {
+            let trans = ParsedString::parse("{G=a}{G=a}{BIG_FONT}bar{NUM}").unwrap();
+            let val_trans = validate_string(&config, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 1);
+            assert_eq!(
+                val_trans[0],
+                ValidationError {
+                    severity: Severity::Warning,
+                    code: ErrorCode::DuplicateGenderDefinition,
+                    pos_begin: Some(5),
+                    pos_end: Some(10),
+                    message: String::from("Duplicate gender definition."),
+                    suggestion: Some(String::from("Remove the second '{G=...}'.")),
+                }
+            );
+        }
+        {
+            let trans = ParsedString::parse("{BIG_FONT}{G=a}bar{NUM}").unwrap();
+            let val_trans = validate_string(&config, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 1);
+            assert_eq!(
+                val_trans[0],
+                ValidationError {
+                    severity: Severity::Warning,
+                    code: ErrorCode::GenderNotAtFront,
+                    pos_begin: Some(10),
+                    pos_end: Some(15),
+                    message: String::from("Gender definitions must be at the front."),
+                    suggestion: Some(String::from(
+                        "Move '{G=...}' to the front of the translation."
+                    )),
+                }
+            );
+        }
+        {
+            let trans = ParsedString::parse("{1:G=a}{BIG_FONT}bar{NUM}").unwrap();
+            let val_trans = validate_string(&config, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 1);
+            assert_eq!(
+                val_trans[0],
+                ValidationError {
+                    severity: Severity::Error,
+                    code: ErrorCode::UnexpectedPositionReference,
+                    pos_begin: Some(0),
+                    pos_end: Some(7),
+                    message: String::from("Gender definitions cannot have a position reference."),
+                    suggestion: Some(String::from("Remove '1:'.")),
+                }
+            );
+        }
+        {
+            let trans = ParsedString::parse("foo{BIG_FONT}bar{NUM}").unwrap();
+            let val_trans = validate_string(&config, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 0);
+        }
+        {
+            let trans = ParsedString::parse("foo{G=a}bar{NUM}").unwrap();
+            let val_trans = validate_string(&config, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 2);
+            assert_eq!(
+                val_trans[0],
+                ValidationError {
+                    severity: Severity::Warning,
+                    code: ErrorCode::GenderNotAtFront,
+                    pos_begin: Some(3),
+                    pos_end: Some(8),
+                    message: String::from("Gender definitions must be at the front."),
+                    suggestion: Some(String::from(
+                        "Move '{G=...}' to the front of the translation."
+                    )),
+                }
+            );
+            assert_eq!(
+                val_trans[1],
+                ValidationError {
+                    severity: Severity::Warning,
+                    code: ErrorCode::MissingCommand,
+                    pos_begin: None,
+                    pos_end: None,
+                    message: String::from("String command '{BIG_FONT}' is missing."),
+                    suggestion: None,
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_unused_case() {
+        let mut config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![String::from("gen"), String::from("dat")],
+            genders: vec![],
+            plural_count: 2,
+            ..Default::default()
+        };
+        let base = ParsedString::parse("{STRING} and {STRING}").unwrap();
+        let trans = ParsedString::parse("{STRING.dat} und {STRING.gen} {1:STRING.dat}").unwrap();
+        assert_eq!(validate_string(&config, &trans, Some(&base)), vec![]);
+
+        config.warn_unused_case = true;
+        assert_eq!(
+            validate_string(&config, &trans, Some(&base)),
+            vec![ValidationError {
+                severity: Severity::Info,
+                code: ErrorCode::UnusedCaseSelection,
+                pos_begin: None,
+                pos_end: None,
+                message: String::from(
+                    "The translation selects the cases 'dat', 'gen', which the base does not use."
+                ),
+                suggestion: None,
+            }]
+        );
+
+        let trans = ParsedString::parse("{STRING} und {STRING}").unwrap();
+        assert_eq!(validate_string(&config, &trans, Some(&base)), vec![]);
+    }
+
+    #[test]
+    fn test_validate_plural_without_number() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            ..Default::default()
+        };
+        let base = ParsedString::parse("{STRING}").unwrap();
+        let trans = ParsedString::parse("{STRING} {P a b}").unwrap();
+        assert_eq!(
+            validate_string(&config, &trans, Some(&base)),
+            vec![ValidationError {
+                severity: Severity::Error,
+                code: ErrorCode::NoPluralParameter,
+                pos_begin: Some(9),
+                pos_end: Some(16),
+                message: String::from(
+                    "Cannot use a plural choice: the base string has no number parameter."
+                ),
+                suggestion: Some(String::from(
+                    "Replace '{P ...}' with the text of a single plural form."
+                )),
+            }]
+        );
+
+        let base = ParsedString::parse("{STRING} {NUM}").unwrap();
+        let trans = ParsedString::parse("{STRING} {P a b} {NUM}").unwrap();
+        assert_eq!(
+            validate_string(&config, &trans, Some(&base))
+                .iter()
+                .map(|e| e.code)
+                .collect::<Vec<_>>(),
+            vec![ErrorCode::PluralNotAllowed]
+        );
+    }
+
+    #[test]
+    fn test_validate_implicit_plural_reference() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            ..Default::default()
+        };
+        let base = ParsedString::parse("{STRING}{NUM}").unwrap();
+
+        // The preceding parameter is not a number.
+        let trans = ParsedString::parse("{STRING}{P a b} {NUM}").unwrap();
+        assert_eq!(
+            validate_string(&config, &trans, Some(&base)),
+            vec![ValidationError {
+                severity: Severity::Error,
+                code: ErrorCode::PluralNotAllowed,
+                pos_begin: Some(8),
+                pos_end: Some(15),
+                message: String::from(
+                    "'{P}' refers to the preceding parameter '{0:STRING}', which is not a number."
+                ),
+                suggestion: None,
+            }]
+        );
+
+        // There is no preceding parameter.
+        let trans = ParsedString::parse("{P a b} {STRING}{NUM}").unwrap();
+        assert_eq!(
+            validate_string(&config, &trans, Some(&base)),
+            vec![ValidationError {
+                severity: Severity::Error,
+                code: ErrorCode::InvalidChoiceReference,
+                pos_begin: Some(0),
+                pos_end: Some(7),
+                message: String::from("'{P}' has no preceding parameter to refer to."),
+                suggestion: Some(String::from("Add a position reference.")),
+            }]
+        );
+
+        let trans = ParsedString::parse("{STRING}{NUM}{P a b}").unwrap();
+        assert_eq!(validate_string(&config, &trans, Some(&base)), vec![]);
+    }
+
+    #[test]
+    fn test_validate_gender_front_severity() {
+        let mut config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![String::from("a"), String::from("b")],
+            plural_count: 2,
+            ..Default::default()
+        };
+        let base = String::from("Train {NUM}");
+        let translation = "Zug {G=a}{NUM}";
+
+        let res = validate_translation(&config, &base, "default", translation);
+        assert_eq!(
+            res.errors
+                .iter()
+                .map(|e| (e.severity, e.code))
+                .collect::<Vec<_>>(),
+            vec![(Severity::Warning, ErrorCode::GenderNotAtFront)]
+        );
+        assert_eq!(res.normalized, Some(String::from("Zug {G=a}{0:NUM}")));
+
+        config.gender_front_severity = Some(Severity::Error);
+        let res = validate_translation(&config, &base, "default", translation);
+        assert_eq!(
+            res.errors
+                .iter()
+                .map(|e| (e.severity, e.code))
+                .collect::<Vec<_>>(),
+            vec![(Severity::Error, ErrorCode::GenderNotAtFront)]
+        );
+        assert_eq!(res.normalized, None);
+
+        let res = validate_translation(&config, &base, "default", "{G=a}{G=b}Zug {NUM}");
+        assert_eq!(
+            res.errors
+                .iter()
+                .map(|e| (e.severity, e.code))
+                .collect::<Vec<_>>(),
+            vec![(Severity::Error, ErrorCode::DuplicateGenderDefinition)]
+        );
+    }
+
+    #[test]
+    fn test_validate_position_references() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![String::from("x"), String::from("y")],
+            genders: vec![String::from("a"), String::from("b")],
+            plural_count: 2,
+            ..Default::default()
+        };
+        let base = ParsedString::parse("{RED}{NUM}{STRING3}").unwrap();
+        let val_base = validate_string(&config, &base, None);
+        assert_eq!(val_base.len(), 0);
+
+        {
+            let trans = ParsedString::parse("{RED}{1:STRING.x}{0:NUM}").unwrap();
+            let val_trans = validate_string(&config, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 0);
+        }
+        {
+            let trans = ParsedString::parse("{2:RED}{1:STRING.z}{0:NUM.x}").unwrap();
+            let val_trans = validate_string(&config, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 3);
+            assert_eq!(
+                val_trans[0],
+                ValidationError {
+                    severity: Severity::Error,
+                    code: ErrorCode::UnexpectedPositionReference,
+                    pos_begin: Some(0),
+                    pos_end: Some(7),
+                    message: String::from("Command '{RED}' cannot have a position reference."),
+                    suggestion: Some(String::from("Remove '2:'.")),
+                }
+            );
+            assert_eq!(
+                val_trans[1],
+                ValidationError {
+                    severity: Severity::Error,
+                    code: ErrorCode::UnknownCase,
+                    pos_begin: Some(7),
+                    pos_end: Some(19),
+                    message: String::from("Unknown case 'z'."),
+                    suggestion: Some(String::from("Known cases are: 'x', 'y'")),
+                }
+            );
+            assert_eq!(
+                val_trans[2],
+                ValidationError {
+                    severity: Severity::Error,
+                    code: ErrorCode::CaseNotAllowed,
+                    pos_begin: Some(19),
+                    pos_end: Some(28),
+                    message: String::from("No case selection allowed for '{NUM}'."),
+                    suggestion: Some(String::from("Remove '.x'.")),
+                }
+            );
+        }
+        {
+            let trans = ParsedString::parse("{RED}{NUM}{G i j}{P i j}{STRING.y}").unwrap();
+            let val_trans = validate_string(&config, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 0);
+        }
+        {
+            let trans = ParsedString::parse("{RED}{NUM}{G 0 i j}{P 1 i j}{STRING.y}").unwrap();
+            let val_trans = validate_string(&config, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 2);
+            assert_eq!(
+                val_trans[0],
+                ValidationError {
+                    severity: Severity::Error,
+                    code: ErrorCode::GenderNotAllowed,
+                    pos_begin: Some(10),
+                    pos_end: Some(19),
+                    message: String::from(
+                        "'{G}' references position '0:0', but '{0:NUM}' does not allow genders."
+                    ),
+                    suggestion: None,
+                }
+            );
+            assert_eq!(
+                val_trans[1],
+                ValidationError {
+                    severity: Severity::Error,
+                    code: ErrorCode::PluralNotAllowed,
+                    pos_begin: Some(19),
+                    pos_end: Some(28),
+                    message: String::from(
+                        "'{P}' references position '1:0', but '{1:STRING}' does not allow plurals."
+                    ),
+                    suggestion: None,
+                }
+            );
+        }
+        {
+            let trans = ParsedString::parse("{RED}{NUM}{G 1:1 i j}{P 1:3 i j}{STRING.y}").unwrap();
+            let val_trans = validate_string(&config, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 0);
+        }
+        {
+            let trans = ParsedString::parse("{RED}{NUM}{G 1:4 i j}{P 1:4 i j}{STRING.y}").unwrap();
+            let val_trans = validate_string(&config, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 2);
+            assert_eq!(
+                val_trans[0],
+                ValidationError {
+                    severity: Severity::Error,
+                    code: ErrorCode::InvalidSubindex,
+                    pos_begin: Some(10),
+                    pos_end: Some(21),
+                    message: String::from(
+                        "'{G}' references position '1:4', but '{1:STRING}' only has 4 subindices."
+                    ),
+                    suggestion: None,
+                }
+            );
+            assert_eq!(
+                val_trans[1],
+                ValidationError {
+                    severity: Severity::Error,
+                    code: ErrorCode::InvalidSubindex,
+                    pos_begin: Some(21),
+                    pos_end: Some(32),
+                    message: String::from(
+                        "'{P}' references position '1:4', but '{1:STRING}' only has 4 subindices."
+                    ),
+                    suggestion: None,
+                }
+            );
+        }
+        {
+            let trans = ParsedString::parse("{RED}{NUM}{G 2 i j}{P 2 i j}{STRING.y}").unwrap();
+            let val_trans = validate_string(&config, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 2);
+            assert_eq!(
+                val_trans[0],
+                ValidationError {
+                    severity: Severity::Error,
+                    code: ErrorCode::InvalidChoiceReference,
+                    pos_begin: Some(10),
+                    pos_end: Some(19),
+                    message: String::from("'{G}' references position '2', which has no parameter."),
+                    suggestion: None,
+                }
+            );
+            assert_eq!(
+                val_trans[1],
+                ValidationError {
+                    severity: Severity::Error,
+                    code: ErrorCode::InvalidChoiceReference,
+                    pos_begin: Some(19),
+                    pos_end: Some(28),
+                    message: String::from("'{P}' references position '2', which has no parameter."),
+                    suggestion: None,
+                }
+            );
+        }
+        {
+            let trans = ParsedString::parse("{RED}{P i j}{NUM}{STRING.y}{G i j}").unwrap();
+            let val_trans = validate_string(&config, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 2);
+            assert_eq!(
+                val_trans[0],
+                ValidationError {
+                    severity: Severity::Error,
+                    code: ErrorCode::InvalidChoiceReference,
+                    pos_begin: Some(5),
+                    pos_end: Some(12),
+                    message: String::from("'{P}' has no preceding parameter to refer to."),
+                    suggestion: Some(String::from("Add a position reference.")),
+                }
+            );
+            assert_eq!(
+                val_trans[1],
+                ValidationError {
+                    severity: Severity::Error,
+                    code: ErrorCode::InvalidChoiceReference,
+                    pos_begin: Some(27),
+                    pos_end: Some(34),
+                    message: String::from("'{G}' references position '2', which has no parameter."),
+                    suggestion: Some(String::from("Add a position reference.")),
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_explicit_indices() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![String::from("x")],
+            genders: vec![],
+            plural_count: 2,
+            require_explicit_indices: true,
+            ..Default::default()
+        };
+        let base = ParsedString::parse("{RED}{NUM}{STRING}").unwrap();
+        let val_base = validate_string(&config, &base, None);
+        assert_eq!(val_base.len(), 0);
+
+        {
+            let trans = ParsedString::parse("{RED}{1:STRING}{0:NUM}").unwrap();
+            let val_trans = validate_string(&config, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 0);
+        }
+        {
+            let trans = ParsedString::parse("{RED}{0:NUM}{STRING.x}").unwrap();
+            let val_trans = validate_string(&config, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 1);
+            assert_eq!(
+                val_trans[0],
+                ValidationError {
+                    severity: Severity::Warning,
+                    code: ErrorCode::MissingPositionReference,
+                    pos_begin: Some(12),
+                    pos_end: Some(22),
+                    message: String::from("Command '{STRING}' has no position reference."),
+                    suggestion: Some(String::from("Use '{1:STRING.x}'.")),
+                }
+            );
+        }
+        {
+            // Explicit indices are checked against the type of the base parameter.
+            let trans = ParsedString::parse("{RED}{1:NUM}{0:STRING}").unwrap();
+            let val_trans = validate_string(&config, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 4);
+            assert_eq!(
+                val_trans[0],
+                ValidationError {
+                    severity: Severity::Error,
+                    code: ErrorCode::ParameterMismatch,
+                    pos_begin: Some(5),
+                    pos_end: Some(12),
+                    message: String::from("Expected '{1:STRING}', found '{NUM}'."),
+                    suggestion: None,
+                }
+            );
+            assert_eq!(
+                val_trans[1],
+                ValidationError {
+                    severity: Severity::Error,
+                    code: ErrorCode::ParameterMismatch,
+                    pos_begin: Some(12),
+                    pos_end: Some(22),
+                    message: String::from("Expected '{0:NUM}', found '{STRING}'."),
+                    suggestion: None,
+                }
+            );
+            assert!(val_trans[2..]
+                .iter()
+                .all(|e| e.code == ErrorCode::MissingParameter));
+        }
+    }
+
+    #[test]
+    fn test_validate_pair() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 3,
+            ..Default::default()
+        };
+        let res = validate_pair(&config, "{NUM} train{P \"\" s}", "default", "{NUM} pociąg");
+        assert_eq!(res.base.errors, vec![]);
+        assert_eq!(
+            res.base.normalized,
+            Some(String::from("{0:NUM} train{P 0 \"\" s}"))
+        );
+        assert_eq!(res.translation.errors, vec![]);
+
+        let res = validate_pair(&config, "{NUM} train{P \"\" s}", "default", "{NUM} {P a b}");
+        assert_eq!(res.base.errors, vec![]);
+        assert_eq!(res.translation.normalized, None);
+        assert_eq!(
+            res.translation
+                .errors
+                .iter()
+                .map(|e| e.code)
+                .collect::<Vec<_>>(),
+            vec![ErrorCode::PluralCountMismatch]
+        );
+
+        let res = validate_pair(&config, "{NUM} train{FOO}", "default", "{NUM} pociąg");
+        assert_eq!(res.base.errors.len(), 1);
+        assert_eq!(res.base.errors[0].code, ErrorCode::UnknownCommand);
+        assert_eq!(
+            res.translation.errors,
+            vec![ValidationError {
+                severity: Severity::Error,
+                code: ErrorCode::InvalidBase,
+                pos_begin: None,
+                pos_end: None,
+                message: String::from(
+                    "The translation was not validated, because the base string is invalid."
+                ),
+                suggestion: Some(String::from("Fix the errors of the base string first.")),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_validate_translation_any() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            ..Default::default()
+        };
+        let bases = vec![
+            String::from("{NUM} item"),
+            String::from("{NUM} {STRING} items"),
+        ];
+
+        let res = validate_translation_any(&config, &bases, "default", "{NUM} {STRING} Dinge");
+        assert_eq!(res.base_index, Some(1));
+        assert_eq!(res.result.errors, vec![]);
+        assert_eq!(
+            res.result.normalized,
+            Some(String::from("{0:NUM} {1:STRING} Dinge"))
+        );
+
+        let res = validate_translation_any(&config, &bases, "default", "{NUM} Ding");
+        assert_eq!(res.base_index, Some(0));
+        assert_eq!(res.result.errors, vec![]);
+
+        let res = validate_translation_any(&config, &bases, "default", "Ding");
+        assert_eq!(res.base_index, Some(0));
+        assert_eq!(res.result.errors.len(), 1);
+
+        let res = validate_translation_any(&config, &[], "default", "Ding");
+        assert_eq!(res.base_index, None);
+        assert_eq!(res.result.errors.len(), 1);
+
+        // Warnings and notices do not prevent a match.
+        let config = LanguageConfig {
+            report_normalization: true,
+            ..config
+        };
+        let bases = vec![String::from("{RED}{NUM} item"), String::from("{NUM} item")];
+        let res = validate_translation_any(&config, &bases, "default", "{NUM} Ding");
+        assert_eq!(res.base_index, Some(0));
+        let severities: Vec<_> = res.result.errors.iter().map(|e| e.severity).collect();
+        assert_eq!(severities, vec![Severity::Info, Severity::Warning]);
+    }
+
+    #[test]
+    fn test_validate_unknown_command_suggestion() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            ..Default::default()
+        };
+        assert_eq!(suggest_command(&config, "REED"), Some(String::from("RED")));
+        assert_eq!(suggest_command(&config, "FOOBAR"), None);
+
+        let base = ParsedString::parse("{REED}warning").unwrap();
+        assert_eq!(
+            validate_string(&config, &base, None),
+            vec![ValidationError {
+                severity: Severity::Error,
+                code: ErrorCode::UnknownCommand,
+                pos_begin: Some(0),
+                pos_end: Some(6),
+                message: String::from("Unknown string command '{REED}'."),
+                suggestion: Some(String::from("Did you mean '{RED}'?")),
+            }]
+        );
+
+        let base = ParsedString::parse("{FOOBAR}warning").unwrap();
+        assert_eq!(
+            validate_string(&config, &base, None),
+            vec![ValidationError {
+                severity: Severity::Error,
+                code: ErrorCode::UnknownCommand,
+                pos_begin: Some(0),
+                pos_end: Some(8),
+                message: String::from("Unknown string command '{FOOBAR}'."),
+                suggestion: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_validate_parameter_limit() {
+        let config = LanguageConfig {
+            dialect: Dialect::NEWGRF,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            ..Default::default()
+        };
+        let base = ParsedString::parse("{15:STRING}").unwrap();
+        assert_eq!(validate_string(&config, &base, None), vec![]);
+
+        let base = ParsedString::parse("{COMMA} {99:STRING}").unwrap();
+        assert_eq!(
+            validate_string(&config, &base, None),
+            vec![ValidationError {
+                severity: Severity::Error,
+                code: ErrorCode::ParameterLimit,
+                pos_begin: Some(8),
+                pos_end: Some(19),
+                message: String::from(
+                    "Position 99 is out of range, newgrf strings have at most 16 parameters."
+                ),
+                suggestion: Some(String::from("Use positions 0 to 15.")),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_validate_ellipsis() {
+        let mut config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            ..Default::default()
+        };
+        let base = ParsedString::parse("{BLACK}Load game...").unwrap();
+        let trans = ParsedString::parse("{BLACK}Spiel laden").unwrap();
+        let val_trans = validate_string(&config, &trans, Some(&base));
+        assert_eq!(val_trans.len(), 0);
+
+        config.compare_ellipsis = true;
+        let val_trans = validate_string(&config, &trans, Some(&base));
+        assert_eq!(
+            val_trans,
+            vec![ValidationError {
+                severity: Severity::Warning,
+                code: ErrorCode::EllipsisMismatch,
+                pos_begin: Some(7),
+                pos_end: Some(18),
+                message: String::from(
+                    "The base ends with an ellipsis, but the translation does not."
+                ),
+                suggestion: Some(String::from("Add '…' to the end of the translation.")),
+            }]
+        );
+
+        let trans = ParsedString::parse("{BLACK}Spiel laden… ").unwrap();
+        let val_trans = validate_string(&config, &trans, Some(&base));
+        assert_eq!(val_trans.len(), 0);
+
+        let trans = ParsedString::parse("{BLACK}Spiel laden...").unwrap();
+        let val_trans = validate_string(&config, &trans, Some(&base));
+        assert_eq!(val_trans.len(), 0);
+
+        let base = ParsedString::parse("{BLACK}Load game").unwrap();
+        let val_trans = validate_string(&config, &trans, Some(&base));
+        assert_eq!(
+            val_trans,
+            vec![ValidationError {
+                severity: Severity::Warning,
+                code: ErrorCode::EllipsisMismatch,
+                pos_begin: Some(18),
+                pos_end: Some(21),
+                message: String::from(
+                    "The translation ends with an ellipsis, but the base does not."
+                ),
+                suggestion: Some(String::from("Remove the ellipsis.")),
+            }]
+        );
+
+        // Braces in text are positioned as written.
+        let trans = ParsedString::parse("{BLACK}Spiel {{}laden}...").unwrap();
+        let val_trans = validate_string(&config, &trans, Some(&base));
+        assert_eq!(val_trans.len(), 1);
+        assert_eq!(
+            (val_trans[0].pos_begin, val_trans[0].pos_end),
+            (Some(22), Some(25))
+        );
+    }
+
+    #[test]
+    fn test_validate_leading_newline() {
+        let mut config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            ..Default::default()
+        };
+        let base = ParsedString::parse("Line 1{}Line 2").unwrap();
+        let trans = ParsedString::parse("{}Zeile 1{}Zeile 2").unwrap();
+        let val_trans = validate_string(&config, &trans, Some(&base));
+        assert_eq!(val_trans.len(), 0);
+
+        config.warn_leading_newline = true;
+        let val_trans = validate_string(&config, &trans, Some(&base));
+        assert_eq!(
+            val_trans,
+            vec![ValidationError {
+                severity: Severity::Warning,
+                code: ErrorCode::LeadingLineBreak,
+                pos_begin: Some(0),
+                pos_end: Some(2),
+                message: String::from(
+                    "The translation starts with a line break, but the base does not."
+                ),
+                suggestion: Some(String::from("Remove the leading '{}'.")),
+            }]
+        );
+
+        let base = ParsedString::parse("{}Line 1{}Line 2").unwrap();
+        let val_trans = validate_string(&config, &trans, Some(&base));
+        assert_eq!(val_trans.len(), 0);
+    }
+
+    #[test]
+    fn test_validate_base_hygiene() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![String::from("x")],
+            genders: vec![String::from("a"), String::from("b")],
+            plural_count: 2,
+            base_hygiene: true,
+            ..Default::default()
+        };
+
+        {
+            let base = ParsedString::parse("{NUM}{P a b}{STRING}").unwrap();
+            let val_base = validate_string(&config, &base, None);
+            assert_eq!(val_base.len(), 0);
+        }
+        {
+            let base = ParsedString::parse("{G=a}{NUM}{STRING.x}").unwrap();
+            let val_base = validate_string(&config, &base, None);
+            assert_eq!(val_base.len(), 2);
+            // Gender definitions are an error in base strings, even without 'base_hygiene'.
+            assert_eq!(
+                val_base[0],
+                ValidationError {
+                    severity: Severity::Error,
+                    code: ErrorCode::TranslationOnly,
+                    pos_begin: Some(0),
+                    pos_end: Some(5),
+                    message: String::from(
+                        "Gender definitions belong in translations, the base language has no genders."
+                    ),
+                    suggestion: Some(String::from("Remove '{G=...}'.")),
+                }
+            );
+            assert_eq!(
+                val_base[1],
+                ValidationError {
+                    severity: Severity::Warning,
+                    code: ErrorCode::TranslationOnly,
+                    pos_begin: Some(10),
+                    pos_end: Some(20),
+                    message: String::from(
+                        "Case selections belong in translations, the base language has no cases."
+                    ),
+                    suggestion: Some(String::from("Remove '.x'.")),
+                }
+            );
+
+            let trans = ParsedString::parse("{G=a}{NUM}{STRING.x}").unwrap();
+            let val_trans = validate_string(&config, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 0);
+        }
+    }
+
+    #[test]
+    fn test_validate_base_gender_definition() {
+        for genders in [vec![], vec![String::from("a"), String::from("b")]] {
+            let config = LanguageConfig {
+                dialect: Dialect::OPENTTD,
+                cases: vec![],
+                genders,
+                plural_count: 2,
+                ..Default::default()
+            };
+            let result = validate_base(&config, &String::from("{G=a}{NUM}"));
+            assert_eq!(
+                result.errors,
+                vec![ValidationError {
+                    severity: Severity::Error,
+                    code: ErrorCode::TranslationOnly,
+                    pos_begin: Some(0),
+                    pos_end: Some(5),
+                    message: String::from(
+                        "Gender definitions belong in translations, the base language has no genders."
+                    ),
+                    suggestion: Some(String::from("Remove '{G=...}'.")),
+                }]
+            );
+            assert_eq!(result.normalized, None);
+        }
+
+        // Gender choices are still handled as before.
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![String::from("a"), String::from("b")],
+            plural_count: 2,
+            ..Default::default()
+        };
+        let result = validate_base(&config, &String::from("{G a b}{STRING}"));
+        assert_eq!(result.errors, vec![]);
+        assert_eq!(result.normalized, Some(String::from("{G 0 a b}{0:STRING}")));
+    }
+
+    #[test]
+    fn test_validate_reference_missing() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            ..Default::default()
+        };
+        let base = ParsedString::parse("{NUM}{P a b}{COMMA}").unwrap();
+        let val_base = validate_string(&config, &base, None);
+        assert_eq!(val_base.len(), 0);
+
+        {
+            let trans = ParsedString::parse("{1:COMMA}{P 0 a b}").unwrap();
+            let val_trans = validate_string(&config, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 2);
+            assert_eq!(
+                val_trans[0],
+                ValidationError {
+                    severity: Severity::Warning,
+                    code: ErrorCode::MissingChoiceParameter,
+                    pos_begin: Some(9),
+                    pos_end: Some(18),
+                    message: String::from(
+                        "'{P}' references position '0', but the translation does not contain '{0:NUM}'."
+                    ),
+                    suggestion: Some(String::from("Add '{0:NUM}'.")),
+                }
+            );
+            assert_eq!(
+                val_trans[1],
+                ValidationError {
+                    severity: Severity::Error,
+                    code: ErrorCode::MissingParameter,
+                    pos_begin: None,
+                    pos_end: None,
+                    message: String::from("String command '{0:NUM}' is missing."),
+                    suggestion: None,
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_non_count_plural() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            warn_non_count_plural: true,
+            ..Default::default()
+        };
+        let base = ParsedString::parse("{NUM}{P a b}{CURRENCY_LONG}{P c d}").unwrap();
+
+        let val_base = validate_string(&config, &base, None);
+        assert_eq!(val_base.len(), 1);
+        assert_eq!(
+            val_base[0],
+            ValidationError {
+                severity: Severity::Warning,
+                code: ErrorCode::NonCountPlural,
+                pos_begin: Some(27),
+                pos_end: Some(34),
+                message: String::from(
+                    "'{P}' references position '1:0', but '{1:CURRENCY_LONG}' is not a count."
+                ),
+                suggestion: Some(String::from(
+                    "Plural selection on this parameter is probably wrong."
+                )),
+            }
+        );
+
+        let config = LanguageConfig {
+            warn_non_count_plural: false,
+            ..config
+        };
+        let val_base = validate_string(&config, &base, None);
+        assert_eq!(val_base.len(), 0);
+    }
+
+    #[test]
+    fn test_validate_case_commands() {
+        for info in COMMANDS {
+            assert!(!(info.allow_case && info.count_like), "{}", info.name);
+        }
+
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![String::from("gen")],
+            genders: vec![],
+            plural_count: 2,
+            ..Default::default()
+        };
+        let base = ParsedString::parse("{COMMA} {STRING}").unwrap();
+
+        {
+            let trans = ParsedString::parse("{COMMA} {STRING.gen}").unwrap();
+            let val_trans = validate_string(&config, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 0);
+        }
+        {
+            let trans = ParsedString::parse("{COMMA.gen} {STRING}").unwrap();
+            let val_trans = validate_string(&config, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 1);
+            assert_eq!(
+                val_trans[0],
+                ValidationError {
+                    severity: Severity::Error,
+                    code: ErrorCode::CaseNotAllowed,
+                    pos_begin: Some(0),
+                    pos_end: Some(11),
+                    message: String::from("No case selection allowed for '{COMMA}'."),
+                    suggestion: Some(String::from("Remove '.gen'.")),
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_case_insensitive_cases() {
+        let mut config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![String::from("Nom"), String::from("gen")],
+            genders: vec![String::from("m"), String::from("f")],
+            plural_count: 2,
+            ..Default::default()
+        };
+        let base = String::from("{STRING} of {STRING}");
+        let trans = "{G=M}{STRING.NOM} von {STRING.Gen}";
+        let val_trans = validate_translation(&config, &base, "default", trans);
+        let codes: Vec<ErrorCode> = val_trans.errors.iter().map(|e| e.code).collect();
+        assert_eq!(
+            codes,
+            vec![
+                ErrorCode::UnknownGender,
+                ErrorCode::UnknownCase,
+                ErrorCode::UnknownCase
+            ]
+        );
+
+        config.case_insensitive_cases = true;
+        let val_trans = validate_translation(&config, &base, "default", trans);
+        assert_eq!(val_trans.errors, vec![]);
+        assert_eq!(
+            val_trans.normalized,
+            Some(String::from("{G=m}{0:STRING.Nom} von {1:STRING.gen}"))
+        );
+
+        let val_trans = validate_translation(&config, &base, "nom", "{STRING} von {STRING}");
+        assert_eq!(val_trans.errors, vec![]);
+    }
+
+    #[test]
+    fn test_validate_index_and_case() {
+        // The position reference is accepted, only the case is rejected.
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![String::from("gen")],
+            genders: vec![],
+            plural_count: 2,
+            ..Default::default()
+        };
+        let result = validate_translation(
+            &config,
+            &String::from("{NUM}"),
+            &String::from("default"),
+            &String::from("{0:NUM.gen}"),
+        );
+        assert_eq!(
+            result.errors,
+            vec![ValidationError {
+                severity: Severity::Error,
+                code: ErrorCode::CaseNotAllowed,
+                pos_begin: Some(0),
+                pos_end: Some(11),
+                message: String::from("No case selection allowed for '{NUM}'."),
+                suggestion: Some(String::from("Remove '.gen'.")),
+            }]
+        );
+        assert_eq!(result.normalized, None);
+    }
+
+    #[test]
+    fn test_validate_hint_case_translations() {
+        let mut config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![String::from("gen"), String::from("dat")],
+            genders: vec![],
+            plural_count: 2,
+            ..Default::default()
+        };
+        let base = String::from("{STRING.gen} of {STRING.dat} and {STRING.gen}");
+        let translation = String::from("{STRING} von {STRING} und {STRING}");
+        let default_case = String::from("default");
+        let result = validate_translation(&config, &base, &default_case, &translation);
+        assert_eq!(result.errors.len(), 0);
+
+        config.hint_case_translations = true;
+        let result = validate_translation(&config, &base, &default_case, &translation);
+        assert_eq!(
+            result.errors,
+            vec![ValidationError {
+                severity: Severity::Info,
+                code: ErrorCode::CaseTranslationsHint,
+                pos_begin: None,
+                pos_end: None,
+                message: String::from(
+                    "The base references the cases 'dat', 'gen', case-specific translations may be required."
+                ),
+                suggestion: Some(String::from("Also translate the cases 'dat', 'gen'.")),
+            }]
+        );
+        assert!(result.normalized.is_some());
+
+        let result = validate_translation(&config, &base, &String::from("gen"), &translation);
+        assert_eq!(result.errors.len(), 0);
+
+        let result = validate_translation(
+            &config,
+            &String::from("{STRING} of {STRING}"),
+            &default_case,
+            &String::from("{STRING} von {STRING}"),
+        );
+        assert_eq!(result.errors.len(), 0);
+    }
+
+    #[test]
+    fn test_validate_nochoices() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 1,
+            ..Default::default()
+        };
+        let base = ParsedString::parse("{NUM}{STRING3}").unwrap();
+        let val_base = validate_string(&config, &base, None);
+        assert_eq!(val_base.len(), 0);
+
+        {
+            let trans = ParsedString::parse("{G=a}{NUM}{P a}{G a}{STRING}").unwrap();
+            let val_trans = validate_string(&config, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 3);
+            assert_eq!(
+                val_trans[0],
+                ValidationError {
+                    severity: Severity::Error,
+                    code: ErrorCode::GendersNotSupported,
+                    pos_begin: Some(0),
+                    pos_end: Some(5),
+                    message: String::from("No gender definitions allowed."),
+                    suggestion: Some(String::from("Remove '{G=...}'.")),
+                }
+            );
+            assert_eq!(
+                val_trans[1],
+                ValidationError {
+                    severity: Severity::Error,
+                    code: ErrorCode::PluralsNotSupported,
+                    pos_begin: Some(10),
+                    pos_end: Some(15),
+                    message: String::from(
+                        "No plural choices allowed, this language has only a single plural form."
+                    ),
+                    suggestion: Some(String::from(
+                        "Replace '{P ...}' with the text of the single plural form."
+                    )),
+                }
+            );
+            assert_eq!(
+                val_trans[2],
+                ValidationError {
+                    severity: Severity::Error,
+                    code: ErrorCode::GendersNotSupported,
+                    pos_begin: Some(15),
+                    pos_end: Some(20),
+                    message: String::from("No gender choices allowed."),
+                    suggestion: Some(String::from("Remove '{G ...}'.")),
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_noplurals() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 0,
+            ..Default::default()
+        };
+        let base = ParsedString::parse("{NUM} {P a b}").unwrap();
+        let val_base = validate_string(&config, &base, None);
+        assert_eq!(val_base.len(), 1);
+        assert_eq!(
+            val_base[0],
+            ValidationError {
+                severity: Severity::Error,
+                code: ErrorCode::PluralsNotSupported,
+                pos_begin: Some(6),
+                pos_end: Some(13),
+                message: String::from("No plural choices allowed."),
+                suggestion: Some(String::from("Remove '{P ...}'.")),
+            }
+        );
+
+        let config = LanguageConfig {
+            plural_count: 1,
+            ..config
+        };
+        let val_base = validate_string(&config, &base, None);
+        assert_eq!(val_base.len(), 1);
+        assert_eq!(val_base[0].message, "No plural choices allowed.");
+
+        let trans = ParsedString::parse("{NUM} {P a}").unwrap();
+        let val_trans = validate_string(&config, &trans, Some(&base));
+        assert_eq!(val_trans.len(), 1);
+        assert_eq!(
+            val_trans[0],
+            ValidationError {
+                severity: Severity::Error,
+                code: ErrorCode::PluralsNotSupported,
+                pos_begin: Some(6),
+                pos_end: Some(11),
+                message: String::from(
+                    "No plural choices allowed, this language has only a single plural form."
+                ),
+                suggestion: Some(String::from(
+                    "Replace '{P ...}' with the text of the single plural form."
+                )),
+            }
+        );
+    }
+
+    #[test]
+    fn test_validate_gschoices() {
+        let config = LanguageConfig {
+            dialect: Dialect::GAMESCRIPT,
+            cases: vec![String::from("x"), String::from("y")],
+            genders: vec![String::from("a"), String::from("b")],
+            plural_count: 2,
+            ..Default::default()
+        };
+        let base = ParsedString::parse("{NUM}{STRING3}").unwrap();
+        let val_base = validate_string(&config, &base, None);
+        assert_eq!(val_base.len(), 0);
+
+        {
+            let trans = ParsedString::parse("{G=a}{NUM}{P a b}{G a b}{STRING.x}").unwrap();
+            let val_trans = validate_string(&config, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 3);
+            assert_eq!(
+                val_trans[0],
+                ValidationError {
+                    severity: Severity::Error,
+                    code: ErrorCode::GendersNotSupported,
+                    pos_begin: Some(0),
+                    pos_end: Some(5),
+                    message: String::from("No gender definitions allowed."),
+                    suggestion: Some(String::from("Remove '{G=...}'.")),
+                }
+            );
+            assert_eq!(
+                val_trans[1],
+                ValidationError {
+                    severity: Severity::Error,
+                    code: ErrorCode::GendersNotSupported,
+                    pos_begin: Some(17),
+                    pos_end: Some(24),
+                    message: String::from("No gender choices allowed."),
+                    suggestion: Some(String::from("Remove '{G ...}'.")),
+                }
+            );
+            assert_eq!(
+                val_trans[2],
+                ValidationError {
+                    severity: Severity::Error,
+                    code: ErrorCode::CasesNotSupported,
+                    pos_begin: Some(24),
+                    pos_end: Some(34),
+                    message: String::from("No case selections allowed."),
+                    suggestion: Some(String::from("Remove '.x'.")),
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_choices() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![String::from("x"), String::from("y")],
+            genders: vec![String::from("a"), String::from("b")],
+            plural_count: 2,
+            ..Default::default()
+        };
+        let base = ParsedString::parse("{NUM}{STRING3}").unwrap();
+        let val_base = validate_string(&config, &base, None);
+        assert_eq!(val_base.len(), 0);
+
+        {
+            let trans = ParsedString::parse("{G=a}{NUM}{P a b}{G a b}{STRING.x}").unwrap();
+            let val_trans = validate_string(&config, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 0);
+        }
+        {
+            let trans = ParsedString::parse("{G=c}{NUM}{P a b c}{G a b c}{STRING.z}").unwrap();
+            let val_trans = validate_string(&config, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 4);
+            assert_eq!(
+                val_trans[0],
+                ValidationError {
+                    severity: Severity::Error,
+                    code: ErrorCode::UnknownGender,
+                    pos_begin: Some(0),
+                    pos_end: Some(5),
+                    message: String::from("Unknown gender 'c'."),
+                    suggestion: Some(String::from("Known genders are: 'a', 'b'")),
+                }
+            );
+            assert_eq!(
+                val_trans[1],
+                ValidationError {
+                    severity: Severity::Error,
+                    code: ErrorCode::PluralCountMismatch,
+                    pos_begin: Some(17),
+                    pos_end: Some(18),
+                    message: String::from("Expected 2 plural choices, found 3."),
+                    suggestion: None,
+                }
+            );
+            assert_eq!(
+                val_trans[2],
+                ValidationError {
+                    severity: Severity::Error,
+                    code: ErrorCode::GenderCountMismatch,
+                    pos_begin: Some(26),
+                    pos_end: Some(27),
+                    message: String::from("Expected 2 gender choices, found 3."),
+                    suggestion: Some(String::from(
+                        "Use one choice per gender in this order: 'a', 'b'."
+                    )),
+                }
+            );
+            assert_eq!(
+                val_trans[3],
+                ValidationError {
+                    severity: Severity::Error,
+                    code: ErrorCode::UnknownCase,
+                    pos_begin: Some(28),
+                    pos_end: Some(38),
+                    message: String::from("Unknown case 'z'."),
+                    suggestion: Some(String::from("Known cases are: 'x', 'y'")),
+                }
+            );
+        }
+        {
+            let trans = ParsedString::parse("{NUM}{P a}{STRING}").unwrap();
+            let val_trans = validate_string(&config, &trans, Some(&base));
+            assert_eq!(
+                val_trans,
+                vec![ValidationError {
+                    severity: Severity::Error,
+                    code: ErrorCode::PluralCountMismatch,
+                    pos_begin: Some(5),
+                    pos_end: Some(10),
+                    message: String::from("Expected 2 plural choices, found 1."),
+                    suggestion: None,
+                }]
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_choice_order() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![String::from("m"), String::from("f")],
+            plural_count: 2,
+            ..Default::default()
+        };
+        let base = ParsedString::parse("{STRING} {STRING} {NUM} {P item items}").unwrap();
+
+        // '{G}' refers to the following, '{P}' to the preceding parameter.
+        let trans = ParsedString::parse("{G a b}{0:STRING} {1:STRING} {NUM} {P x y}").unwrap();
+        assert_eq!(validate_string(&config, &trans, Some(&base)), vec![]);
+
+        // After '{0:STRING}' the next position is 1, but '{1:STRING}' came first.
+        let trans = ParsedString::parse("{1:STRING} {0:STRING}{G a b} {2:NUM} {P x y}").unwrap();
+        assert_eq!(
+            validate_string(&config, &trans, Some(&base)),
+            vec![ValidationError {
+                severity: Severity::Warning,
+                code: ErrorCode::MisplacedChoiceReference,
+                pos_begin: Some(21),
+                pos_end: Some(28),
+                message: String::from(
+                    "'{G ...}' refers to position 1, but the parameter does not follow it."
+                ),
+                suggestion: Some(String::from(
+                    "Add a position reference, like '{G 1 ...}', or move the choice."
+                )),
+            }]
+        );
+
+        // Explicit references are not checked.
+        let trans = ParsedString::parse("{1:STRING} {0:STRING}{G 1 a b} {2:NUM} {P x y}").unwrap();
+        assert_eq!(validate_string(&config, &trans, Some(&base)), vec![]);
+    }
+
+    #[test]
+    fn test_validate_dropped_gender_choice() {
+        let mut config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![String::from("m"), String::from("f")],
+            plural_count: 2,
+            ..Default::default()
+        };
+        let base = ParsedString::parse("{STRING} {G is are} {STRING} here").unwrap();
+        let trans = ParsedString::parse("{STRING} ist {STRING} hier").unwrap();
+        assert_eq!(validate_string(&config, &trans, Some(&base)), vec![]);
+
+        config.warn_dropped_gender_choice = true;
+        assert_eq!(
+            validate_string(&config, &trans, Some(&base)),
+            vec![ValidationError {
+                severity: Severity::Warning,
+                code: ErrorCode::DroppedGenderChoice,
+                pos_begin: None,
+                pos_end: None,
+                message: String::from(
+                    "The base has a gender choice for position 1, but the translation has none."
+                ),
+                suggestion: Some(String::from(
+                    "Add '{G 1 ...}', if the translation depends on the gender."
+                )),
+            }]
+        );
+
+        let trans = ParsedString::parse("{STRING} {G ist ist} {STRING} hier").unwrap();
+        assert_eq!(validate_string(&config, &trans, Some(&base)), vec![]);
+    }
+
+    #[test]
+    fn test_validate_max_length() {
+        let mut config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            max_length: Some(10),
+            ..Default::default()
+        };
+        let base = ParsedString::parse("{RED}{NUM} train{P \"\" s} in the depot").unwrap();
+        assert_eq!(validate_string(&config, &base, None), vec![]);
+
+        let trans = ParsedString::parse("{RED}{NUM} Zug{P \"\" e}").unwrap();
+        assert_eq!(validate_string(&config, &trans, Some(&base)), vec![]);
+        // exactly at the limit, the line break counts as one character
+        let trans = ParsedString::parse("{RED}{NUM} Züge im{}D").unwrap();
+        assert_eq!(validate_string(&config, &trans, Some(&base)), vec![]);
+        let trans = ParsedString::parse("{RED}{NUM} Züge im Depot").unwrap();
+        assert_eq!(
+            validate_string(&config, &trans, Some(&base)),
+            vec![ValidationError {
+                severity: Severity::Warning,
+                code: ErrorCode::TranslationTooLong,
+                pos_begin: None,
+                pos_end: None,
+                message: String::from(
+                    "The translation is 14 characters long, at most 10 are allowed."
+                ),
+                suggestion: Some(String::from("Shorten the translation.")),
+            }]
+        );
+
+        config.max_length = None;
+        assert_eq!(validate_string(&config, &trans, Some(&base)), vec![]);
+    }
+
+    #[test]
+    fn test_validate_gender_count_hint() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![String::from("m"), String::from("f"), String::from("n")],
+            plural_count: 2,
+            ..Default::default()
+        };
+        let base = ParsedString::parse("{STRING} is here").unwrap();
+        let trans = ParsedString::parse("{G Er Sie} {STRING} ist da").unwrap();
+        assert_eq!(
+            validate_string(&config, &trans, Some(&base)),
+            vec![ValidationError {
+                severity: Severity::Error,
+                code: ErrorCode::GenderCountMismatch,
+                pos_begin: Some(0),
+                pos_end: Some(10),
+                message: String::from("Expected 3 gender choices, found 2."),
+                suggestion: Some(String::from(
+                    "Use one choice per gender in this order: 'm', 'f', 'n'."
+                )),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_canonical_command_order() {
+        let mut config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            ..Default::default()
+        };
+        let base = String::from("{TINY_FONT}{RED}{NUM} {COMMA} x{BLUE}");
+        let res = validate_base(&config, &base);
+        assert_eq!(res.errors, vec![]);
+        assert_eq!(
+            res.normalized,
+            Some(String::from("{TINY_FONT}{RED}{0:NUM} {1:COMMA} x{BLUE}"))
+        );
+
+        config.canonical_command_order = true;
+        let res = validate_base(&config, &base);
+        assert_eq!(res.errors, vec![]);
+        assert_eq!(
+            res.normalized,
+            Some(String::from("{RED}{TINY_FONT}{0:NUM} {1:COMMA} x{BLUE}"))
+        );
+
+        // positional commands and text end the sorted part
+        let res = validate_base(&config, &String::from("{TINY_FONT}{NUM}{RED}{COMMA}"));
+        assert_eq!(
+            res.normalized,
+            Some(String::from("{TINY_FONT}{0:NUM}{RED}{1:COMMA}"))
+        );
+        let res = validate_base(&config, &String::from("{TINY_FONT} {RED}"));
+        assert_eq!(res.normalized, Some(String::from("{TINY_FONT} {RED}")));
+
+        // the last colour wins, so multiple colours keep their order
+        let res = validate_base(&config, &String::from("{TINY_FONT}{RED}{BLUE}x"));
+        assert_eq!(
+            res.normalized,
+            Some(String::from("{TINY_FONT}{RED}{BLUE}x"))
+        );
+
+        // custom font commands are sorted like built-in ones, the name does not matter
+        config.custom_commands = vec![CustomCommand {
+            name: String::from("CONDENSED"),
+            occurence: Occurence::ANY,
+            allow_case: false,
+            count_like: false,
+            sets_colour: false,
+            sets_font: true,
+            is_space_like: false,
+            requires_following: false,
+            parameters: vec![],
+        }];
+        let res = validate_base(&config, &String::from("{RED}{CONDENSED}x"));
+        assert_eq!(res.normalized, Some(String::from("{CONDENSED}{RED}x")));
+    }
+
+    #[test]
+    fn test_validate_empty_choice() {
+        let mut config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 3,
+            ..Default::default()
+        };
+        let trans = ParsedString::parse(r##"{NUM} Ding{P "" e ""}"##).unwrap();
+        assert_eq!(validate_string(&config, &trans, None), vec![]);
+
+        config.warn_empty_choice = true;
+        let trans = ParsedString::parse(r##"{NUM} Ding{P "" e en}"##).unwrap();
+        assert_eq!(validate_string(&config, &trans, None), vec![]);
+
+        let trans = ParsedString::parse(r##"{NUM} {P Ding "" Dinge}"##).unwrap();
+        assert_eq!(
+            validate_string(&config, &trans, None),
+            vec![ValidationError {
+                severity: Severity::Warning,
+                code: ErrorCode::EmptyChoice,
+                pos_begin: Some(14),
+                pos_end: Some(16),
+                message: String::from("Choice 2 of '{P ...}' is empty."),
+                suggestion: Some(String::from("Check whether a word is missing.")),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_validate_choice_count_vs_base() {
+        let base_config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            ..Default::default()
+        };
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 3,
+            compare_choice_counts: true,
+            ..Default::default()
+        };
+        let base = ParsedString::parse("{NUM}{P a b}{STRING}").unwrap();
+        let val_base = validate_string(&base_config, &base, None);
+        assert_eq!(val_base.len(), 0);
+
+        {
+            let trans = ParsedString::parse("{NUM}{P a b c}{STRING}").unwrap();
+            let val_trans = validate_string(&config, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 1);
+            assert_eq!(
+                val_trans[0],
+                ValidationError {
+                    severity: Severity::Warning,
+                    code: ErrorCode::ChoiceCountDiffers,
+                    pos_begin: Some(5),
+                    pos_end: Some(14),
+                    message: String::from(
+                        "'{P}' has 3 choices, but the base has 2 choices for position 0."
+                    ),
+                    suggestion: Some(String::from(
+                        "Check the language configuration of base and translation."
+                    )),
+                }
+            );
+        }
+        {
+            let trans = ParsedString::parse("{NUM}{STRING}{P 0 a b c}").unwrap();
+            let val_trans = validate_string(&config, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 1);
+            assert_eq!(val_trans[0].pos_begin, Some(13));
+        }
+        {
+            let trans = ParsedString::parse("{NUM}{STRING}{P a b c}").unwrap();
+            let val_trans = validate_string(&config, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 1);
+            assert_eq!(
+                val_trans[0].message,
+                "'{P}' refers to the preceding parameter '{1:STRING}', which is not a number."
+            );
+            assert_eq!(
+                val_trans[0].suggestion,
+                Some(String::from(
+                    "Add a position reference to the number, like '{P 0 ...}'."
+                ))
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_plural_count_from_translation() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 3,
+            ..Default::default()
+        };
+        let base = ParsedString::parse("{NUM}{P a b}").unwrap();
+        {
+            let trans = ParsedString::parse("{NUM}{P a b c}").unwrap();
+            let val_trans = validate_string(&config, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 0);
+        }
+        {
+            let trans = ParsedString::parse("{NUM}{P a b}").unwrap();
+            let val_trans = validate_string(&config, &trans, Some(&base));
+            assert_eq!(
+                val_trans,
+                vec![ValidationError {
+                    severity: Severity::Error,
+                    code: ErrorCode::PluralCountMismatch,
+                    pos_begin: Some(5),
+                    pos_end: Some(12),
+                    message: String::from("Expected 3 plural choices, found 2."),
+                    suggestion: None,
+                }]
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_forbid_reorder() {
+        let mut config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            ..Default::default()
+        };
+        let base = ParsedString::parse("{STRING} has {NUM} {STRING}").unwrap();
+        let trans = ParsedString::parse("{2:STRING}: {0:STRING} {1:NUM}").unwrap();
+        let val_trans = validate_string(&config, &trans, Some(&base));
+        assert_eq!(val_trans.len(), 0);
+
+        config.forbid_reorder = true;
+        let val_trans = validate_string(&config, &trans, Some(&base));
+        assert_eq!(
+            val_trans,
+            vec![ValidationError {
+                severity: Severity::Error,
+                code: ErrorCode::ParameterReordered,
+                pos_begin: Some(12),
+                pos_end: Some(22),
+                message: String::from(
+                    "Parameter '{0:STRING}' is out of order, reordering parameters is not allowed."
+                ),
+                suggestion: Some(String::from(
+                    "Use the parameters in the same order as the base."
+                )),
+            }]
+        );
+
+        let trans = ParsedString::parse("{STRING} hat {NUM} {STRING} und {0:STRING}").unwrap();
+        let val_trans = validate_string(&config, &trans, Some(&base));
+        assert_eq!(val_trans.len(), 0);
+    }
+
+    #[test]
+    fn test_validate_choice_newline() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            ..Default::default()
+        };
+        let base = ParsedString::parse("{NUM}").unwrap();
+
+        {
+            let trans = ParsedString::parse("{NUM}{P \"a b\" c}").unwrap();
+            let val_trans = validate_string(&config, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 0);
+        }
+        {
+            let trans = ParsedString::parse("{NUM}{P a \"b\nc\"}").unwrap();
+            let val_trans = validate_string(&config, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 1);
+            assert_eq!(
+                val_trans[0],
+                ValidationError {
+                    severity: Severity::Warning,
+                    code: ErrorCode::LineBreakInChoice,
+                    pos_begin: Some(5),
+                    pos_end: Some(16),
+                    message: String::from(
+                        "Choice 1 of '{P}' contains a line break, which will be replaced by a space."
+                    ),
+                    suggestion: Some(String::from("Remove the line break from the choice.")),
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_command_in_choice() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            ..Default::default()
+        };
+        let base = ParsedString::parse("{NUM} {P item items}").unwrap();
+
+        // The first '}' ends the choice list, so only '{NUM' is a choice.
+        let trans = ParsedString::parse("{NUM} {P {NUM} b}").unwrap();
+        let val_trans = validate_string(&config, &trans, Some(&base));
+        assert_eq!(val_trans.len(), 2);
+        assert_eq!(val_trans[0].code, ErrorCode::PluralCountMismatch);
+        assert_eq!(
+            val_trans[1],
+            ValidationError {
+                severity: Severity::Warning,
+                code: ErrorCode::CommandInChoice,
+                pos_begin: Some(9),
+                pos_end: Some(13),
+                message: String::from(
+                    "Choice 0 of '{P}' contains '{', but string commands are not supported inside choices."
+                ),
+                suggestion: Some(String::from(
+                    "Move the string command out of the choice list."
+                )),
+            }
+        );
+
+        // Other braces are fine.
+        let trans = ParsedString::parse("{NUM} {P \"{a\" b}").unwrap();
+        let val_trans = validate_string(&config, &trans, Some(&base));
+        assert_eq!(val_trans, vec![]);
+    }
+
+    #[test]
+    fn test_validate_choice_contents() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            parse_choice_contents: true,
+            ..Default::default()
+        };
+        let base = "{NUM} {P item items} in {STRING}";
+
+        let val_trans = validate_translation(
+            &config,
+            base,
+            "default",
+            "{NUM} {P item \"{RED}items of {1:STRING}\"} in {STRING}",
+        );
+        assert_eq!(val_trans.errors, vec![]);
+        assert_eq!(
+            val_trans.normalized.unwrap(),
+            "{0:NUM} {P 0 item \"{RED}items of {1:STRING}\"} in {1:STRING}"
+        );
+
+        let val_trans = validate_translation(
+            &config,
+            base,
+            "default",
+            "{NUM} {P {NUM}x \"{2:NUM} {0:STRING} {FOO}\"} in {STRING}",
+        );
+        assert_eq!(
+            val_trans.errors[0],
+            ValidationError {
+                severity: Severity::Error,
+                code: ErrorCode::MissingPositionReference,
+                pos_begin: Some(9),
+                pos_end: Some(14),
+                message: String::from(
+                    "Command '{NUM}' inside a choice needs a position reference."
+                ),
+                suggestion: Some(String::from("Use '{0:NUM}'.")),
+            }
+        );
+        let errors: Vec<_> = val_trans.errors[1..]
+            .iter()
+            .map(|e| (e.code, e.pos_begin, e.pos_end))
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                (ErrorCode::ParameterOutOfRange, Some(17), Some(24)),
+                (ErrorCode::ParameterMismatch, Some(25), Some(35)),
+                (ErrorCode::UnknownCommand, Some(36), Some(41)),
+            ]
+        );
+
+        // Without the option, the choice list ends at the first '}'.
+        let config = LanguageConfig {
+            parse_choice_contents: false,
+            ..config
+        };
+        let val_trans = validate_translation(
+            &config,
+            base,
+            "default",
+            "{NUM} {P item \"{RED}items of {1:STRING}\"} in {STRING}",
+        );
+        assert_eq!(val_trans.errors[0].code, ErrorCode::ParseError);
+    }
+
+    #[test]
+    fn test_validate_literal_brace() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            ..Default::default()
+        };
+        let base = String::from("Type {{} and {NUM}");
+        let val_base = validate_base(&config, &base);
+        assert_eq!(val_base.errors, vec![]);
+        assert_eq!(
+            val_base.normalized,
+            Some(String::from("Type {{} and {0:NUM}"))
+        );
+
+        // '{{}' is no parameter, and may be added or removed.
+        for (trans, normalized) in [("{{}{NUM} {{}", "{{}{0:NUM} {{}"), ("{NUM}", "{0:NUM}")] {
+            let val_trans = validate_translation(&config, &base, "default", trans);
+            assert_eq!(val_trans.errors, vec![]);
+            assert_eq!(val_trans.normalized, Some(String::from(normalized)));
+        }
+    }
+
+    #[test]
+    fn test_validate_nonpositional() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 0,
+            ..Default::default()
+        };
+        let base = ParsedString::parse("{RED}{NBSP}{}{NBSP}{GREEN}{}{RED}{TRAIN}").unwrap();
+        let val_base = validate_string(&config, &base, None);
+        assert_eq!(
+            val_base,
+            vec![ValidationError {
+                severity: Severity::Warning,
+                code: ErrorCode::TrailingSpaceCommand,
+                pos_begin: Some(5),
+                pos_end: Some(11),
+                message: String::from("'{NBSP}' at the end of the line has no visible effect."),
+                suggestion: Some(String::from("Remove '{NBSP}'.")),
+            }]
+        );
+
+        {
+            let trans = ParsedString::parse("{RED}{}{GREEN}{}{RED}{TRAIN}").unwrap();
+            let val_trans = validate_string(&config, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 0);
+        }
+        {
+            let trans = ParsedString::parse("{RED}{}{GREEN}{NBSP}{RED}{NBSP}{GREEN}{}{RED}{TRAIN}")
+                .unwrap();
+            let val_trans = validate_string(&config, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 0);
+        }
+        {
+            let trans =
+                ParsedString::parse("{RED}{}{RED}{TRAIN}{BLUE}{TRAIN}{RIGHT_ARROW}{SHIP}").unwrap();
+            let val_trans = validate_string(&config, &trans, Some(&base));
+            assert_eq!(val_trans.len(), 4);
+            assert_eq!(
+                val_trans[0],
+                ValidationError {
+                    severity: Severity::Warning,
+                    code: ErrorCode::MissingCommand,
+                    pos_begin: None,
+                    pos_end: None,
+                    message: String::from("String command '{GREEN}' is missing."),
+                    suggestion: None,
+                }
+            );
+            assert_eq!(
+                val_trans[1],
+                ValidationError {
+                    severity: Severity::Warning,
+                    code: ErrorCode::OccurrenceMismatch,
+                    pos_begin: None,
+                    pos_end: None,
+                    message: String::from(
+                        "String command '{TRAIN}': expected 1 times, found 2 times."
+                    ),
+                    suggestion: None,
+                }
+            );
+            assert_eq!(
+                val_trans[2],
+                ValidationError {
+                    severity: Severity::Warning,
+                    code: ErrorCode::UnexpectedCommand,
+                    pos_begin: Some(19),
+                    pos_end: Some(25),
+                    message: String::from("String command '{BLUE}' is unexpected."),
+                    suggestion: Some(String::from("Remove this command.")),
+                }
+            );
+            assert_eq!(
+                val_trans[3],
+                ValidationError {
+                    severity: Severity::Warning,
+                    code: ErrorCode::UnexpectedCommand,
+                    pos_begin: Some(45),
+                    pos_end: Some(51),
+                    message: String::from("String command '{SHIP}' is unexpected."),
+                    suggestion: Some(String::from("Remove this command.")),
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_unexpected_command_position() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 0,
+            ..Default::default()
+        };
+        let base = ParsedString::parse("{RED}text").unwrap();
+        let trans = ParsedString::parse("{RED}te{SHIP}xt{SHIP}").unwrap();
+        let val_trans = validate_string(&config, &trans, Some(&base));
+        // Points at the first occurrence.
+        assert_eq!(
+            val_trans,
+            vec![ValidationError {
+                severity: Severity::Warning,
+                code: ErrorCode::UnexpectedCommand,
+                pos_begin: Some(7),
+                pos_end: Some(13),
+                message: String::from("String command '{SHIP}' is unexpected."),
+                suggestion: Some(String::from("Remove this command.")),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_annotate_commands() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            ..Default::default()
+        };
+        let base = "{RED}{NUM}{P a b}{STRING2}";
+
+        let val_base = annotate_commands(&config, None, base);
+        assert_eq!(val_base.errors.len(), 0);
+        assert_eq!(val_base.commands.len(), 3);
+        assert_eq!(val_base.commands[0].pos_begin, 0);
+        assert_eq!(val_base.commands[0].pos_end, 5);
+        assert_eq!(val_base.commands[0].info.unwrap().name, "RED");
+        assert_eq!(val_base.commands[1].info.unwrap().name, "NUM");
+        assert_eq!(val_base.commands[2].info.unwrap().name, "STRING2");
+        assert_eq!(val_base.commands[2].info.unwrap().parameters.len(), 3);
+
+        let trans = "{FOO}{1:STRING}{0:NUM}";
+        let val_trans = annotate_commands(&config, Some(base), trans);
+        assert_eq!(val_trans.errors.len(), 2);
+        assert_eq!(val_trans.commands.len(), 3);
+        assert!(val_trans.commands[0].info.is_none());
+        assert_eq!(val_trans.commands[1].pos_begin, 5);
+        assert_eq!(val_trans.commands[1].pos_end, 15);
+        assert_eq!(val_trans.commands[1].info.unwrap().name, "STRING2");
+        assert_eq!(val_trans.commands[2].info.unwrap().name, "NUM");
+    }
+
+    #[test]
+    fn test_would_normalize_change() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            ..Default::default()
+        };
+        assert_eq!(would_normalize_change(&config, ""), Ok(false));
+        assert_eq!(
+            would_normalize_change(&config, "{RED}{0:NUM} {P 0 a b}{}x"),
+            Ok(false)
+        );
+        assert_eq!(would_normalize_change(&config, "{NUM} {P a b}"), Ok(true));
+        assert_eq!(would_normalize_change(&config, "{0:RAW_STRING}"), Ok(true));
+        assert_eq!(would_normalize_change(&config, "foo {}bar"), Ok(true));
+        assert_eq!(would_normalize_change(&config, "{G = a}"), Ok(true));
+        assert!(would_normalize_change(&config, "{NUM").is_err());
+    }
+
+    #[test]
+    fn test_validate_case_consistency() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![String::from("gen"), String::from("dat")],
+            genders: vec![],
+            plural_count: 2,
+            ..Default::default()
+        };
+        let translations = vec![
+            (
+                String::from("default"),
+                String::from("{NUM}{NBSP}x {STRING}"),
+            ),
+            (String::from("gen"), String::from("{NUM}{NBSP}y {STRING}.")),
+            (String::from("dat"), String::from("{NUM} z{STRING}")),
+        ];
+        let errs = validate_case_consistency(&config, &translations);
+        assert_eq!(errs.len(), 2);
+        assert_eq!(
+            errs[0],
+            ValidationError {
+                severity: Severity::Warning,
+                code: ErrorCode::CaseInconsistency,
+                pos_begin: None,
+                pos_end: None,
+                message: String::from(
+                    "Spacing or markup around position 0 differs between case 'default' and case 'dat'."
+                ),
+                suggestion: Some(String::from("Use the same spacing as case 'default'.")),
+            }
+        );
+        assert_eq!(
+            errs[1].message,
+            "Spacing or markup around position 1 differs between case 'default' and case 'dat'."
+        );
+
+        let errs = validate_case_consistency(&config, &translations[0..2]);
+        assert_eq!(errs.len(), 0);
+    }
+
+    #[test]
+    fn test_explain() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![String::from("f"), String::from("m")],
+            plural_count: 2,
+            ..Default::default()
+        };
+        assert_eq!(
+            explain(
+                &config,
+                "{G=f}{RED}{NUM} {P a b} {STRING2} {G 1:1 c d}{FOO}{0:NUM}"
+            ),
+            vec![
+                "The gender of this string is 'f'.",
+                "Position 0: NUM (a number).",
+                "'{P}' selects one of 2 plural forms by position 0.",
+                "Position 1: STRING (a name or text).",
+                "'{G}' selects one of 2 gender forms by position 1:1.",
+                "Unknown string command '{FOO}'.",
+            ]
+        );
+        // The same normalization options apply as for validation.
+        let insensitive = LanguageConfig {
+            case_insensitive_cases: true,
+            ..config.clone()
+        };
+        assert_eq!(
+            explain(&insensitive, "{G=F}"),
+            vec!["The gender of this string is 'f'."]
+        );
+        assert_eq!(
+            explain(&config, "{1:RED}{NUM}"),
+            vec!["Position 0: NUM (a number)."]
+        );
+        assert_eq!(
+            explain(&config, "{NUM"),
+            vec!["Invalid string: Unterminated string command, '}' expected."]
+        );
+    }
+
+    #[test]
+    fn test_validate_occurrence_overrides() {
+        let mut config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            ..Default::default()
+        };
+        let base = ParsedString::parse("{RED}1{NBSP}km").unwrap();
+        let trans = ParsedString::parse("1 km").unwrap();
+        let val_trans = validate_string(&config, &trans, Some(&base));
+        assert_eq!(
+            val_trans,
+            vec![ValidationError {
+                severity: Severity::Warning,
+                code: ErrorCode::MissingCommand,
+                pos_begin: None,
+                pos_end: None,
+                message: String::from("String command '{RED}' is missing."),
+                suggestion: None,
+            }]
+        );
+
+        config.occurrence_overrides = HashMap::from([
+            (String::from("NBSP"), Occurence::EXACT),
+            (String::from("RED"), Occurence::ANY),
+        ]);
+        let val_trans = validate_string(&config, &trans, Some(&base));
+        assert_eq!(
+            val_trans,
+            vec![ValidationError {
+                severity: Severity::Warning,
+                code: ErrorCode::MissingCommand,
+                pos_begin: None,
+                pos_end: None,
+                message: String::from("String command '{NBSP}' is missing."),
+                suggestion: None,
+            }]
+        );
+
+        let trans = ParsedString::parse("1{NBSP}{NBSP}km").unwrap();
+        let val_trans = validate_string(&config, &trans, Some(&base));
+        assert_eq!(
+            val_trans,
+            vec![ValidationError {
+                severity: Severity::Warning,
+                code: ErrorCode::OccurrenceMismatch,
+                pos_begin: None,
+                pos_end: None,
+                message: String::from("String command '{NBSP}': expected 1 times, found 2 times."),
+                suggestion: None,
+            }]
+        );
+
+        config.occurrence_overrides = HashMap::from([(String::from("FOO"), Occurence::ANY)]);
+        let val_trans = validate_string(&config, &trans, Some(&base));
+        assert_eq!(
+            val_trans,
+            vec![ValidationError {
+                severity: Severity::Error,
+                code: ErrorCode::UnknownOverride,
+                pos_begin: None,
+                pos_end: None,
+                message: String::from("Unknown string command '{FOO}' in occurrence overrides."),
+                suggestion: Some(String::from("Fix the language configuration.")),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_validate_comments() {
+        let config = LanguageConfig {
+            dialect: Dialect::GAMESCRIPT,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            ..Default::default()
+        };
+        let base =
+            String::from("{#Town name}{STRING} has {#population}{NUM} {P inhabitant inhabitants}");
+        let val_base = validate_base(&config, &base);
+        assert_eq!(val_base.errors, vec![]);
+        assert_eq!(
+            val_base.normalized,
+            Some(String::from(
+                "{0:STRING} has {1:NUM} {P 1 inhabitant inhabitants}"
+            ))
+        );
+
+        // Comments do not count as parameters.
+        let val_trans = validate_translation(
+            &config,
+            &base,
+            "default",
+            "{STRING} {#unused}hat {NUM} {P Einwohner Einwohner}",
+        );
+        assert_eq!(val_trans.errors, vec![]);
+        assert_eq!(
+            val_trans.normalized,
+            Some(String::from(
+                "{0:STRING} hat {1:NUM} {P 1 Einwohner Einwohner}"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_validate_custom_commands() {
+        let mut config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            custom_commands: vec![
+                CustomCommand {
+                    name: String::from("FOO"),
+                    occurence: Occurence::EXACT,
+                    allow_case: false,
+                    count_like: false,
+                    sets_colour: false,
+                    sets_font: false,
+                    is_space_like: false,
+                    requires_following: false,
+                    parameters: vec![],
+                },
+                CustomCommand {
+                    name: String::from("WIDGETS"),
+                    occurence: Occurence::EXACT,
+                    allow_case: false,
+                    count_like: true,
+                    sets_colour: false,
+                    sets_font: false,
+                    is_space_like: false,
+                    requires_following: false,
+                    parameters: vec![crate::commands::ParameterInfo {
+                        allow_plural: true,
+                        allow_gender: false,
+                    }],
+                },
+            ],
+            ..Default::default()
+        };
+        assert_eq!(validate_config(&config), vec![]);
+
+        let base = String::from("{FOO}{WIDGETS} {P widget widgets}");
+        let val_base = validate_base(&config, &base);
+        assert_eq!(val_base.errors, vec![]);
+        assert_eq!(
+            val_base.normalized,
+            Some(String::from("{FOO}{0:WIDGETS} {P 0 widget widgets}"))
+        );
+
+        let trans = ParsedString::parse("{WIDGETS} {P Widget Widgets}").unwrap();
+        let base = ParsedString::parse(&base).unwrap();
+        let val_trans = validate_string(&config, &trans, Some(&base));
+        assert_eq!(
+            val_trans,
+            vec![ValidationError {
+                severity: Severity::Warning,
+                code: ErrorCode::MissingCommand,
+                pos_begin: None,
+                pos_end: None,
+                message: String::from("String command '{FOO}' is missing."),
+                suggestion: None,
+            }]
+        );
+
+        // Built-in commands cannot be replaced.
+        config.custom_commands[0].name = String::from("NBSP");
+        let expected = vec![ValidationError {
+            severity: Severity::Error,
+            code: ErrorCode::InvalidConfig,
+            pos_begin: None,
+            pos_end: None,
+            message: String::from(
+                "Custom string command '{NBSP}' conflicts with a built-in command.",
+            ),
+            suggestion: Some(String::from("Fix the language configuration.")),
+        }];
+        assert_eq!(validate_config(&config), expected);
+        assert_eq!(validate_string(&config, &trans, Some(&base)), expected);
+        let result = validate_translation(
+            &config,
+            "{NBSP}{WIDGETS} {P widget widgets}",
+            "default",
+            "{NBSP}{WIDGETS} {P Widget Widgets}",
+        );
+        assert_eq!(result.errors, expected);
+        assert_eq!(result.normalized, None);
+    }
+
+    #[test]
+    fn test_validate_unknown_command_severity() {
+        let mut config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            ..Default::default()
+        };
+        let base = String::from("{FOO}{NUM} apples");
+        let val_base = validate_base(&config, &base);
+        assert_eq!(
+            val_base.errors,
+            vec![ValidationError {
+                severity: Severity::Error,
+                code: ErrorCode::UnknownCommand,
+                pos_begin: Some(0),
+                pos_end: Some(5),
+                message: String::from("Unknown string command '{FOO}'."),
+                suggestion: None,
+            }]
+        );
+        assert_eq!(val_base.normalized, None);
+
+        config.unknown_command_severity = Severity::Warning;
+        let val_base = validate_base(&config, &base);
+        assert_eq!(
+            val_base.errors,
+            vec![ValidationError {
+                severity: Severity::Warning,
+                code: ErrorCode::UnknownCommand,
+                pos_begin: Some(0),
+                pos_end: Some(5),
+                message: String::from("Unknown string command '{FOO}'."),
+                suggestion: None,
+            }]
+        );
+        assert_eq!(
+            val_base.normalized,
+            Some(String::from("{FOO}{0:NUM} apples"))
+        );
+
+        // The unknown command is compared like any other command.
+        let val_trans = validate_translation(&config, &base, "default", "{NUM} Äpfel");
+        assert_eq!(
+            val_trans.errors,
+            vec![ValidationError {
+                severity: Severity::Warning,
+                code: ErrorCode::MissingCommand,
+                pos_begin: None,
+                pos_end: None,
+                message: String::from("String command '{FOO}' is missing."),
+                suggestion: None,
+            }]
+        );
+        assert_eq!(val_trans.normalized, Some(String::from("{0:NUM} Äpfel")));
+    }
+
+    #[test]
+    fn test_validate_param_as_text() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            ..Default::default()
+        };
+        let result = validate_translation(
+            &config,
+            &String::from("{BLACK}{NUM} {P item items}"),
+            &String::from("default"),
+            &String::from("{BLACK}number {P Ding Dinge}"),
+        );
+        assert_eq!(
+            result.errors,
+            vec![
+                ValidationError {
+                    severity: Severity::Error,
+                    code: ErrorCode::InvalidChoiceReference,
+                    pos_begin: Some(14),
+                    pos_end: Some(28),
+                    message: String::from("'{P}' has no preceding parameter to refer to."),
+                    suggestion: Some(String::from("Add a position reference.")),
+                },
+                ValidationError {
+                    severity: Severity::Error,
+                    code: ErrorCode::MissingParameter,
+                    pos_begin: None,
+                    pos_end: None,
+                    message: String::from("String command '{0:NUM}' is missing."),
+                    suggestion: None,
+                },
+            ]
+        );
+        assert_eq!(result.normalized, None);
+
+        let result = validate_translation(
+            &config,
+            &String::from("{NUM} items"),
+            &String::from("default"),
+            &String::from("number Dinge"),
+        );
+        assert_eq!(
+            result.errors,
+            vec![ValidationError {
+                severity: Severity::Error,
+                code: ErrorCode::MissingParameter,
+                pos_begin: None,
+                pos_end: None,
+                message: String::from("String command '{0:NUM}' is missing."),
+                suggestion: None,
+            }]
+        );
+        assert_eq!(result.normalized, None);
+    }
+
+    #[test]
+    fn test_validate_colour_stack() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            ..Default::default()
+        };
+
+        let nested = ParsedString::parse(
+            "{PUSH_COLOUR}{RED}a{PUSH_COLOUR}{BLUE}b{POP_COLOUR}c{POP_COLOUR}d",
+        )
+        .unwrap();
+        assert_eq!(validate_string(&config, &nested, None), vec![]);
+        assert_eq!(validate_string(&config, &nested, Some(&nested)), vec![]);
+
+        let push = ParsedString::parse("{PUSH_COLOUR}{RED}a{PUSH_COLOUR}b{POP_COLOUR}").unwrap();
+        assert_eq!(
+            validate_string(&config, &push, None),
+            vec![ValidationError {
+                severity: Severity::Error,
+                code: ErrorCode::UnbalancedColourStack,
+                pos_begin: Some(0),
+                pos_end: Some(13),
+                message: String::from("'{PUSH_COLOUR}' without following '{POP_COLOUR}'."),
+                suggestion: Some(String::from("Add '{POP_COLOUR}'.")),
+            }]
+        );
+
+        let pop = ParsedString::parse("{RED}a{POP_COLOUR}b").unwrap();
+        assert_eq!(
+            validate_string(&config, &pop, None),
+            vec![ValidationError {
+                severity: Severity::Error,
+                code: ErrorCode::UnbalancedColourStack,
+                pos_begin: Some(6),
+                pos_end: Some(18),
+                message: String::from("'{POP_COLOUR}' without preceding '{PUSH_COLOUR}'."),
+                suggestion: Some(String::from("Remove '{POP_COLOUR}'.")),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_list_commands() {
+        let commands = list_commands(&Dialect::GAMESCRIPT);
+        for info in COMMANDS.iter() {
+            let descriptor = commands.iter().find(|d| d.name == info.name);
+            if info.dialects.contains(&Dialect::GAMESCRIPT) {
+                let descriptor = descriptor.unwrap();
+                assert_eq!(descriptor.allow_case, info.allow_case, "{}", info.name);
+                assert_eq!(descriptor.norm_name, info.norm_name, "{}", info.name);
+            } else {
+                assert!(descriptor.is_none(), "{}", info.name);
+            }
+        }
+        assert!(commands.iter().any(|d| d.name == "STRING"));
+        let string1 = commands.iter().find(|d| d.name == "STRING1").unwrap();
+        assert_eq!(string1.norm_name, Some("STRING"));
+        assert!(!commands.iter().any(|d| d.name == "REV"));
+
+        let openttd = list_commands(&Dialect::OPENTTD);
+        let cargo = openttd.iter().find(|d| d.name == "CARGO_LONG").unwrap();
+        assert!(cargo.has_parameters && cargo.allow_gender && cargo.allow_plural);
+        assert_eq!(cargo.occurence, Occurence::EXACT);
+    }
+
+    #[test]
+    fn test_unknown_dialect() {
+        assert_eq!(Dialect::try_from("newgrf"), Ok(Dialect::NEWGRF));
+        assert_eq!("game-script".parse(), Ok(Dialect::GAMESCRIPT));
+        assert_eq!(
+            Dialect::try_from("openttdd"),
+            Err(String::from(
+                "Unknown dialect 'openttdd'. Known dialects are: 'game-script', 'newgrf', 'openttd'"
+            ))
+        );
+
+        let err = serde_json::from_str::<LanguageConfig>(
+            r#"{"dialect": "openttdd", "cases": [], "genders": [], "plural_count": 2}"#,
+        )
+        .unwrap_err();
+        assert!(err.to_string().starts_with(
+            "unknown variant `openttdd`, expected one of `game-script`, `newgrf`, `openttd`"
+        ));
+    }
+
+    #[test]
+    fn test_validate_redundant_colours() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            ..Default::default()
+        };
+
+        let base = ParsedString::parse("{RED}x{GREEN}y").unwrap();
+        let trans = ParsedString::parse("{RED}{GREEN}x").unwrap();
+        assert_eq!(
+            validate_string(&config, &trans, Some(&base)),
+            vec![ValidationError {
                 severity: Severity::Warning,
-                pos_begin: None,
-                pos_end: None,
-                message: format!(
-                    "String command '{{{}}}': expected {} times, found {} times.",
-                    norm_name, ex_count, found_count
+                code: ErrorCode::RedundantColour,
+                pos_begin: Some(0),
+                pos_end: Some(5),
+                message: String::from(
+                    "'{RED}' has no effect, because it is directly followed by '{GREEN}'."
                 ),
-                suggestion: None,
-            });
+                suggestion: Some(String::from("Remove '{RED}'.")),
+            },]
+        );
+
+        for trans in [
+            "{RED}x{GREEN}y",
+            "{RED}{NBSP}{GREEN}y",
+            "{RED}{PUSH_COLOUR}{GREEN}y{POP_COLOUR}",
+        ] {
+            let base = ParsedString::parse(trans).unwrap();
+            assert_eq!(validate_string(&config, &base, None), vec![], "{}", trans);
         }
     }
-    for (norm_name, (occurence, _)) in &nonpositional_count {
-        if *occurence != Occurence::ANY && signature.nonpositional_count.get(norm_name).is_none() {
-            errors.push(ValidationError {
-                severity: Severity::Warning,
-                pos_begin: None,
-                pos_end: None,
-                message: format!("String command '{{{}}}' is unexpected.", norm_name),
-                suggestion: Some(String::from("Remove this command.")),
-            });
-        }
+
+    #[test]
+    fn test_unicode_nfc() {
+        let mut config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            ..Default::default()
+        };
+        let base = String::from("Caf\u{65}\u{301} {NUM} {P tass\u{65}\u{301} tass\u{65}\u{301}s}");
+        let result = validate_base(&config, &base);
+        assert_eq!(result.errors, vec![]);
+        assert_eq!(
+            result.normalized,
+            Some(String::from(
+                "Caf\u{65}\u{301} {0:NUM} {P 0 tass\u{65}\u{301} tass\u{65}\u{301}s}"
+            ))
+        );
+
+        config.unicode_nfc = true;
+        let result = validate_base(&config, &base);
+        assert_eq!(result.errors, vec![]);
+        assert_eq!(
+            result.normalized,
+            Some(String::from(
+                "Caf\u{e9} {0:NUM} {P 0 tass\u{e9} tass\u{e9}s}"
+            ))
+        );
     }
 
-    errors
-}
+    #[test]
+    fn test_validate_base_all_dialects() {
+        let config = LanguageConfig::default();
+        assert_eq!(
+            validate_base_all_dialects(
+                &config,
+                "{RAW_STRING} {COMMA}{SIGNED_WORD}{REV}{RAW_STRING}"
+            ),
+            Ok(vec![
+                DialectSupport {
+                    command: String::from("RAW_STRING"),
+                    rejected_by: vec![Dialect::NEWGRF],
+                },
+                DialectSupport {
+                    command: String::from("COMMA"),
+                    rejected_by: vec![],
+                },
+                DialectSupport {
+                    command: String::from("SIGNED_WORD"),
+                    rejected_by: vec![Dialect::GAMESCRIPT, Dialect::OPENTTD],
+                },
+                DialectSupport {
+                    command: String::from("REV"),
+                    rejected_by: vec![Dialect::GAMESCRIPT, Dialect::NEWGRF],
+                },
+            ])
+        );
 
-fn normalize_string(dialect: &Dialect, parsed: &mut ParsedString) {
-    let mut parameters = HashMap::new();
+        let result = validate_base_all_dialects(&config, "{COMMA");
+        assert_eq!(result.unwrap_err()[0].code, ErrorCode::ParseError);
+    }
 
-    let mut pos = 0;
-    for fragment in &mut parsed.fragments {
-        match &mut fragment.content {
-            FragmentContent::Command(cmd) => {
-                if let Some(info) = COMMANDS
-                    .into_iter()
-                    .find(|ci| ci.name == cmd.name && ci.dialects.contains(&dialect))
-                {
-                    if let Some(norm_name) = info.norm_name {
-                        // normalize name
-                        cmd.name = String::from(norm_name);
-                    }
-                    if !info.parameters.is_empty() {
-                        if let Some(index) = cmd.index {
-                            pos = index;
-                        } else {
-                            // add missing indices
-                            cmd.index = Some(pos);
-                        }
-                        parameters.insert(pos, info);
-                        pos += 1;
-                    }
-                }
-            }
-            FragmentContent::Choice(cmd) => {
-                match cmd.name.as_str() {
-                    "P" => {
-                        if cmd.indexref.is_none() && pos > 0 {
-                            // add missing indices
-                            cmd.indexref = Some(pos - 1);
-                        }
-                    }
-                    "G" => {
-                        if cmd.indexref.is_none() {
-                            // add missing indices
-                            cmd.indexref = Some(pos);
-                        }
-                    }
-                    _ => panic!(),
-                };
+    #[test]
+    fn test_normalize_subindex() {
+        let dialect = Dialect::OPENTTD;
+
+        // Implicit references are resolved, so the sub-index is compiled.
+        let mut parsed = ParsedString::parse("{G a b}{STRING} {NUM} {P c d}").unwrap();
+        for fragment in &mut parsed.fragments {
+            if let FragmentContent::Choice(cmd) = &mut fragment.content {
+                cmd.indexsubref = Some(1);
             }
-            _ => (),
         }
-    }
+        normalize_string(&dialect, &[], &mut parsed);
+        assert_eq!(
+            parsed.compile(),
+            "{G 0:1 a b}{0:STRING} {1:NUM} {P 1:1 c d}"
+        );
+        let reparsed = ParsedString::parse(&parsed.compile()).unwrap();
+        assert_eq!(reparsed.compile(), parsed.compile());
 
-    for fragment in &mut parsed.fragments {
-        if let FragmentContent::Choice(cmd) = &mut fragment.content {
-            if let Some(ref_info) = cmd.indexref.and_then(|pos| parameters.get(&pos)) {
-                if cmd.indexsubref == ref_info.def_plural_subindex.or(Some(0)) {
-                    // remove subindex, if default
-                    cmd.indexsubref = None;
-                }
-            }
+        // Without a parameter to refer to, there is nothing to select a sub-parameter from.
+        let mut parsed = ParsedString::parse("{P a b}").unwrap();
+        if let FragmentContent::Choice(cmd) = &mut parsed.fragments[0].content {
+            cmd.indexsubref = Some(1);
         }
-    }
-}
+        normalize_string(&dialect, &[], &mut parsed);
+        assert_eq!(parsed.compile(), "{P a b}");
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        // Default sub-indices are removed, other sub-indices are kept.
+        let mut parsed = ParsedString::parse("{NUM}{P 0:0 a b}{STRING1}{G 1:1 c d}").unwrap();
+        normalize_string(&dialect, &[], &mut parsed);
+        assert_eq!(parsed.compile(), "{0:NUM}{P 0 a b}{1:STRING}{G 1:1 c d}");
+    }
 
     #[test]
-    fn test_sanitize() {
-        let mut s1 = String::from("");
-        let mut s2 = String::from(" a b c ");
-        let mut s3 = String::from("\0a\tb\rc\r\n");
-        let mut s4 = String::from("abc\u{b3}");
-        remove_ascii_ctrl(&mut s1);
-        remove_ascii_ctrl(&mut s2);
-        remove_ascii_ctrl(&mut s3);
-        remove_ascii_ctrl(&mut s4);
-        assert_eq!(s1, String::from(""));
-        assert_eq!(s2, String::from(" a b c "));
-        assert_eq!(s3, String::from(" a b c  "));
-        assert_eq!(s4, String::from("abc\u{b3}"));
-        remove_trailing_blanks(&mut s1);
-        remove_trailing_blanks(&mut s2);
-        remove_trailing_blanks(&mut s3);
-        remove_trailing_blanks(&mut s4);
-        assert_eq!(s1, String::from(""));
-        assert_eq!(s2, String::from(" a b c"));
-        assert_eq!(s3, String::from(" a b c"));
-        assert_eq!(s4, String::from("abc\u{b3}"));
+    fn test_error_order() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            normalize_on_error: true,
+            report_normalization: true,
+            ..Default::default()
+        };
+        let result = validate_translation(
+            &config,
+            "{RED}{NUM} apples{STRING}",
+            "default",
+            "{BLUE}{NUM} Äpfel{P a b c}{FOO}",
+        );
+        let order: Vec<(Option<usize>, Severity, ErrorCode)> = result
+            .errors
+            .iter()
+            .map(|e| (e.pos_begin, e.severity, e.code))
+            .collect();
+        // Normalization notices are found last, but sorted by position.
+        assert_eq!(
+            order,
+            vec![
+                (Some(0), Severity::Warning, ErrorCode::UnexpectedCommand),
+                (Some(6), Severity::Info, ErrorCode::Normalized),
+                (Some(17), Severity::Info, ErrorCode::Normalized),
+                (Some(24), Severity::Error, ErrorCode::PluralCountMismatch),
+                (Some(26), Severity::Error, ErrorCode::UnknownCommand),
+                (None, Severity::Error, ErrorCode::MissingParameter),
+                (None, Severity::Warning, ErrorCode::MissingCommand),
+            ]
+        );
+
+        // Errors at the same position are ordered by severity, and otherwise keep their order.
+        let error = |pos_begin, severity, code| ValidationError {
+            severity,
+            code,
+            pos_begin,
+            pos_end: None,
+            message: String::new(),
+            suggestion: None,
+        };
+        let mut errors = vec![
+            error(None, Severity::Warning, ErrorCode::MissingCommand),
+            error(Some(3), Severity::Info, ErrorCode::Normalized),
+            error(None, Severity::Error, ErrorCode::MissingParameter),
+            error(Some(3), Severity::Error, ErrorCode::UnknownCommand),
+            error(Some(3), Severity::Info, ErrorCode::RawLineBreak),
+        ];
+        sort_errors(&mut errors);
+        assert_eq!(
+            errors.iter().map(|e| e.code).collect::<Vec<_>>(),
+            vec![
+                ErrorCode::UnknownCommand,
+                ErrorCode::Normalized,
+                ErrorCode::RawLineBreak,
+                ErrorCode::MissingParameter,
+                ErrorCode::MissingCommand,
+            ]
+        );
     }
 
     #[test]
-    fn test_signature_empty() {
-        let parsed = ParsedString::parse("").unwrap();
-        let sig = get_signature(&Dialect::OPENTTD, &parsed).unwrap();
-        assert!(sig.parameters.is_empty());
-        assert!(sig.nonpositional_count.is_empty());
+    fn test_normalize_on_error() {
+        let mut config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            ..Default::default()
+        };
+        let base = String::from("{NUM} car{P \"\" s}");
+        let case = String::from("default");
+        let translation = String::from("{FOO}{NUM} Auto{P \"\" s es}  ");
+
+        let result = validate_base(&config, &String::from("{FOO}{NUM}"));
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.normalized, None);
+        let result = validate_translation(&config, &base, &case, &translation);
+        assert_eq!(result.errors.len(), 2);
+        assert_eq!(result.normalized, None);
+
+        config.normalize_on_error = true;
+        let result = validate_base(&config, &String::from("{FOO}{NUM}"));
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.normalized, Some(String::from("{FOO}{0:NUM}")));
+        let result = validate_translation(&config, &base, &case, &translation);
+        assert_eq!(result.errors.len(), 2);
+        assert_eq!(
+            result.normalized,
+            Some(String::from("{FOO}{0:NUM} Auto{P 0 \"\" s es}"))
+        );
+
+        // Strings, which cannot be parsed, have no preview.
+        let result = validate_translation(&config, &base, &case, &String::from("{NUM"));
+        assert_eq!(result.normalized, None);
+        let result = validate_translation(&config, &String::from("{NUM"), &case, &translation);
+        assert_eq!(result.normalized, None);
     }
 
     #[test]
-    fn test_signature_pos() {
-        let parsed = ParsedString::parse("{P a b}{RED}{NUM}{NBSP}{MONO_FONT}{5:STRING.foo}{RED}{2:STRING3.bar}{RAW_STRING}{3:RAW_STRING}{G c d}").unwrap();
-        let sig = get_signature(&Dialect::OPENTTD, &parsed).unwrap();
-        assert_eq!(sig.parameters.len(), 4);
-        assert_eq!(sig.parameters.get(&0).unwrap().0.name, "NUM");
-        assert_eq!(sig.parameters.get(&0).unwrap().1, 1);
-        assert_eq!(sig.parameters.get(&5).unwrap().0.name, "STRING");
-        assert_eq!(sig.parameters.get(&5).unwrap().1, 1);
-        assert_eq!(sig.parameters.get(&2).unwrap().0.name, "STRING3");
-        assert_eq!(sig.parameters.get(&2).unwrap().1, 1);
-        assert_eq!(sig.parameters.get(&3).unwrap().0.name, "RAW_STRING");
-        assert_eq!(sig.parameters.get(&3).unwrap().1, 2);
-        assert_eq!(sig.nonpositional_count.len(), 3);
+    fn test_validate_plural_has_other() {
+        let mut config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            ..Default::default()
+        };
+        let base = ParsedString::parse("{NUM} car{P \"\" s}").unwrap();
+        let two = ParsedString::parse("{NUM} Auto{P a b}").unwrap();
+        let three = ParsedString::parse("{NUM} Auto{P a b c}").unwrap();
+        let four = ParsedString::parse("{NUM} Auto{P a b c d}").unwrap();
+        let error = |pos_begin, pos_end, message: &str| ValidationError {
+            severity: Severity::Error,
+            code: ErrorCode::PluralCountMismatch,
+            pos_begin: Some(pos_begin),
+            pos_end: Some(pos_end),
+            message: String::from(message),
+            suggestion: None,
+        };
+
+        assert_eq!(validate_string(&config, &two, Some(&base)), vec![]);
         assert_eq!(
-            sig.nonpositional_count.get("RED"),
-            Some(&(Occurence::NONZERO, 2))
+            validate_string(&config, &three, Some(&base)),
+            vec![error(17, 18, "Expected 2 plural choices, found 3.")]
         );
         assert_eq!(
-            sig.nonpositional_count.get("MONO_FONT"),
-            Some(&(Occurence::EXACT, 1))
+            validate_string(&config, &four, Some(&base)),
+            vec![error(17, 18, "Expected 2 plural choices, found 4.")]
         );
+
+        config.plural_has_other = true;
+        assert_eq!(validate_string(&config, &two, Some(&base)), vec![]);
+        assert_eq!(validate_string(&config, &three, Some(&base)), vec![]);
         assert_eq!(
-            sig.nonpositional_count.get("NBSP"),
-            Some(&(Occurence::ANY, 1))
+            validate_string(&config, &four, Some(&base)),
+            vec![error(19, 20, "Expected 2 or 3 plural choices, found 4.")]
         );
     }
 
     #[test]
-    fn test_signature_dialect() {
-        let parsed = ParsedString::parse("{RAW_STRING}").unwrap();
+    fn test_plural_count_for_language() {
+        assert_eq!(plural_count_for_language("en_GB"), Some(2));
+        assert_eq!(plural_count_for_language("en-US"), Some(2));
+        assert_eq!(plural_count_for_language("ja_JP"), Some(1));
+        assert_eq!(plural_count_for_language("ja"), Some(1));
+        assert_eq!(plural_count_for_language("ru_RU"), Some(3));
+        assert_eq!(plural_count_for_language("sl_SI"), Some(4));
+        assert_eq!(plural_count_for_language("xx_XX"), None);
+        assert_eq!(plural_count_for_language(""), None);
+        for (isocode, count) in LANGUAGE_PLURAL_COUNTS {
+            assert!(count <= DEFAULT_MAX_PLURAL_COUNT, "{}", isocode);
+        }
+    }
 
-        let sig = get_signature(&Dialect::OPENTTD, &parsed).unwrap();
-        assert_eq!(sig.parameters.len(), 1);
-        assert_eq!(sig.parameters.get(&0).unwrap().0.name, "RAW_STRING");
-        assert_eq!(sig.parameters.get(&0).unwrap().1, 1);
-        assert_eq!(sig.nonpositional_count.len(), 0);
+    #[test]
+    fn test_validate_leading_whitespace() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            ..Default::default()
+        };
 
-        let err = get_signature(&Dialect::NEWGRF, &parsed).err().unwrap();
-        assert_eq!(err.len(), 1);
+        let base = ParsedString::parse("{RED}Age: {NUM}").unwrap();
+        let trans = ParsedString::parse("{RED}Alter: {NUM}").unwrap();
+        assert_eq!(validate_string(&config, &trans, Some(&base)), vec![]);
+
+        let trans = ParsedString::parse("{RED} Alter: {NUM}").unwrap();
         assert_eq!(
-            err[0],
-            ValidationError {
-                severity: Severity::Error,
-                pos_begin: Some(0),
-                pos_end: Some(12),
-                message: String::from("Unknown string command '{RAW_STRING}'."),
-                suggestion: None,
-            }
+            validate_string(&config, &trans, Some(&base)),
+            vec![ValidationError {
+                severity: Severity::Warning,
+                code: ErrorCode::LeadingWhitespace,
+                pos_begin: Some(5),
+                pos_end: Some(6),
+                message: String::from(
+                    "The text starts with different whitespace than in the base."
+                ),
+                suggestion: Some(String::from("Remove the leading whitespace.")),
+            }]
         );
-    }
 
-    #[test]
-    fn test_signature_unknown() {
-        let parsed = ParsedString::parse("{FOOBAR}").unwrap();
-        let err = get_signature(&Dialect::OPENTTD, &parsed).err().unwrap();
-        assert_eq!(err.len(), 1);
+        let base = ParsedString::parse("  Age: {NUM}").unwrap();
+        let trans = ParsedString::parse("  Alter: {NUM}").unwrap();
+        assert_eq!(validate_string(&config, &trans, Some(&base)), vec![]);
+        let trans = ParsedString::parse("Alter: {NUM}").unwrap();
         assert_eq!(
-            err[0],
-            ValidationError {
-                severity: Severity::Error,
+            validate_string(&config, &trans, Some(&base)),
+            vec![ValidationError {
+                severity: Severity::Warning,
+                code: ErrorCode::LeadingWhitespace,
                 pos_begin: Some(0),
-                pos_end: Some(8),
-                message: String::from("Unknown string command '{FOOBAR}'."),
-                suggestion: None,
-            }
+                pos_end: Some(7),
+                message: String::from(
+                    "The text starts with different whitespace than in the base."
+                ),
+                suggestion: Some(String::from("Use the same leading whitespace as the base.")),
+            }]
         );
+
+        // Text after parameters is not compared.
+        let base = ParsedString::parse("{STRING} has {NUM}").unwrap();
+        let trans = ParsedString::parse("{0:STRING}: {1:NUM}").unwrap();
+        assert_eq!(validate_string(&config, &trans, Some(&base)), vec![]);
+
+        // Base strings are not compared.
+        let base = ParsedString::parse(" Age").unwrap();
+        assert_eq!(validate_string(&config, &base, None), vec![]);
     }
 
     #[test]
-    fn test_signature_nonpos() {
-        let parsed = ParsedString::parse("{1:RED}").unwrap();
-        let err = get_signature(&Dialect::OPENTTD, &parsed).err().unwrap();
-        assert_eq!(err.len(), 1);
+    fn test_validate_control_characters() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            ..Default::default()
+        };
+        let base = String::from("{RED}Line 1{}Line 2");
+        let case = String::from("default");
+
+        let result = validate_translation(
+            &config,
+            &base,
+            &case,
+            &String::from("{RED}Zeile 1\nZeile 2"),
+        );
         assert_eq!(
-            err[0],
-            ValidationError {
-                severity: Severity::Error,
-                pos_begin: Some(0),
-                pos_end: Some(7),
-                message: String::from("Command '{RED}' cannot have a position reference."),
-                suggestion: Some(String::from("Remove '1:'.")),
-            }
+            result.errors,
+            vec![ValidationError {
+                severity: Severity::Warning,
+                code: ErrorCode::RawLineBreak,
+                pos_begin: Some(12),
+                pos_end: Some(13),
+                message: String::from(
+                    "The text contains a raw line break (LF), which will be replaced by a space."
+                ),
+                suggestion: Some(String::from("Use '{}' for line breaks.")),
+            }]
+        );
+        // Normalization still replaces the control character.
+        assert_eq!(
+            result.normalized,
+            Some(String::from("{RED}Zeile 1 Zeile 2"))
+        );
+
+        // A '}' in text does not shift the position.
+        let result = validate_translation(
+            &config,
+            &base,
+            &case,
+            &String::from("{RED}Zeile} 1\nZeile 2"),
+        );
+        assert_eq!(
+            (result.errors[0].pos_begin, result.errors[0].pos_end),
+            (Some(13), Some(14))
+        );
+
+        // CRLF and CR are reported as single line break, and result in the same text as LF.
+        for (translation, kind, len) in [
+            ("Zeile 1\r\nZeile 2", "CRLF", 2),
+            ("Zeile 1\rZeile 2", "CR", 1),
+        ] {
+            let result =
+                validate_translation(&config, &base, &case, &format!("{{RED}}{}", translation));
+            assert_eq!(
+                result.errors,
+                vec![ValidationError {
+                    severity: Severity::Warning,
+                    code: ErrorCode::RawLineBreak,
+                    pos_begin: Some(12),
+                    pos_end: Some(12 + len),
+                    message: format!(
+                        "The text contains a raw line break ({}), which will be replaced by a space.",
+                        kind
+                    ),
+                    suggestion: Some(String::from("Use '{}' for line breaks.")),
+                }]
+            );
+            assert_eq!(
+                result.normalized,
+                Some(String::from("{RED}Zeile 1 Zeile 2"))
+            );
+        }
+
+        let result = validate_translation(
+            &config,
+            &base,
+            &case,
+            &String::from("{RED}Zeile\t1{}Zeile 2"),
+        );
+        assert_eq!(
+            result.errors,
+            vec![ValidationError {
+                severity: Severity::Warning,
+                code: ErrorCode::ControlCharacter,
+                pos_begin: Some(10),
+                pos_end: Some(11),
+                message: String::from(
+                    "The text contains the control character U+0009, which will be replaced by a space."
+                ),
+                suggestion: Some(String::from("Remove the control character.")),
+            }]
+        );
+        assert_eq!(
+            result.normalized,
+            Some(String::from("{RED}Zeile 1{}Zeile 2"))
+        );
+
+        // Every line break and control character is reported.
+        let result = validate_translation(
+            &config,
+            &base,
+            &case,
+            &String::from("{RED}a\tb\r\nc\r\nd\n"),
+        );
+        let errors: Vec<_> = result
+            .errors
+            .iter()
+            .map(|e| (e.code, e.pos_begin, e.pos_end))
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                (ErrorCode::ControlCharacter, Some(6), Some(7)),
+                (ErrorCode::RawLineBreak, Some(8), Some(10)),
+                (ErrorCode::RawLineBreak, Some(11), Some(13)),
+                (ErrorCode::RawLineBreak, Some(14), Some(15)),
+            ]
         );
     }
 
     #[test]
-    fn test_validate_empty() {
+    fn test_error_code() {
         let config = LanguageConfig {
             dialect: Dialect::OPENTTD,
             cases: vec![],
             genders: vec![],
-            plural_count: 0,
+            plural_count: 2,
+            ..Default::default()
         };
-        let base = ParsedString::parse("").unwrap();
-
-        let val_base = validate_string(&config, &base, None);
-        assert_eq!(val_base.len(), 0);
+        let result = validate_base(&config, &String::from("{FOO}"));
+        assert_eq!(result.errors.len(), 1);
+        let error = &result.errors[0];
+        assert_eq!(error.code, ErrorCode::UnknownCommand);
 
-        let val_trans = validate_string(&config, &base, Some(&base));
-        assert_eq!(val_trans.len(), 0);
+        let json = serde_json::to_value(error).unwrap();
+        assert_eq!(json["code"], "unknown-command");
+        let error: ValidationError = serde_json::from_value(json).unwrap();
+        assert_eq!(error.code, ErrorCode::UnknownCommand);
     }
 
     #[test]
-    fn test_validate_invalid() {
-        let config = LanguageConfig {
-            dialect: Dialect::OPENTTD,
-            cases: vec![],
-            genders: vec![],
-            plural_count: 0,
+    fn test_validation_summary() {
+        let make_error = |severity: Severity| ValidationError {
+            severity,
+            code: ErrorCode::UnexpectedCommand,
+            pos_begin: None,
+            pos_end: None,
+            message: String::new(),
+            suggestion: None,
         };
-        let base = ParsedString::parse("{FOOBAR}").unwrap();
 
-        let val_base = validate_string(&config, &base, None);
-        assert_eq!(val_base.len(), 1);
+        let result = ValidationResult {
+            errors: vec![],
+            normalized: Some(String::from("foo")),
+        };
         assert_eq!(
-            val_base[0],
-            ValidationError {
-                severity: Severity::Error,
-                pos_begin: Some(0),
-                pos_end: Some(8),
-                message: String::from("Unknown string command '{FOOBAR}'."),
-                suggestion: None,
+            result.summary(),
+            ValidationSummary {
+                committable: true,
+                error_count: 0,
+                warning_count: 0,
+                info_count: 0,
             }
         );
 
-        let val_trans = validate_string(&config, &base, Some(&base));
-        assert_eq!(val_trans.len(), 1);
+        let result = ValidationResult {
+            errors: vec![
+                make_error(Severity::Warning),
+                make_error(Severity::Info),
+                make_error(Severity::Warning),
+            ],
+            normalized: Some(String::from("foo")),
+        };
         assert_eq!(
-            val_trans[0],
-            ValidationError {
-                severity: Severity::Error,
-                pos_begin: None,
-                pos_end: None,
-                message: String::from("Base language text is invalid."),
-                suggestion: Some(String::from("This is a bug; wait until it is fixed.")),
+            result.summary(),
+            ValidationSummary {
+                committable: true,
+                error_count: 0,
+                warning_count: 2,
+                info_count: 1,
+            }
+        );
+
+        let result = ValidationResult {
+            errors: vec![
+                make_error(Severity::Info),
+                make_error(Severity::Error),
+                make_error(Severity::Warning),
+            ],
+            normalized: None,
+        };
+        assert_eq!(
+            result.summary(),
+            ValidationSummary {
+                committable: false,
+                error_count: 1,
+                warning_count: 1,
+                info_count: 1,
             }
         );
     }
 
     #[test]
-    fn test_validate_positional() {
+    fn test_validation_result_checks() {
+        let make_error = |severity: Severity| ValidationError {
+            severity,
+            code: ErrorCode::UnexpectedCommand,
+            pos_begin: None,
+            pos_end: None,
+            message: String::new(),
+            suggestion: None,
+        };
+        let check = |severities: Vec<Severity>| {
+            let result = ValidationResult {
+                errors: severities.into_iter().map(make_error).collect(),
+                normalized: None,
+            };
+            (
+                result.has_errors(),
+                result.has_warnings(),
+                result.is_clean(),
+            )
+        };
+
+        assert_eq!(check(vec![]), (false, false, true));
+        assert_eq!(check(vec![Severity::Info]), (false, false, true));
+        assert_eq!(
+            check(vec![Severity::Warning, Severity::Warning]),
+            (false, true, false)
+        );
+        assert_eq!(
+            check(vec![Severity::Info, Severity::Error]),
+            (true, false, false)
+        );
+    }
+
+    #[test]
+    fn test_normalize_batch() {
         let config = LanguageConfig {
             dialect: Dialect::OPENTTD,
             cases: vec![],
             genders: vec![],
-            plural_count: 0,
+            plural_count: 2,
+            ..Default::default()
         };
-        let base = ParsedString::parse("{NUM}").unwrap();
-        let val_base = validate_string(&config, &base, None);
-        assert_eq!(val_base.len(), 0);
-
-        {
-            let trans = ParsedString::parse("{0:NUM}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 0);
-        }
-        {
-            let trans = ParsedString::parse("{FOOBAR}{NUM}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 1);
-            assert_eq!(
-                val_trans[0],
-                ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: Some(0),
-                    pos_end: Some(8),
-                    message: String::from("Unknown string command '{FOOBAR}'."),
-                    suggestion: None,
-                }
-            );
-        }
-        {
-            let trans = ParsedString::parse("{1:NUM}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 2);
-            assert_eq!(
-                val_trans[0],
-                ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: Some(0),
-                    pos_end: Some(7),
-                    message: String::from("There is no parameter in position 1, found '{NUM}'."),
-                    suggestion: None,
-                }
-            );
-            assert_eq!(
-                val_trans[1],
-                ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: None,
-                    pos_end: None,
-                    message: String::from("String command '{0:NUM}' is missing."),
-                    suggestion: None,
-                }
-            );
-        }
-        {
-            let trans = ParsedString::parse("{COMMA}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 2);
-            assert_eq!(
-                val_trans[0],
-                ValidationError {
+        let strings = vec![
+            String::from("{STRING2} {NUM}  "),
+            String::from("{NUM"),
+            String::from(""),
+            String::from("{RED}foo"),
+        ];
+        assert_eq!(
+            normalize_batch(&config, &strings),
+            vec![
+                Ok(String::from("{0:STRING} {1:NUM}")),
+                Err(ValidationError {
                     severity: Severity::Error,
+                    code: ErrorCode::ParseError,
                     pos_begin: Some(0),
-                    pos_end: Some(7),
-                    message: String::from("Expected '{0:NUM}', found '{COMMA}'."),
-                    suggestion: None,
-                }
-            );
-            assert_eq!(
-                val_trans[1],
-                ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: None,
-                    pos_end: None,
-                    message: String::from("String command '{0:NUM}' is missing."),
-                    suggestion: None,
-                }
-            );
-        }
-        {
-            let trans = ParsedString::parse("{0:NUM}{0:NUM}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 1);
-            assert_eq!(
-                val_trans[0],
-                ValidationError {
-                    severity: Severity::Warning,
-                    pos_begin: None,
                     pos_end: None,
-                    message: String::from(
-                        "String command '{0:NUM}': expected 1 times, found 2 times."
-                    ),
+                    message: String::from("Unterminated string command, '}' expected."),
                     suggestion: None,
-                }
-            );
-        }
+                }),
+                Ok(String::from("")),
+                Ok(String::from("{RED}foo")),
+            ]
+        );
     }
 
     #[test]
-    fn test_validate_front() {
+    fn test_validate_substring_gender() {
         let config = LanguageConfig {
             dialect: Dialect::OPENTTD,
             cases: vec![],
-            genders: vec![String::from("a"), String::from("b")],
-            plural_count: 0,
+            genders: vec![String::from("m"), String::from("f")],
+            plural_count: 2,
+            ..Default::default()
         };
-        let base = ParsedString::parse("{BIG_FONT}foo{NUM}").unwrap();
-        let val_base = validate_string(&config, &base, None);
-        assert_eq!(val_base.len(), 0);
+        let host = "{NUM} {G 1 Neuer Neue} {STRING}";
+        assert_eq!(
+            validate_substring_gender(&config, host, 1, "Zug"),
+            vec![ValidationError {
+                severity: Severity::Warning,
+                code: ErrorCode::MissingSubstringGender,
+                pos_begin: None,
+                pos_end: None,
+                message: String::from(
+                    "The string is inserted into position 1 of a string with gender choices, but does not define its gender."
+                ),
+                suggestion: Some(String::from("Add '{G=...}' to the front of the string.")),
+            }]
+        );
+        assert_eq!(
+            validate_substring_gender(&config, host, 1, "{G=m}Zug").len(),
+            0
+        );
+        assert_eq!(validate_substring_gender(&config, host, 0, "Zug").len(), 0);
+        assert_eq!(
+            validate_substring_gender(&config, "{NUM} {STRING}", 1, "Zug").len(),
+            0
+        );
+    }
 
-        {
-            let trans = ParsedString::parse("{G=a}{BIG_FONT}bar{NUM}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 0);
-        }
-        {
-            let trans = ParsedString::parse("{G=a}{G=a}{BIG_FONT}bar{NUM}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 1);
-            assert_eq!(
-                val_trans[0],
-                ValidationError {
-                    severity: Severity::Warning,
-                    pos_begin: Some(5),
-                    pos_end: Some(10),
-                    message: String::from("Duplicate gender definition."),
-                    suggestion: Some(String::from("Remove the second '{G=...}'.")),
-                }
-            );
-        }
-        {
-            let trans = ParsedString::parse("{BIG_FONT}{G=a}bar{NUM}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 1);
-            assert_eq!(
-                val_trans[0],
-                ValidationError {
-                    severity: Severity::Warning,
-                    pos_begin: Some(10),
-                    pos_end: Some(15),
-                    message: String::from("Gender definitions must be at the front."),
-                    suggestion: Some(String::from(
-                        "Move '{G=...}' to the front of the translation."
-                    )),
-                }
-            );
-        }
-        {
-            let trans = ParsedString::parse("foo{BIG_FONT}bar{NUM}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 0);
-        }
-        {
-            let trans = ParsedString::parse("foo{G=a}bar{NUM}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 2);
-            assert_eq!(
-                val_trans[0],
-                ValidationError {
-                    severity: Severity::Warning,
-                    pos_begin: Some(3),
-                    pos_end: Some(8),
-                    message: String::from("Gender definitions must be at the front."),
-                    suggestion: Some(String::from(
-                        "Move '{G=...}' to the front of the translation."
-                    )),
-                }
-            );
-            assert_eq!(
-                val_trans[1],
-                ValidationError {
-                    severity: Severity::Warning,
-                    pos_begin: None,
-                    pos_end: None,
-                    message: String::from("String command '{BIG_FONT}' is missing."),
-                    suggestion: None,
-                }
+    #[test]
+    fn test_command_diff() {
+        let mut config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            plural_count: 2,
+            ..Default::default()
+        };
+        assert_eq!(
+            command_diff(
+                &config,
+                "{RED}{NUM}{NBSP}km {STRING2} {STRING2}",
+                "{BLUE}{0:NUM} km {2:STRING} {1:STRING}{BLUE}"
+            ),
+            Ok(CommandDiff {
+                added: vec![String::from("{BLUE}"), String::from("{BLUE}")],
+                removed: vec![String::from("{RED}"), String::from("{NBSP}")],
+            })
+        );
+        assert_eq!(
+            command_diff(&config, "{NUM}", "{NUM}"),
+            Ok(CommandDiff {
+                added: vec![],
+                removed: vec![],
+            })
+        );
+        assert!(command_diff(&config, "{NUM}", "{NUM").is_err());
+        // custom commands and choice contents of the configuration are used, like for validation
+        config.custom_commands = vec![CustomCommand {
+            name: String::from("FOO"),
+            occurence: Occurence::ANY,
+            allow_case: false,
+            count_like: false,
+            sets_colour: false,
+            sets_font: false,
+            is_space_like: false,
+            requires_following: false,
+            parameters: vec![],
+        }];
+        config.parse_choice_contents = true;
+        assert_eq!(
+            command_diff(&config, "{NUM} {P a b}", "{FOO}{NUM} {P \"{0:NUM} a\" b}"),
+            Ok(CommandDiff {
+                added: vec![String::from("{FOO}")],
+                removed: vec![],
+            })
+        );
+    }
+
+    #[test]
+    fn test_validate_requires_following() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            custom_commands: vec![CustomCommand {
+                name: String::from("FORCE_SIGN"),
+                occurence: Occurence::ANY,
+                allow_case: false,
+                count_like: false,
+                sets_colour: false,
+                sets_font: false,
+                is_space_like: false,
+                requires_following: true,
+                parameters: vec![],
+            }],
+            ..Default::default()
+        };
+        let base = ParsedString::parse("Change: {FORCE_SIGN}{CURRENCY_LONG}").unwrap();
+        assert_eq!(validate_string(&config, &base, None), vec![]);
+
+        let base = ParsedString::parse("Change: {FORCE_SIGN} {CURRENCY_LONG}").unwrap();
+        assert_eq!(
+            validate_string(&config, &base, None),
+            vec![ValidationError {
+                severity: Severity::Error,
+                code: ErrorCode::MissingFollowingParameter,
+                pos_begin: Some(8),
+                pos_end: Some(20),
+                message: String::from(
+                    "'{FORCE_SIGN}' must be directly followed by a number, like '{NUM}'."
+                ),
+                suggestion: Some(String::from(
+                    "Move '{FORCE_SIGN}' directly in front of the number."
+                )),
+            }]
+        );
+        let base = ParsedString::parse("{FORCE_SIGN}{STRING}").unwrap();
+        assert_eq!(validate_string(&config, &base, None).len(), 1);
+        let base = ParsedString::parse("{FORCE_SIGN}").unwrap();
+        assert_eq!(validate_string(&config, &base, None).len(), 1);
+
+        // Built-in currency and decimal commands take their value as parameter, they do not modify the following number.
+        for (dialect, string) in [
+            (Dialect::OPENTTD, "{CURRENCY_LONG}{NUM}"),
+            (Dialect::OPENTTD, "{CURRENCY_SHORT}"),
+            (Dialect::GAMESCRIPT, "{DECIMAL}{NUM}"),
+            (Dialect::GAMESCRIPT, "{DECIMAL}"),
+            (Dialect::NEWGRF, "{CURRENCY}{COMMA}"),
+            (Dialect::NEWGRF, "{CURRENCY}"),
+        ] {
+            let config = LanguageConfig {
+                dialect,
+                plural_count: 2,
+                ..Default::default()
+            };
+            let base = ParsedString::parse(string).unwrap();
+            assert!(
+                !validate_string(&config, &base, None)
+                    .iter()
+                    .any(|e| e.code == ErrorCode::MissingFollowingParameter),
+                "{}",
+                string
             );
         }
     }
 
     #[test]
-    fn test_validate_position_references() {
+    fn test_validate_trailing_space_command() {
         let config = LanguageConfig {
             dialect: Dialect::OPENTTD,
-            cases: vec![String::from("x"), String::from("y")],
-            genders: vec![String::from("a"), String::from("b")],
+            cases: vec![],
+            genders: vec![],
             plural_count: 2,
+            ..Default::default()
         };
-        let base = ParsedString::parse("{RED}{NUM}{STRING3}").unwrap();
-        let val_base = validate_string(&config, &base, None);
-        assert_eq!(val_base.len(), 0);
+        let base = ParsedString::parse("a{NBSP}b{NBSP}{RED}c").unwrap();
+        assert_eq!(validate_string(&config, &base, None), vec![]);
 
-        {
-            let trans = ParsedString::parse("{RED}{1:STRING.x}{0:NUM}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 0);
-        }
-        {
-            let trans = ParsedString::parse("{2:RED}{1:STRING.z}{0:NUM.x}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 3);
-            assert_eq!(
-                val_trans[0],
+        let base = ParsedString::parse("a{NBSP}{}b {NBSP} ").unwrap();
+        assert_eq!(
+            validate_string(&config, &base, None),
+            vec![
                 ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: Some(0),
+                    severity: Severity::Warning,
+                    code: ErrorCode::TrailingSpaceCommand,
+                    pos_begin: Some(1),
                     pos_end: Some(7),
-                    message: String::from("Command '{RED}' cannot have a position reference."),
-                    suggestion: Some(String::from("Remove '2:'.")),
-                }
-            );
-            assert_eq!(
-                val_trans[1],
-                ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: Some(7),
-                    pos_end: Some(19),
-                    message: String::from("Unknown case 'z'."),
-                    suggestion: Some(String::from("Known cases are: 'x', 'y'")),
-                }
-            );
-            assert_eq!(
-                val_trans[2],
-                ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: Some(19),
-                    pos_end: Some(28),
-                    message: String::from("No case selection allowed for '{NUM}'."),
-                    suggestion: Some(String::from("Remove '.x'.")),
-                }
-            );
-        }
-        {
-            let trans = ParsedString::parse("{RED}{NUM}{G i j}{P i j}{STRING.y}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 0);
-        }
-        {
-            let trans = ParsedString::parse("{RED}{NUM}{G 0 i j}{P 1 i j}{STRING.y}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 2);
-            assert_eq!(
-                val_trans[0],
-                ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: Some(10),
-                    pos_end: Some(19),
-                    message: String::from(
-                        "'{G}' references position '0:0', but '{0:NUM}' does not allow genders."
-                    ),
-                    suggestion: None,
-                }
-            );
-            assert_eq!(
-                val_trans[1],
-                ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: Some(19),
-                    pos_end: Some(28),
-                    message: String::from(
-                        "'{P}' references position '1:0', but '{1:STRING}' does not allow plurals."
-                    ),
-                    suggestion: None,
-                }
-            );
-        }
-        {
-            let trans = ParsedString::parse("{RED}{NUM}{G 1:1 i j}{P 1:3 i j}{STRING.y}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 0);
-        }
-        {
-            let trans = ParsedString::parse("{RED}{NUM}{G 1:4 i j}{P 1:4 i j}{STRING.y}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 2);
-            assert_eq!(
-                val_trans[0],
-                ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: Some(10),
-                    pos_end: Some(21),
-                    message: String::from(
-                        "'{G}' references position '1:4', but '{1:STRING}' only has 4 subindices."
-                    ),
-                    suggestion: None,
-                }
-            );
-            assert_eq!(
-                val_trans[1],
-                ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: Some(21),
-                    pos_end: Some(32),
-                    message: String::from(
-                        "'{P}' references position '1:4', but '{1:STRING}' only has 4 subindices."
-                    ),
-                    suggestion: None,
-                }
-            );
-        }
-        {
-            let trans = ParsedString::parse("{RED}{NUM}{G 2 i j}{P 2 i j}{STRING.y}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 2);
-            assert_eq!(
-                val_trans[0],
-                ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: Some(10),
-                    pos_end: Some(19),
-                    message: String::from("'{G}' references position '2', which has no parameter."),
-                    suggestion: None,
-                }
-            );
-            assert_eq!(
-                val_trans[1],
-                ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: Some(19),
-                    pos_end: Some(28),
-                    message: String::from("'{P}' references position '2', which has no parameter."),
-                    suggestion: None,
-                }
-            );
-        }
-        {
-            let trans = ParsedString::parse("{RED}{P i j}{NUM}{STRING.y}{G i j}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 2);
-            assert_eq!(
-                val_trans[0],
+                    message: String::from("'{NBSP}' at the end of the line has no visible effect."),
+                    suggestion: Some(String::from("Remove '{NBSP}'.")),
+                },
                 ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: Some(5),
-                    pos_end: Some(12),
+                    severity: Severity::Warning,
+                    code: ErrorCode::TrailingSpaceCommand,
+                    pos_begin: Some(11),
+                    pos_end: Some(17),
                     message: String::from(
-                        "'{P}' references position '-1', which has no parameter."
+                        "'{NBSP}' at the end of the string has no visible effect."
                     ),
-                    suggestion: Some(String::from("Add a position reference.")),
-                }
-            );
-            assert_eq!(
-                val_trans[1],
-                ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: Some(27),
-                    pos_end: Some(34),
-                    message: String::from("'{G}' references position '2', which has no parameter."),
-                    suggestion: Some(String::from("Add a position reference.")),
-                }
-            );
-        }
+                    suggestion: Some(String::from("Remove '{NBSP}'.")),
+                },
+            ]
+        );
+        // Custom commands can be space-like, too.
+        let config = LanguageConfig {
+            custom_commands: vec![CustomCommand {
+                name: String::from("THIN_SPACE"),
+                occurence: Occurence::ANY,
+                allow_case: false,
+                count_like: false,
+                sets_colour: false,
+                sets_font: false,
+                is_space_like: true,
+                requires_following: false,
+                parameters: vec![],
+            }],
+            ..config
+        };
+        let base = ParsedString::parse("a{THIN_SPACE}b{THIN_SPACE}").unwrap();
+        let val_base = validate_string(&config, &base, None);
+        assert_eq!(val_base.len(), 1);
+        assert_eq!(val_base[0].code, ErrorCode::TrailingSpaceCommand);
+        assert_eq!(val_base[0].pos_begin, Some(14));
     }
 
     #[test]
-    fn test_validate_nochoices() {
+    fn test_base_signature() {
         let config = LanguageConfig {
             dialect: Dialect::OPENTTD,
             cases: vec![],
             genders: vec![],
-            plural_count: 1,
+            plural_count: 2,
+            ..Default::default()
         };
-        let base = ParsedString::parse("{NUM}{STRING3}").unwrap();
-        let val_base = validate_string(&config, &base, None);
-        assert_eq!(val_base.len(), 0);
+        let par = |allow_plural, allow_gender| ParameterInfo {
+            allow_plural,
+            allow_gender,
+        };
+        assert_eq!(
+            base_signature(&config, "{NUM}{STRING}"),
+            Ok(vec![
+                SignatureParameter {
+                    position: 0,
+                    name: String::from("NUM"),
+                    allow_case: false,
+                    parameters: vec![par(true, false)],
+                },
+                SignatureParameter {
+                    position: 1,
+                    name: String::from("STRING"),
+                    allow_case: true,
+                    parameters: vec![par(false, true)],
+                },
+            ])
+        );
+        // '{STRING1}' is normalized to '{STRING}', but keeps its sub-parameters.
+        assert_eq!(
+            base_signature(&config, "{1:CARGO_LONG} {0:STRING1} {RED}"),
+            Ok(vec![
+                SignatureParameter {
+                    position: 0,
+                    name: String::from("STRING"),
+                    allow_case: true,
+                    parameters: vec![par(false, true), par(true, true)],
+                },
+                SignatureParameter {
+                    position: 1,
+                    name: String::from("CARGO_LONG"),
+                    allow_case: false,
+                    parameters: vec![par(false, true), par(true, false)],
+                },
+            ])
+        );
+        assert_eq!(
+            base_signature(&config, "{FOO}").map_err(|errs| errs[0].code),
+            Err(ErrorCode::UnknownCommand)
+        );
+    }
 
-        {
-            let trans = ParsedString::parse("{G=a}{NUM}{P a}{G a}{STRING}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 3);
-            assert_eq!(
-                val_trans[0],
-                ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: Some(0),
-                    pos_end: Some(5),
-                    message: String::from("No gender definitions allowed."),
-                    suggestion: Some(String::from("Remove '{G=...}'.")),
-                }
-            );
-            assert_eq!(
-                val_trans[1],
-                ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: Some(10),
-                    pos_end: Some(15),
-                    message: String::from("No plural choices allowed."),
-                    suggestion: Some(String::from("Remove '{P ...}'.")),
-                }
-            );
-            assert_eq!(
-                val_trans[2],
-                ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: Some(15),
-                    pos_end: Some(20),
-                    message: String::from("No gender choices allowed."),
-                    suggestion: Some(String::from("Remove '{G ...}'.")),
-                }
-            );
-        }
+    #[test]
+    fn test_signature_diff() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            ..Default::default()
+        };
+        assert_eq!(
+            signature_diff(
+                &config,
+                "{GREEN}{NUM} {RED}{STRING2}{RED}",
+                "{BLUE}{0:NUM} {RED}{2:STRING}"
+            ),
+            Ok(SignatureDiff {
+                added: vec![String::from("{2:STRING}"), String::from("{BLUE}")],
+                removed: vec![String::from("{1:STRING}"), String::from("{GREEN}")],
+                count_changed: vec![CountChange {
+                    command: String::from("{RED}"),
+                    base_count: 2,
+                    translation_count: 1,
+                }],
+            })
+        );
+        assert_eq!(
+            signature_diff(&config, "{NUM}", "{NUM}"),
+            Ok(SignatureDiff {
+                added: vec![],
+                removed: vec![],
+                count_changed: vec![],
+            })
+        );
+        assert_eq!(
+            signature_diff(&config, "{NUM}", "{FOO}")
+                .err()
+                .unwrap()
+                .code,
+            ErrorCode::UnknownCommand
+        );
+    }
+
+    #[test]
+    fn test_validate_config() {
+        let mut config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 6,
+            ..Default::default()
+        };
+        assert_eq!(validate_config(&config), vec![]);
+
+        config.plural_count = 20;
+        assert_eq!(
+            validate_config(&config),
+            vec![ValidationError {
+                severity: Severity::Warning,
+                code: ErrorCode::PluralCountLimit,
+                pos_begin: None,
+                pos_end: None,
+                message: String::from("Plural count 20 exceeds the maximum of 6 plural forms."),
+                suggestion: Some(String::from(
+                    "Check the plural count of the language configuration."
+                )),
+            }]
+        );
+
+        config.max_plural_count = Some(20);
+        assert_eq!(validate_config(&config), vec![]);
+
+        config.occurrence_overrides = HashMap::from([(String::from("FOO"), Occurence::ANY)]);
+        assert_eq!(validate_config(&config).len(), 1);
+
+        config.occurrence_overrides.clear();
+        config.case_plural_counts = HashMap::from([(String::from("gen"), 30)]);
+        assert_eq!(
+            validate_config(&config),
+            vec![ValidationError {
+                severity: Severity::Warning,
+                code: ErrorCode::PluralCountLimit,
+                pos_begin: None,
+                pos_end: None,
+                message: String::from(
+                    "Plural count 30 of case 'gen' exceeds the maximum of 20 plural forms."
+                ),
+                suggestion: Some(String::from(
+                    "Check the plural count of the language configuration."
+                )),
+            }]
+        );
     }
 
     #[test]
-    fn test_validate_gschoices() {
+    fn test_validate_case_plural_counts() {
         let config = LanguageConfig {
-            dialect: Dialect::GAMESCRIPT,
-            cases: vec![String::from("x"), String::from("y")],
-            genders: vec![String::from("a"), String::from("b")],
+            dialect: Dialect::OPENTTD,
+            cases: vec![String::from("gen")],
+            genders: vec![],
             plural_count: 2,
+            case_plural_counts: HashMap::from([(String::from("gen"), 3)]),
+            ..Default::default()
         };
-        let base = ParsedString::parse("{NUM}{STRING3}").unwrap();
-        let val_base = validate_string(&config, &base, None);
-        assert_eq!(val_base.len(), 0);
+        let base = String::from("{NUM} car{P \"\" s}");
+        let default_case = String::from("default");
+        let gen_case = String::from("gen");
 
-        {
-            let trans = ParsedString::parse("{G=a}{NUM}{P a b}{G a b}{STRING.x}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 3);
-            assert_eq!(
-                val_trans[0],
-                ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: Some(0),
-                    pos_end: Some(5),
-                    message: String::from("No gender definitions allowed."),
-                    suggestion: Some(String::from("Remove '{G=...}'.")),
-                }
-            );
-            assert_eq!(
-                val_trans[1],
-                ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: Some(17),
-                    pos_end: Some(24),
-                    message: String::from("No gender choices allowed."),
-                    suggestion: Some(String::from("Remove '{G ...}'.")),
-                }
-            );
-            assert_eq!(
-                val_trans[2],
-                ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: Some(24),
-                    pos_end: Some(34),
-                    message: String::from("No case selections allowed."),
-                    suggestion: Some(String::from("Remove '.x'.")),
-                }
-            );
-        }
+        let two = String::from("{NUM} Auto{P \"\" s}");
+        let three = String::from("{NUM} Auto{P \"\" s es}");
+
+        let result = validate_translation(&config, &base, &default_case, &two);
+        assert_eq!(result.errors, vec![]);
+        let result = validate_translation(&config, &base, &gen_case, &three);
+        assert_eq!(result.errors, vec![]);
+
+        let result = validate_translation(&config, &base, &default_case, &three);
+        assert_eq!(
+            result.errors,
+            vec![ValidationError {
+                severity: Severity::Error,
+                code: ErrorCode::PluralCountMismatch,
+                pos_begin: Some(18),
+                pos_end: Some(20),
+                message: String::from("Expected 2 plural choices, found 3."),
+                suggestion: None,
+            }]
+        );
+        let result = validate_translation(&config, &base, &gen_case, &two);
+        assert_eq!(
+            result.errors,
+            vec![ValidationError {
+                severity: Severity::Error,
+                code: ErrorCode::PluralCountMismatch,
+                pos_begin: Some(10),
+                pos_end: Some(18),
+                message: String::from("Expected 3 plural choices, found 2."),
+                suggestion: None,
+            }]
+        );
     }
 
     #[test]
-    fn test_validate_choices() {
+    fn test_coverage_report() {
         let config = LanguageConfig {
             dialect: Dialect::OPENTTD,
-            cases: vec![String::from("x"), String::from("y")],
-            genders: vec![String::from("a"), String::from("b")],
+            cases: vec![],
+            genders: vec![String::from("m"), String::from("f")],
             plural_count: 2,
+            ..Default::default()
         };
-        let base = ParsedString::parse("{NUM}{STRING3}").unwrap();
-        let val_base = validate_string(&config, &base, None);
-        assert_eq!(val_base.len(), 0);
+        let entry = |choice, pos, subindex, command, covered| CoverageEntry {
+            choice,
+            pos,
+            subindex,
+            command,
+            covered,
+        };
+        assert_eq!(
+            coverage_report(
+                &config,
+                "{COMPANY} {CARGO_LONG} {RED}{NUM}",
+                "{COMPANY} {G 1 a b} {CARGO_LONG} {P 1 a b} {NUM} {P a b}"
+            ),
+            Ok(CoverageReport {
+                parameters: vec![
+                    entry("G", 0, 0, "COMPANY", false),
+                    entry("G", 1, 0, "CARGO_LONG", true),
+                    entry("P", 1, 1, "CARGO_LONG", true),
+                    entry("P", 2, 0, "NUM", true),
+                ],
+            })
+        );
+        assert_eq!(
+            coverage_report(
+                &config,
+                "{STRING} {CARGO_LONG}",
+                "{STRING} {CARGO_LONG} {G 1:1 a b}"
+            ),
+            Ok(CoverageReport {
+                parameters: vec![
+                    entry("G", 0, 0, "STRING", false),
+                    entry("G", 1, 0, "CARGO_LONG", false),
+                    entry("P", 1, 1, "CARGO_LONG", false),
+                ],
+            })
+        );
+        assert!(coverage_report(&config, "{NUM}", "{NUM").is_err());
+        assert!(coverage_report(&config, "{FOO}", "{NUM}").is_err());
+    }
 
-        {
-            let trans = ParsedString::parse("{G=a}{NUM}{P a b}{G a b}{STRING.x}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 0);
-        }
-        {
-            let trans = ParsedString::parse("{G=c}{NUM}{P a b c}{G a b c}{STRING.z}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 4);
-            assert_eq!(
-                val_trans[0],
-                ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: Some(0),
-                    pos_end: Some(5),
-                    message: String::from("Unknown gender 'c'."),
-                    suggestion: Some(String::from("Known genders are: 'a', 'b'")),
-                }
-            );
-            assert_eq!(
-                val_trans[1],
-                ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: Some(10),
-                    pos_end: Some(19),
-                    message: String::from("Expected 2 plural choices, found 3."),
-                    suggestion: None,
-                }
-            );
-            assert_eq!(
-                val_trans[2],
-                ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: Some(19),
-                    pos_end: Some(28),
-                    message: String::from("Expected 2 gender choices, found 3."),
-                    suggestion: None,
-                }
-            );
-            assert_eq!(
-                val_trans[3],
-                ValidationError {
-                    severity: Severity::Error,
-                    pos_begin: Some(28),
-                    pos_end: Some(38),
-                    message: String::from("Unknown case 'z'."),
-                    suggestion: Some(String::from("Known cases are: 'x', 'y'")),
-                }
-            );
+    #[test]
+    fn test_validate_translation_batch() {
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![String::from("gen")],
+            genders: vec![],
+            plural_count: 2,
+            ..Default::default()
+        };
+        let base = String::from("{RED}{NUM} {P apple apples} of {STRING}");
+        let translations = vec![
+            (
+                String::from("default"),
+                String::from("{RED}{NUM} {P Apfel Äpfel} von {STRING}"),
+            ),
+            (
+                String::from("gen"),
+                String::from("{RED}{NUM} {P Apfels Äpfel} {STRING}  "),
+            ),
+            (String::from("dat"), String::from("{RED}{NUM} {STRING}")),
+            (
+                String::from("default"),
+                String::from("{BLUE}{NUM} {P a b c} {STRING}"),
+            ),
+            (String::from("default"), String::from("{NUM")),
+        ];
+        let batch = validate_translation_batch(&config, &base, &translations);
+        assert_eq!(batch.len(), translations.len());
+        for ((case, translation), result) in translations.iter().zip(&batch) {
+            let single = validate_translation(&config, &base, case, translation);
+            assert_eq!(result.errors, single.errors);
+            assert_eq!(result.normalized, single.normalized);
         }
+        assert!(batch[0].normalized.is_some());
+        assert!(batch[1].normalized.is_some());
+        assert!(batch[2].normalized.is_none());
+        assert!(batch[3].normalized.is_none());
+        assert!(batch[4].normalized.is_none());
+
+        let batch = validate_translation_batch(&config, "{FOO}", &translations[..2]);
+        assert_eq!(batch.len(), 2);
+        assert_eq!(batch[0].errors, vec![invalid_base_error()]);
+        assert_eq!(batch[1].errors, vec![invalid_base_error()]);
     }
 
     #[test]
-    fn test_validate_nonpositional() {
+    fn test_validate_dictionary() {
         let config = LanguageConfig {
             dialect: Dialect::OPENTTD,
             cases: vec![],
             genders: vec![],
-            plural_count: 0,
+            plural_count: 2,
+            ..Default::default()
         };
-        let base = ParsedString::parse("{RED}{NBSP}{}{GREEN}{NBSP}{}{RED}{TRAIN}").unwrap();
-        let val_base = validate_string(&config, &base, None);
-        assert_eq!(val_base.len(), 0);
+        let base_dict = HashMap::from([
+            (String::from("STR_A"), String::from("{NUM} apples")),
+            (String::from("STR_B"), String::from("{RED}Pear")),
+            (String::from("STR_C"), String::from("Cherry")),
+        ]);
+        let trans_dict = HashMap::from([
+            (String::from("STR_A"), String::from("{NUM} Äpfel")),
+            (String::from("STR_B"), String::from("{FOO}Birne")),
+            (String::from("STR_D"), String::from("Dattel")),
+        ]);
+        let results = validate_dictionary(&config, &base_dict, &trans_dict);
+        assert_eq!(results.len(), 4);
 
-        {
-            let trans = ParsedString::parse("{RED}{}{GREEN}{}{RED}{TRAIN}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 0);
-        }
-        {
-            let trans = ParsedString::parse("{RED}{}{GREEN}{NBSP}{RED}{NBSP}{GREEN}{}{RED}{TRAIN}")
-                .unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 0);
-        }
-        {
-            let trans =
-                ParsedString::parse("{RED}{}{RED}{TRAIN}{BLUE}{TRAIN}{RIGHT_ARROW}{SHIP}").unwrap();
-            let val_trans = validate_string(&config, &trans, Some(&base));
-            assert_eq!(val_trans.len(), 4);
-            assert_eq!(
-                val_trans[0],
-                ValidationError {
-                    severity: Severity::Warning,
-                    pos_begin: None,
-                    pos_end: None,
-                    message: String::from("String command '{GREEN}' is missing."),
-                    suggestion: None,
-                }
-            );
-            assert_eq!(
-                val_trans[1],
-                ValidationError {
-                    severity: Severity::Warning,
-                    pos_begin: None,
-                    pos_end: None,
-                    message: String::from(
-                        "String command '{TRAIN}': expected 1 times, found 2 times."
-                    ),
-                    suggestion: None,
-                }
-            );
-            assert_eq!(
-                val_trans[2],
-                ValidationError {
-                    severity: Severity::Warning,
-                    pos_begin: None,
-                    pos_end: None,
-                    message: String::from("String command '{BLUE}' is unexpected."),
-                    suggestion: Some(String::from("Remove this command.")),
-                }
-            );
-            assert_eq!(
-                val_trans[3],
-                ValidationError {
-                    severity: Severity::Warning,
-                    pos_begin: None,
-                    pos_end: None,
-                    message: String::from("String command '{SHIP}' is unexpected."),
-                    suggestion: Some(String::from("Remove this command.")),
-                }
-            );
-        }
+        let a = results.get("STR_A").unwrap();
+        assert_eq!(a.errors.len(), 0);
+        assert_eq!(a.normalized, Some(String::from("{0:NUM} Äpfel")));
+
+        let b = results.get("STR_B").unwrap();
+        assert_eq!(b.errors.len(), 2);
+        assert_eq!(b.normalized, None);
+
+        let c = results.get("STR_C").unwrap();
+        assert_eq!(
+            c.errors,
+            vec![ValidationError {
+                severity: Severity::Warning,
+                code: ErrorCode::MissingTranslation,
+                pos_begin: None,
+                pos_end: None,
+                message: String::from("Translation of 'STR_C' is missing."),
+                suggestion: None,
+            }]
+        );
+        assert_eq!(c.normalized, None);
+
+        let d = results.get("STR_D").unwrap();
+        assert_eq!(
+            d.errors,
+            vec![ValidationError {
+                severity: Severity::Warning,
+                code: ErrorCode::UnknownString,
+                pos_begin: None,
+                pos_end: None,
+                message: String::from("String 'STR_D' does not exist in the base language."),
+                suggestion: Some(String::from("Remove this translation.")),
+            }]
+        );
+        assert_eq!(d.normalized, None);
     }
 
     #[test]
     fn test_normalize_cmd() {
         let mut parsed =
             ParsedString::parse("{RED}{NBSP}{2:RAW_STRING}{0:STRING5}{COMMA}").unwrap();
-        normalize_string(&Dialect::OPENTTD, &mut parsed);
+        normalize_string(&Dialect::OPENTTD, &[], &mut parsed);
         let result = parsed.compile();
         assert_eq!(result, "{RED}{NBSP}{2:STRING}{0:STRING}{1:COMMA}");
     }
 
+    #[test]
+    fn test_normalize_comment() {
+        let mut parsed = ParsedString::parse("{#first}{NUM} {#second}{STRING}").unwrap();
+        normalize_string(&Dialect::OPENTTD, &[], &mut parsed);
+        let result = parsed.compile();
+        assert_eq!(result, "{0:NUM} {1:STRING}");
+    }
+
     #[test]
     fn test_normalize_ref() {
         let mut parsed = ParsedString::parse("{RED}{NBSP}{P a b}{2:STRING}{P 1 a b}{G 0:1 a b}{0:STRING}{G 0 a b}{P 0:1 a b}{COMMA}{P a b}{G a b}").unwrap();
-        normalize_string(&Dialect::OPENTTD, &mut parsed);
+        normalize_string(&Dialect::OPENTTD, &[], &mut parsed);
         let result = parsed.compile();
         assert_eq!(result, "{RED}{NBSP}{P a b}{2:STRING}{P 1 a b}{G 0:1 a b}{0:STRING}{G 0 a b}{P 0:1 a b}{1:COMMA}{P 1 a b}{G 2 a b}");
     }
 
+    #[test]
+    fn test_report_normalization() {
+        let mut config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![],
+            plural_count: 2,
+            ..Default::default()
+        };
+        let base = String::from("{NUM} train{P \"\" s}{#note} {1:STRING1}");
+        let res = validate_base(&config, &base);
+        assert_eq!(res.errors, vec![]);
+
+        config.report_normalization = true;
+        let res = validate_base(&config, &base);
+        assert_eq!(
+            res.normalized,
+            Some(String::from("{0:NUM} train{P 0 \"\" s} {1:STRING}"))
+        );
+        let info = |pos_begin, pos_end, message: &str| ValidationError {
+            severity: Severity::Info,
+            code: ErrorCode::Normalized,
+            pos_begin: Some(pos_begin),
+            pos_end: Some(pos_end),
+            message: String::from(message),
+            suggestion: None,
+        };
+        assert_eq!(
+            res.errors,
+            vec![
+                info(0, 5, "Added position index 0 to '{NUM}'."),
+                info(11, 19, "Added position reference 0 to '{P ...}'."),
+                info(19, 26, "Removed comment."),
+                info(27, 38, "Replaced '{STRING1}' with '{STRING}'."),
+            ]
+        );
+
+        let res = validate_base(&config, &String::from("{0:NUM} train{P 0 \"\" s}"));
+        assert_eq!(res.errors, vec![]);
+    }
+
+    #[test]
+    fn test_normalize_explicit_indices() {
+        // fully explicit translations round-trip unchanged, regardless of their order
+        for string in [
+            "{2:STRING}{0:NUM}{1:STRING}",
+            "{2:STRING} {G 2 a b} {0:NUM} {P 0 a b} {1:STRING} {P 0 c d}",
+            "{1:STRING}{G 0 a b}{0:STRING}{P 1 a b}",
+        ] {
+            let mut parsed = ParsedString::parse(string).unwrap();
+            normalize_string(&Dialect::OPENTTD, &[], &mut parsed);
+            assert_eq!(parsed.compile(), string);
+        }
+
+        let config = LanguageConfig {
+            dialect: Dialect::OPENTTD,
+            cases: vec![],
+            genders: vec![String::from("m"), String::from("f")],
+            plural_count: 2,
+            ..Default::default()
+        };
+        let base = String::from("{NUM} {STRING} {STRING}");
+        let translation = "{2:STRING} {G 2 a b} {0:NUM} {P 0 a b} {1:STRING}";
+        let res = validate_translation(&config, &base, "default", translation);
+        assert_eq!(res.errors, vec![]);
+        assert_eq!(res.normalized, Some(String::from(translation)));
+    }
+
     #[test]
     fn test_normalize_subref() {
         let mut parsed = ParsedString::parse(
             "{NUM}{P 0:0 a b}{G 1:0 a b}{G 1:1 a b}{STRING}{P 1:2 a b}{CARGO_LONG}{P 2:1 a b}",
         )
         .unwrap();
-        normalize_string(&Dialect::OPENTTD, &mut parsed);
+        normalize_string(&Dialect::OPENTTD, &[], &mut parsed);
         let result = parsed.compile();
         assert_eq!(
             result,
             "{0:NUM}{P 0 a b}{G 1 a b}{G 1:1 a b}{1:STRING}{P 1:2 a b}{2:CARGO_LONG}{P 2 a b}"
         );
     }
+
+    #[test]
+    fn test_normalize_subref_multiple_candidates() {
+        // '{CARGO_LONG}' has a single sub-parameter for plurals and genders each, so the default subindex is redundant.
+        let mut parsed =
+            ParsedString::parse("{CARGO_LONG}{P 0:1 a b}{G 0:0 a b}{P 0:0 a b}").unwrap();
+        normalize_string(&Dialect::OPENTTD, &[], &mut parsed);
+        assert_eq!(
+            parsed.compile(),
+            "{0:CARGO_LONG}{P 0 a b}{G 0 a b}{P 0:0 a b}"
+        );
+
+        // '{STRING2}' has multiple sub-parameters, which allow plurals and genders, so the subindex is kept.
+        let mut parsed =
+            ParsedString::parse("{STRING2}{G 0:0 a b}{G 0:1 a b}{P 0:1 a b}{NUM}{G 0 a b}")
+                .unwrap();
+        normalize_string(&Dialect::OPENTTD, &[], &mut parsed);
+        assert_eq!(
+            parsed.compile(),
+            "{0:STRING}{G 0:0 a b}{G 0:1 a b}{P 0:1 a b}{1:NUM}{G 0 a b}"
+        );
+    }
+
+    #[test]
+    fn test_normalize_subref_nonzero_default() {
+        // '{CARGO_LONG}' has the plural default at subindex 1, but genders always default to subindex 0.
+        let mut parsed = ParsedString::parse(
+            "{CARGO_LONG}{P 0:1 a b}{P 0:0 a b}{G 0:0 a b}{G 0:1 a b}{P 0 a b}{G 0 a b}",
+        )
+        .unwrap();
+        normalize_string(&Dialect::OPENTTD, &[], &mut parsed);
+        let result = parsed.compile();
+        assert_eq!(
+            result,
+            "{0:CARGO_LONG}{P 0 a b}{P 0:0 a b}{G 0 a b}{G 0:1 a b}{P 0 a b}{G 0 a b}"
+        );
+    }
 }
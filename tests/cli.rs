@@ -0,0 +1,135 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Run the binary with 'args' and 'input' on stdin, and return whether it succeeded and its stdout.
+fn run(args: &[&str], input: &str) -> (bool, String) {
+    run_bytes(args, input.as_bytes())
+}
+
+/// Run the binary with 'args' and raw 'input' on stdin, which need not be UTF-8.
+fn run_bytes(args: &[&str], input: &[u8]) -> (bool, String) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_nile-library"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(input).unwrap();
+    let output = child.wait_with_output().unwrap();
+    (
+        output.status.success(),
+        String::from_utf8(output.stdout).unwrap(),
+    )
+}
+
+#[test]
+fn test_base_from_stdin() {
+    let (success, stdout) = run(&["-"], "{NUM} item{P \"\" s}\n\n{FOO}\n");
+    assert!(!success);
+    assert_eq!(
+        stdout,
+        "1: NORMALIZED:{0:NUM} item{P 0 \"\" s}\n\
+         2: NORMALIZED:\n\
+         3: ERROR at position 0 to 5: Unknown string command '{FOO}'.\n"
+    );
+}
+
+#[test]
+fn test_base_from_stdin_json() {
+    let (success, stdout) = run(&["--format", "json", "-"], "{NUM}\n{FOO}\n");
+    assert!(!success);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0], r#"{"errors":[],"normalized":"{0:NUM}"}"#);
+    assert!(lines[1].contains(r#""code":"unknown-command""#));
+}
+
+#[test]
+fn test_list_commands() {
+    let (success, stdout) = run(&["--list-commands"], "");
+    assert!(success);
+    assert!(
+        stdout
+            .lines()
+            .any(|l| l
+                == "COMPANY params=true case=false gender=true plural=false occurrence=nonzero")
+    );
+    assert!(stdout
+        .lines()
+        .any(|l| l.starts_with("RAW_STRING ") && l.ends_with(" alias-of=STRING")));
+
+    let (success, stdout) = run(&["--list-commands", "--dialect", "newgrf"], "");
+    assert!(success);
+    assert!(!stdout.lines().any(|l| l.starts_with("RAW_STRING ")));
+    assert!(!stdout.lines().any(|l| l.starts_with("COMPANY ")));
+
+    let (success, stdout) = run(&["--list-commands", "--format", "json"], "");
+    assert!(success);
+    assert!(stdout.contains(r#""name":"COMPANY""#));
+}
+
+#[test]
+fn test_all_dialects() {
+    let (success, stdout) = run(&["--dialect", "all", "{RAW_STRING} {COMMA}"], "");
+    assert!(!success);
+    assert_eq!(
+        stdout,
+        "RAW_STRING game-script=yes newgrf=no openttd=yes\n\
+         COMMA game-script=yes newgrf=yes openttd=yes\n"
+    );
+
+    let (success, stdout) = run(&["--dialect", "all", "--format", "json", "{COMMA}"], "");
+    assert!(success);
+    assert_eq!(stdout, "[{\"command\":\"COMMA\",\"rejected_by\":[]}]\n");
+}
+
+#[test]
+fn test_min_severity() {
+    let (success, stdout) = run(&["{RED}{NUM} apples", "{NUM} Äpfel"], "");
+    assert!(!success);
+    assert_eq!(
+        stdout,
+        "WARNING: String command '{RED}' is missing.\n\
+         NORMALIZED:{0:NUM} Äpfel\n"
+    );
+
+    let args = [
+        "--min-severity",
+        "error",
+        "{RED}{NUM} apples",
+        "{NUM} Äpfel",
+    ];
+    let (success, stdout) = run(&args, "");
+    assert!(success);
+    assert_eq!(stdout, "NORMALIZED:{0:NUM} Äpfel\n");
+
+    let args = ["--no-normalize", "{RED}{NUM} apples", "{RED}{NUM} Äpfel"];
+    let (success, stdout) = run(&args, "");
+    assert!(success);
+    assert_eq!(stdout, "");
+}
+
+#[test]
+fn test_stdin_min_severity() {
+    let args = ["--stdin", "{RED}{NUM} apples"];
+    let (success, stdout) = run(&args, "{NUM} Äpfel\n\n");
+    assert!(!success);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].contains(r#""severity":"warning""#));
+    assert_eq!(lines[1], "null");
+
+    let args = [
+        "--stdin",
+        "--min-severity",
+        "error",
+        "--no-normalize",
+        "{RED}{NUM} apples",
+    ];
+    let (success, stdout) = run(&args, "{NUM} Äpfel\n");
+    assert!(success);
+    assert_eq!(stdout, "{\"errors\":[],\"normalized\":null}\n");
+
+    let (success, _) = run_bytes(&["--stdin"], b"{NUM}\n\xff\n");
+    assert!(!success);
+}
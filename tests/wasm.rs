@@ -0,0 +1,79 @@
+#![cfg(target_arch = "wasm32")]
+
+use nile_library::validate::{ErrorCode, Severity, ValidationResult};
+use serde::Serialize;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::*;
+
+fn js_config(config: serde_json::Value) -> JsValue {
+    config
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .unwrap()
+}
+
+#[wasm_bindgen_test]
+fn test_validate_base_missing_plural_count() {
+    let config = js_config(serde_json::json!({
+        "dialect": "openttd",
+        "cases": [],
+        "genders": [],
+    }));
+    let response = nile_library::validate_base(config, String::from("{NUM} train"));
+    let result: ValidationResult = serde_wasm_bindgen::from_value(response).unwrap();
+    assert_eq!(result.normalized, None);
+    assert_eq!(result.errors.len(), 1);
+    assert_eq!(result.errors[0].severity, Severity::Error);
+    assert_eq!(result.errors[0].code, ErrorCode::InvalidConfig);
+    assert!(result.errors[0].message.contains("plural_count"));
+}
+
+#[wasm_bindgen_test]
+fn test_validate_translation_missing_plural_count() {
+    let config = js_config(serde_json::json!({
+        "dialect": "openttd",
+        "cases": ["default"],
+        "genders": [],
+    }));
+    let response = nile_library::validate_translation(
+        config,
+        String::from("{NUM} train"),
+        String::from("default"),
+        String::from("{NUM} Zug"),
+    );
+    let result: ValidationResult = serde_wasm_bindgen::from_value(response).unwrap();
+    assert_eq!(result.errors.len(), 1);
+    assert_eq!(result.errors[0].code, ErrorCode::InvalidConfig);
+}
+
+#[wasm_bindgen_test]
+fn test_validate_translation_batch_missing_plural_count() {
+    let config = js_config(serde_json::json!({
+        "dialect": "openttd",
+        "cases": [],
+        "genders": [],
+    }));
+    let translations = js_config(serde_json::json!([["default", "{NUM} Zug"]]));
+    let response =
+        nile_library::validate_translation_batch(config, String::from("{NUM} train"), translations);
+    let result: ValidationResult = serde_wasm_bindgen::from_value(response).unwrap();
+    assert_eq!(result.errors.len(), 1);
+    assert_eq!(result.errors[0].code, ErrorCode::InvalidConfig);
+}
+
+#[wasm_bindgen_test]
+fn test_normalize_batch_invalid_strings() {
+    let config = js_config(serde_json::json!({
+        "dialect": "openttd",
+        "cases": [],
+        "genders": [],
+        "plural_count": 2,
+    }));
+    let strings = js_config(serde_json::json!("{NUM} train"));
+    let response = nile_library::normalize_batch(config, strings);
+    let result: ValidationResult = serde_wasm_bindgen::from_value(response).unwrap();
+    assert_eq!(result.errors.len(), 1);
+    assert_eq!(result.errors[0].code, ErrorCode::InvalidArgument);
+    assert!(result.errors[0]
+        .message
+        .starts_with("Invalid list of strings: "));
+}